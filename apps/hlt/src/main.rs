@@ -0,0 +1,22 @@
+#![no_std]
+#![no_main]
+
+use core::panic::PanicInfo;
+
+/// Waits forever until killed.
+///
+/// `hlt` is privileged in ring 3, so this spins and lets the scheduler preempt it.
+#[no_mangle]
+pub extern "sysv64" fn entry_point() -> ! {
+    loop {
+        core::hint::spin_loop();
+    }
+}
+
+
+#[panic_handler]
+fn panic(_info: &PanicInfo) -> ! {
+    loop {
+        core::hint::spin_loop();
+    }
+}
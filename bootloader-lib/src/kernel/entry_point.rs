@@ -23,6 +23,7 @@ impl EntryPoint {
         memory_map: &MemoryMapIter,
         rsdp: &Option<*const c_void>,
         fat_volume: *mut u8,
+        kernel_elf: &'static [u8],
     ) {
         let entry_point_ptr = *self.0 as *const ();
         let entry_point: extern "sysv64" fn(
@@ -30,9 +31,18 @@ impl EntryPoint {
             memory_map: &MemoryMapIter,
             rsdp: &Option<*const c_void>,
             fat_volume: *mut u8,
+            kernel_elf: *const u8,
+            kernel_elf_len: usize,
         ) -> () = unsafe { core::mem::transmute(entry_point_ptr) };
 
-        entry_point(frame_buffer_config, memory_map, rsdp, fat_volume);
+        entry_point(
+            frame_buffer_config,
+            memory_map,
+            rsdp,
+            fat_volume,
+            kernel_elf.as_ptr(),
+            kernel_elf.len(),
+        );
     }
 }
 
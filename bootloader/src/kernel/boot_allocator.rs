@@ -15,7 +15,7 @@ impl<'a> BootAllocator<'a> {
 
 
 impl Allocatable for BootAllocator<'_> {
-    fn copy_mem(&self, dest: *mut u8, src: *const u8, size: usize) {
+    fn copy_mem(&mut self, dest: *mut u8, src: *const u8, size: usize) {
         unsafe {
             self.0
                 .boot_services()
@@ -15,15 +15,20 @@ use common_lib::loader::{Allocatable, ExecuteFileLoadable};
 
 use crate::gop::{obtain_frame_buffer_config, open_gop};
 
+/// Loads the kernel and returns its entry point with the whole ELF file.
+///
+/// The file is never freed so that the kernel can read its symbol table for backtraces.
 pub fn load_kernel(
     fs: &mut FileSystem,
     kernel_file_path: &str,
     allocator: &mut impl Allocatable,
-) -> BootLoaderResult<EntryPoint> {
-    let mut kernel_buff = fs.read(Path::new(&CString16::try_from(kernel_file_path).unwrap()))?;
-    let entry_point_addr = ElfLoader::new().load(kernel_buff.as_mut_slice(), allocator)?;
+) -> BootLoaderResult<(EntryPoint, &'static [u8])> {
+    let kernel_buff = fs
+        .read(Path::new(&CString16::try_from(kernel_file_path).unwrap()))?
+        .leak();
+    let entry_point_addr = ElfLoader::new().load(kernel_buff, allocator)?;
 
-    Ok(EntryPoint::new(entry_point_addr))
+    Ok((EntryPoint::new(entry_point_addr), kernel_buff))
 }
 
 
@@ -31,6 +36,7 @@ pub fn execute_kernel(
     entry_point: EntryPoint,
     system_table: SystemTable<Boot>,
     fat_volume: *mut u8,
+    kernel_elf: &'static [u8],
 ) -> Result<(), ()> {
     let memory_map_vec = new_memory_map_vec(&system_table);
     let frame_buffer_config = obtain_frame_buffer_config(&mut open_gop(&system_table).unwrap());
@@ -42,6 +48,7 @@ pub fn execute_kernel(
         &memory_map.entries(),
         &rsdp_ptr,
         fat_volume,
+        kernel_elf,
     );
     core::mem::forget(memory_map_vec);
     Ok(())
@@ -34,7 +34,7 @@ fn main(handle: Handle, mut system_table: SystemTable<Boot>) -> Status {
         .read(Path::new(&CString16::try_from("fat_disk").unwrap()))
         .unwrap();
 
-    let entry_point = kernel::process::load_kernel(
+    let (entry_point, kernel_elf) = kernel::process::load_kernel(
         &mut open_file_system(handle, unsafe { &system_table.unsafe_clone() }).unwrap(),
         "kernel.elf",
         &mut BootAllocator::new(&mut system_table),
@@ -42,7 +42,13 @@ fn main(handle: Handle, mut system_table: SystemTable<Boot>) -> Status {
     .unwrap();
 
 
-    kernel::process::execute_kernel(entry_point, system_table, disk_buff.as_mut_ptr()).unwrap();
+    kernel::process::execute_kernel(
+        entry_point,
+        system_table,
+        disk_buff.as_mut_ptr(),
+        kernel_elf,
+    )
+    .unwrap();
 
     common_lib::assembly::hlt_forever();
 
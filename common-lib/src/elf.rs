@@ -1,5 +1,7 @@
 pub mod ehdr;
 pub mod phdr;
+pub mod shdr;
+pub mod sym;
 
 
 /// プログラムアドレス(64bitアーキテクチャ)
@@ -27,6 +27,11 @@ impl ElfHeaderPtr {
     }
 
 
+    pub fn entry_point(&self) -> u64 {
+        unsafe { *self.0 }.e_entry
+    }
+
+
     pub fn segment_at(&self, p_offset: u64) -> *const u8 {
         unsafe {
             self.0
@@ -0,0 +1 @@
+pub mod section_header;
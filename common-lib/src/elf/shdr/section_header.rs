@@ -0,0 +1,48 @@
+use crate::elf::{Elf64Addr, Elf64Off};
+
+/// シンボルテーブル
+pub const SHT_SYMTAB: u32 = 2;
+
+/// 文字列テーブル
+pub const SHT_STRTAB: u32 = 3;
+
+
+/// ELFファイルのセクションヘッダ
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+pub struct SectionHeader {
+    /// セクション名の文字列テーブル内のオフセット
+    pub sh_name: u32,
+
+    /// セクションの種類
+    /// 未知の値も含まれるため、列挙体ではなく`SHT_*`の定数と比較します。
+    pub sh_type: u32,
+    pub sh_flags: u64,
+
+    /// メモリ上に展開されるセクションの場合、そのアドレス
+    pub sh_addr: Elf64Addr,
+
+    /// ファイル先頭からのオフセット
+    pub sh_offset: Elf64Off,
+    pub sh_size: u64,
+
+    /// 関連するセクションのインデックス
+    /// シンボルテーブルの場合、シンボル名の文字列テーブルを指します。
+    pub sh_link: u32,
+    pub sh_info: u32,
+    pub sh_addralign: u64,
+
+    /// テーブルを持つセクションの場合、エントリのサイズ
+    pub sh_entsize: u64,
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::elf::shdr::section_header::SectionHeader;
+
+    #[test]
+    fn it_size() {
+        assert_eq!(core::mem::size_of::<SectionHeader>(), 0x40);
+    }
+}
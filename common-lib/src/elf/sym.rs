@@ -0,0 +1,3 @@
+pub mod demangle;
+pub mod symbol;
+pub mod symbol_table;
@@ -0,0 +1,167 @@
+use core::fmt::{Display, Formatter, Write};
+
+/// Rustのlegacy形式でマングリングされたシンボル名を、`crate::module::function`の形式で表示します。
+/// 解釈できない名前は、そのまま表示します。
+#[derive(Debug, Copy, Clone)]
+pub struct Demangle<'a>(pub &'a str);
+
+
+impl Display for Demangle<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        let Some(path) = legacy_path(self.0) else {
+            return f.write_str(self.0);
+        };
+
+        let mut rest = path;
+        let mut first = true;
+        while let Some((ident, next)) = split_ident(rest) {
+            rest = next;
+            // 末尾のハッシュは省略します。
+            if rest.starts_with('E') && is_hash(ident) {
+                break;
+            }
+            if !first {
+                f.write_str("::")?;
+            }
+            first = false;
+
+            write_ident(ident, f)?;
+        }
+
+        Ok(())
+    }
+}
+
+
+/// `_ZN`と`E`の間にある、長さと識別子の組の列を返します。
+fn legacy_path(name: &str) -> Option<&str> {
+    let path = name.strip_prefix("_ZN")?;
+
+    let mut rest = path;
+    while !rest.starts_with('E') {
+        rest = split_ident(rest)?.1;
+    }
+    if rest.len() != 1 && !rest[1..].starts_with('.') {
+        return None;
+    }
+
+    Some(path)
+}
+
+
+fn split_ident(s: &str) -> Option<(&str, &str)> {
+    let digits = s
+        .bytes()
+        .take_while(u8::is_ascii_digit)
+        .count();
+    let len: usize = s[..digits].parse().ok()?;
+    let rest = &s[digits..];
+
+    Some((rest.get(..len)?, rest.get(len..)?))
+}
+
+
+fn is_hash(ident: &str) -> bool {
+    ident.len() == 17
+        && ident.starts_with('h')
+        && ident[1..]
+            .bytes()
+            .all(|b| b.is_ascii_hexdigit())
+}
+
+
+fn write_ident(ident: &str, f: &mut Formatter<'_>) -> core::fmt::Result {
+    let mut rest = if ident.starts_with("_$") { &ident[1..] } else { ident };
+
+    while !rest.is_empty() {
+        if let Some(next) = rest.strip_prefix("..") {
+            f.write_str("::")?;
+            rest = next;
+        } else if let Some((escape, next)) = rest
+            .strip_prefix('$')
+            .and_then(|s| s.split_once('$'))
+        {
+            match unescape(escape) {
+                Some(c) => f.write_char(c)?,
+                None => write!(f, "${escape}$")?,
+            }
+            rest = next;
+        } else {
+            let len = rest[1..]
+                .find(['$', '.'])
+                .map_or(rest.len(), |i| i + 1);
+            f.write_str(&rest[..len])?;
+            rest = &rest[len..];
+        }
+    }
+
+    Ok(())
+}
+
+
+fn unescape(escape: &str) -> Option<char> {
+    match escape {
+        "SP" => Some('@'),
+        "BP" => Some('*'),
+        "RF" => Some('&'),
+        "LT" => Some('<'),
+        "GT" => Some('>'),
+        "LP" => Some('('),
+        "RP" => Some(')'),
+        "C" => Some(','),
+        _ => {
+            let code = u32::from_str_radix(escape.strip_prefix('u')?, 16).ok()?;
+            char::from_u32(code)
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use alloc::format;
+
+    use crate::elf::sym::demangle::Demangle;
+
+    #[test]
+    fn it_demangle_path() {
+        let name = "_ZN10kernel_lib6layers6Layers12update_layer17h0123456789abcdefE";
+
+        assert_eq!(format!("{}", Demangle(name)), "kernel_lib::layers::Layers::update_layer");
+    }
+
+
+    #[test]
+    fn it_demangle_escapes() {
+        let name = concat!(
+            "_ZN4core3ptr46drop_in_place$LT$alloc..vec..Vec$LT$u8$GT$$GT$",
+            "17h0123456789abcdefE"
+        );
+
+        assert_eq!(
+            format!("{}", Demangle(name)),
+            "core::ptr::drop_in_place<alloc::vec::Vec<u8>>"
+        );
+    }
+
+
+    #[test]
+    fn it_demangle_trait_impl() {
+        let name = concat!(
+            "_ZN50_$LT$kernel..Foo$u20$as$u20$core..fmt..Display$GT$3fmt",
+            "17h0123456789abcdefE"
+        );
+
+        assert_eq!(
+            format!("{}", Demangle(name)),
+            "<kernel::Foo as core::fmt::Display>::fmt"
+        );
+    }
+
+
+    #[test]
+    fn it_keep_unmangled_name() {
+        assert_eq!(format!("{}", Demangle("kernel_main")), "kernel_main");
+        assert_eq!(format!("{}", Demangle("_ZN3foo")), "_ZN3foo");
+    }
+}
@@ -0,0 +1,68 @@
+use crate::elf::Elf64Addr;
+
+/// 関数
+const STT_FUNC: u8 = 2;
+
+
+/// シンボルテーブルのエントリ
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+pub struct Symbol {
+    /// シンボル名の文字列テーブル内のオフセット
+    pub st_name: u32,
+
+    /// 下位4bitが種類、上位4bitがバインド属性を表します。
+    pub st_info: u8,
+    pub st_other: u8,
+
+    /// シンボルが定義されているセクションのインデックス
+    pub st_shndx: u16,
+    pub st_value: Elf64Addr,
+    pub st_size: u64,
+}
+
+
+impl Symbol {
+    #[inline]
+    pub const fn is_function(&self) -> bool {
+        self.st_info & 0x0F == STT_FUNC
+    }
+
+
+    /// `addr`がシンボルの範囲内にあるかを返します。
+    /// サイズが0のシンボルは、自身のアドレスのみを含むとみなします。
+    #[inline]
+    pub const fn contains(&self, addr: Elf64Addr) -> bool {
+        let size = if self.st_size == 0 { 1 } else { self.st_size };
+        self.st_value <= addr && addr - self.st_value < size
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::elf::sym::symbol::Symbol;
+
+    #[test]
+    fn it_size() {
+        assert_eq!(core::mem::size_of::<Symbol>(), 0x18);
+    }
+
+
+    #[test]
+    fn it_contains() {
+        let symbol = Symbol {
+            st_name: 0,
+            st_info: 0x12,
+            st_other: 0,
+            st_shndx: 1,
+            st_value: 0x1000,
+            st_size: 0x10,
+        };
+
+        assert!(symbol.is_function());
+        assert!(symbol.contains(0x1000));
+        assert!(symbol.contains(0x100F));
+        assert!(!symbol.contains(0x1010));
+    }
+}
@@ -0,0 +1,131 @@
+use core::mem::{align_of, size_of};
+
+use crate::elf::ehdr::elf_header::ElfHeader;
+use crate::elf::shdr::section_header::{SectionHeader, SHT_STRTAB, SHT_SYMTAB};
+use crate::elf::sym::symbol::Symbol;
+
+const ELF_MAGIC: [u8; 4] = [0x7F, b'E', b'L', b'F'];
+
+
+/// ELFファイルの`.symtab`から、アドレスを含む関数を探します。
+#[derive(Debug, Copy, Clone)]
+pub struct SymbolTable<'a> {
+    symbols: &'a [Symbol],
+    names: &'a [u8],
+}
+
+
+impl<'a> SymbolTable<'a> {
+    /// ELFファイル全体から`.symtab`と、そのシンボル名の文字列テーブルを読み込みます。
+    /// シンボルテーブルを持たない場合や、ファイルが壊れている場合はNoneを返します。
+    pub fn from_elf(file: &'a [u8]) -> Option<Self> {
+        if !file.starts_with(&ELF_MAGIC) {
+            return None;
+        }
+        let ehdr = slice_at::<ElfHeader>(file, 0, 1)?[0];
+
+        let sections = slice_at::<SectionHeader>(file, ehdr.e_shoff, ehdr.e_shnum as u64)?;
+        let symtab = sections
+            .iter()
+            .find(|section| section.sh_type == SHT_SYMTAB)?;
+        let strtab = sections.get(symtab.sh_link as usize)?;
+        if strtab.sh_type != SHT_STRTAB {
+            return None;
+        }
+
+        let symbol_count = symtab.sh_size / size_of::<Symbol>() as u64;
+        Some(Self {
+            symbols: slice_at::<Symbol>(file, symtab.sh_offset, symbol_count)?,
+            names: slice_at::<u8>(file, strtab.sh_offset, strtab.sh_size)?,
+        })
+    }
+
+
+    /// シンボルの数を返します。
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.symbols.len()
+    }
+
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.symbols.is_empty()
+    }
+
+
+    /// `addr`を含む関数のシンボル名と、関数の先頭からのオフセットを返します。
+    pub fn find(&self, addr: u64) -> Option<(&'a str, u64)> {
+        let symbol = self
+            .symbols
+            .iter()
+            .filter(|symbol| symbol.is_function() && symbol.contains(addr))
+            .max_by_key(|symbol| symbol.st_value)?;
+
+        Some((self.name(symbol)?, addr - symbol.st_value))
+    }
+
+
+    fn name(&self, symbol: &Symbol) -> Option<&'a str> {
+        let name = self
+            .names
+            .get(symbol.st_name as usize..)?;
+        let len = name
+            .iter()
+            .position(|b| *b == 0)?;
+
+        core::str::from_utf8(&name[..len]).ok()
+    }
+}
+
+
+/// ファイル内の`offset`から始まる`count`個の`T`を参照します。
+/// 範囲がファイルを超える場合や、アラインメントが合わない場合はNoneを返します。
+fn slice_at<T>(file: &[u8], offset: u64, count: u64) -> Option<&[T]> {
+    let len = (count as usize).checked_mul(size_of::<T>())?;
+    let bytes = file.get(offset as usize..(offset as usize).checked_add(len)?)?;
+    if bytes.as_ptr() as usize % align_of::<T>() != 0 {
+        return None;
+    }
+
+    Some(unsafe { core::slice::from_raw_parts(bytes.as_ptr().cast::<T>(), count as usize) })
+}
+
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use crate::elf::sym::symbol_table::SymbolTable;
+
+    fn load_kernel_elf() -> Vec<u8> {
+        let path = env!("CARGO_MANIFEST_DIR");
+        std::fs::read(alloc::format!("{}/resources/test/kernel.elf", path)).unwrap()
+    }
+
+
+    #[test]
+    fn it_read_symtab() {
+        let file = load_kernel_elf();
+        let symbols = SymbolTable::from_elf(&file).unwrap();
+
+        assert_eq!(symbols.len(), 3);
+    }
+
+
+    #[test]
+    fn it_find_function() {
+        let file = load_kernel_elf();
+        let symbols = SymbolTable::from_elf(&file).unwrap();
+
+        assert_eq!(symbols.find(0x101000), Some(("_start", 0)));
+        assert_eq!(symbols.find(0x101005), Some(("_start", 5)));
+        assert_eq!(symbols.find(0x101006), None);
+    }
+
+
+    #[test]
+    fn it_fail_if_not_elf() {
+        assert!(SymbolTable::from_elf(&[0; 0x40]).is_none());
+    }
+}
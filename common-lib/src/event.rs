@@ -0,0 +1,59 @@
+//! Events delivered to tasks, shared with applications through `read_event`.
+
+pub const MOUSE_BUTTON_LEFT: u8 = 0x01;
+pub const MOUSE_BUTTON_RIGHT: u8 = 0x02;
+pub const MOUSE_BUTTON_MIDDLE: u8 = 0x04;
+
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[repr(C, u32)]
+pub enum Key {
+    ArrowUp,
+    ArrowDown,
+    Ascii(char),
+}
+
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[repr(C)]
+pub struct KeyEvent {
+    pub modifier_bits: u8,
+    pub key: Key,
+}
+
+
+impl KeyEvent {
+    #[inline]
+    pub const fn new(modifier_bits: u8, key: Key) -> Self {
+        Self { modifier_bits, key }
+    }
+}
+
+
+/// Sent when a mouse button is pressed or released.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[repr(C)]
+pub struct MouseEvent {
+    /// The cursor position on the screen.
+    pub x: usize,
+    pub y: usize,
+    /// The buttons held down, as `MOUSE_BUTTON_*` bits.
+    pub buttons: u8,
+}
+
+
+impl MouseEvent {
+    #[inline]
+    pub const fn new(x: usize, y: usize, buttons: u8) -> Self {
+        Self { x, y, buttons }
+    }
+}
+
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[repr(C, u64)]
+pub enum AppEvent {
+    Key(KeyEvent),
+    Mouse(MouseEvent),
+    TimerExpired { timer_id: u64 },
+}
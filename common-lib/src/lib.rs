@@ -7,6 +7,7 @@ pub mod array;
 pub mod assembly;
 pub mod elf;
 pub mod error;
+pub mod event;
 pub mod frame_buffer;
 pub mod iter;
 pub mod loader;
@@ -15,4 +16,5 @@ pub mod nums;
 pub mod physical_address;
 pub mod queue;
 pub mod repeat;
+pub mod syscall;
 pub mod transform;
@@ -40,9 +40,8 @@ impl ExecuteFileLoadable for ElfLoader {
         let phdr_table = ehdr.phdr_table();
 
         copy_load_segments(&ehdr, phdr_table, allocator);
-        let entry_point_addr_ptr = (load_segment_start_addr + 24) as *const u64;
-        let entry_point_addr = unsafe { *entry_point_addr_ptr };
-        Ok(EntryPointAddr::new(entry_point_addr))
+        // Read from the file, since the allocator may have failed to map the loaded header.
+        Ok(EntryPointAddr::new(ehdr.entry_point()))
     }
 }
 
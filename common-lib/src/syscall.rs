@@ -0,0 +1,309 @@
+//! The system call ABI shared between the kernel and applications.
+//!
+//! Applications issue `syscall` with the number in `rax` and
+//! the arguments in `rdi`, `rsi`, `rdx`, `r10`, `r8` and `r9`.
+//! The kernel returns the value in `rax` and the error code in `rdx`,
+//! where `0` means success.
+
+use core::arch::asm;
+
+use crate::event::AppEvent;
+
+pub const STDIN: u64 = 0;
+pub const STDOUT: u64 = 1;
+pub const STDERR: u64 = 2;
+
+pub const OPEN_READ: u64 = 0x01;
+pub const OPEN_WRITE: u64 = 0x02;
+/// Creates the file if it doesn't exist. Requires [`OPEN_WRITE`].
+pub const OPEN_CREATE: u64 = 0x04;
+/// Empties the file when opened. Requires [`OPEN_WRITE`].
+pub const OPEN_TRUNCATE: u64 = 0x08;
+
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[repr(u64)]
+pub enum Syscall {
+    /// `write(fd, buff, len) -> written bytes`
+    Write = 0,
+
+    /// `open(path, path_len, flags) -> fd`
+    Open = 1,
+
+    /// `read(fd, buff, len) -> read bytes`
+    Read = 2,
+
+    /// `close(fd)`
+    Close = 3,
+
+    /// `exit(code)`, never returns.
+    Exit = 4,
+
+    /// `get_time() -> milliseconds since boot`
+    GetTime = 5,
+
+    /// `sleep(milliseconds)`
+    Sleep = 6,
+
+    /// `open_window(x, y, width, height, title, title_len) -> window id`
+    OpenWindow = 7,
+
+    /// `read_event(events, len) -> read events`, blocks until an event arrives.
+    ReadEvent = 8,
+}
+
+
+impl Syscall {
+    pub const COUNT: usize = 9;
+
+
+    pub const fn from_number(number: u64) -> Option<Self> {
+        match number {
+            0 => Some(Self::Write),
+            1 => Some(Self::Open),
+            2 => Some(Self::Read),
+            3 => Some(Self::Close),
+            4 => Some(Self::Exit),
+            5 => Some(Self::GetTime),
+            6 => Some(Self::Sleep),
+            7 => Some(Self::OpenWindow),
+            8 => Some(Self::ReadEvent),
+            _ => None,
+        }
+    }
+}
+
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[repr(u64)]
+pub enum SyscallError {
+    NotSupported = 1,
+    InvalidArgument = 2,
+    NotFound = 3,
+    BadDescriptor = 4,
+    Failed = 5,
+}
+
+
+impl SyscallError {
+    pub const fn from_code(code: u64) -> Self {
+        match code {
+            1 => Self::NotSupported,
+            2 => Self::InvalidArgument,
+            3 => Self::NotFound,
+            4 => Self::BadDescriptor,
+            _ => Self::Failed,
+        }
+    }
+}
+
+
+/// The arguments saved by the kernel's entry point, in the order of the registers.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[repr(C)]
+pub struct SyscallArgs {
+    pub number: u64,
+    pub args: [u64; 6],
+}
+
+
+/// Returned in `rax` and `rdx`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+#[repr(C)]
+pub struct SyscallResult {
+    pub value: u64,
+    pub error: u64,
+}
+
+
+impl SyscallResult {
+    #[inline]
+    pub const fn ok(value: u64) -> Self {
+        Self { value, error: 0 }
+    }
+
+
+    #[inline]
+    pub const fn err(error: SyscallError) -> Self {
+        Self {
+            value: 0,
+            error: error as u64,
+        }
+    }
+
+
+    #[inline]
+    pub const fn into_result(self) -> Result<u64, SyscallError> {
+        if self.error == 0 {
+            Ok(self.value)
+        } else {
+            Err(SyscallError::from_code(self.error))
+        }
+    }
+}
+
+
+impl From<Result<u64, SyscallError>> for SyscallResult {
+    fn from(result: Result<u64, SyscallError>) -> Self {
+        match result {
+            Ok(value) => Self::ok(value),
+            Err(error) => Self::err(error),
+        }
+    }
+}
+
+
+/// Issues the system call.
+///
+/// # Safety
+///
+/// Pointers passed as arguments must be valid for the call.
+#[inline]
+pub unsafe fn syscall(syscall: Syscall, args: [u64; 6]) -> SyscallResult {
+    let value: u64;
+    let error: u64;
+    asm!(
+        "syscall",
+        inlateout("rax") syscall as u64 => value,
+        inlateout("rdi") args[0] => _,
+        inlateout("rsi") args[1] => _,
+        inlateout("rdx") args[2] => error,
+        inlateout("r10") args[3] => _,
+        inlateout("r8") args[4] => _,
+        inlateout("r9") args[5] => _,
+        lateout("rcx") _,
+        lateout("r11") _,
+        clobber_abi("sysv64"),
+        options(nostack)
+    );
+
+    SyscallResult { value, error }
+}
+
+
+pub fn write(fd: u64, buff: &[u8]) -> Result<usize, SyscallError> {
+    let result = unsafe {
+        syscall(
+            Syscall::Write,
+            [fd, buff.as_ptr() as u64, buff.len() as u64, 0, 0, 0],
+        )
+    };
+
+    result
+        .into_result()
+        .map(|written| written as usize)
+}
+
+
+pub fn open(path: &str, flags: u64) -> Result<u64, SyscallError> {
+    unsafe {
+        syscall(
+            Syscall::Open,
+            [path.as_ptr() as u64, path.len() as u64, flags, 0, 0, 0],
+        )
+        .into_result()
+    }
+}
+
+
+pub fn read(fd: u64, buff: &mut [u8]) -> Result<usize, SyscallError> {
+    let result = unsafe {
+        syscall(
+            Syscall::Read,
+            [fd, buff.as_mut_ptr() as u64, buff.len() as u64, 0, 0, 0],
+        )
+    };
+
+    result
+        .into_result()
+        .map(|read| read as usize)
+}
+
+
+pub fn close(fd: u64) -> Result<(), SyscallError> {
+    unsafe {
+        syscall(Syscall::Close, [fd, 0, 0, 0, 0, 0])
+            .into_result()
+            .map(|_| ())
+    }
+}
+
+
+pub fn exit(code: i32) -> ! {
+    unsafe {
+        syscall(Syscall::Exit, [code as u64, 0, 0, 0, 0, 0]);
+    }
+
+    unreachable!()
+}
+
+
+pub fn get_time() -> u64 {
+    unsafe {
+        syscall(Syscall::GetTime, [0; 6]).value
+    }
+}
+
+
+pub fn sleep(milliseconds: u64) {
+    unsafe {
+        syscall(Syscall::Sleep, [milliseconds, 0, 0, 0, 0, 0]);
+    }
+}
+
+
+pub fn open_window(
+    x: u64,
+    y: u64,
+    width: u64,
+    height: u64,
+    title: &str,
+) -> Result<u64, SyscallError> {
+    unsafe {
+        syscall(
+            Syscall::OpenWindow,
+            [x, y, width, height, title.as_ptr() as u64, title.len() as u64],
+        )
+        .into_result()
+    }
+}
+
+
+/// Waits for events sent to the application, such as key presses in its window.
+pub fn read_event(events: &mut [AppEvent]) -> Result<usize, SyscallError> {
+    let result = unsafe {
+        syscall(
+            Syscall::ReadEvent,
+            [events.as_mut_ptr() as u64, events.len() as u64, 0, 0, 0, 0],
+        )
+    };
+
+    result
+        .into_result()
+        .map(|read| read as usize)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::syscall::{Syscall, SyscallError, SyscallResult};
+
+    #[test]
+    fn it_from_number() {
+        for number in 0..Syscall::COUNT as u64 {
+            assert_eq!(Syscall::from_number(number).unwrap() as u64, number);
+        }
+
+        assert!(Syscall::from_number(Syscall::COUNT as u64).is_none());
+    }
+
+
+    #[test]
+    fn it_into_result() {
+        assert_eq!(SyscallResult::ok(3).into_result(), Ok(3));
+        assert_eq!(
+            SyscallResult::err(SyscallError::NotFound).into_result(),
+            Err(SyscallError::NotFound)
+        );
+    }
+}
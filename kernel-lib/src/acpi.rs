@@ -1,16 +1,35 @@
+use alloc::vec;
+use alloc::vec::Vec;
 use core::ffi::c_void;
+use core::sync::atomic::{AtomicU64, Ordering};
 
+use crate::acpi::description_header::DescriptionHeader;
+use crate::acpi::facs::Facs;
 use crate::acpi::fadt::Fadt;
+use crate::acpi::hpet::Hpet;
+use crate::acpi::madt::Madt;
+use crate::acpi::mcfg::Mcfg;
 use crate::acpi::rsdp::{Rsdp, RsdpAddr};
+use crate::acpi::xsdt::Xsdt;
 use crate::error::KernelResult;
 use crate::{kernel_bail, kernel_error};
 
-mod description_header;
+pub mod aml;
+pub mod description_header;
+pub mod facs;
 pub mod fadt;
+pub mod generic_address;
+pub mod hpet;
+pub mod madt;
+pub mod mcfg;
+pub mod power;
 pub mod rsdp;
 pub mod volatile_chars;
 pub mod xsdt;
 
+/// The address of the XSDT, or 0 until [`init`] is called.
+static XSDT_ADDR: AtomicU64 = AtomicU64::new(0);
+
 
 pub fn init_acpi_timer(rsdp: Option<*const c_void>) -> KernelResult<Fadt> {
     if let Some(rsdp) = rsdp {
@@ -24,3 +43,68 @@ pub fn init_acpi_timer(rsdp: Option<*const c_void>) -> KernelResult<Fadt> {
 
     kernel_bail!("Not Found FADT")
 }
+
+
+/// Finds the XSDT from the RSDP handed over by the bootloader,
+/// so that the tables can be looked up afterward.
+pub fn init(rsdp: Option<*const c_void>) -> KernelResult {
+    let rsdp = rsdp.ok_or(kernel_error!("Not Found RSDP"))?;
+    let xsdt = Rsdp::new(RsdpAddr::from(rsdp as u64))?.xsdt()?;
+
+    XSDT_ADDR.store(xsdt.header().addr(), Ordering::Relaxed);
+    Ok(())
+}
+
+
+pub fn xsdt() -> KernelResult<Xsdt> {
+    match XSDT_ADDR.load(Ordering::Relaxed) {
+        0 => kernel_bail!("ACPI is not initialized"),
+        addr => Xsdt::new(addr),
+    }
+}
+
+
+/// Returns the headers of the XSDT, the tables it points to and the DSDT.
+pub fn tables() -> KernelResult<Vec<DescriptionHeader>> {
+    let xsdt = xsdt()?;
+
+    let mut tables = vec![xsdt.header().clone()];
+    tables.extend(xsdt.clone());
+    tables.extend(xsdt.dsdt());
+    Ok(tables)
+}
+
+
+pub fn find_fadt() -> KernelResult<Fadt> {
+    xsdt()?
+        .fadt()
+        .ok_or(kernel_error!("Not Found FADT"))
+}
+
+
+pub fn find_madt() -> KernelResult<Madt> {
+    xsdt()?
+        .madt()
+        .ok_or(kernel_error!("Not Found MADT"))
+}
+
+
+pub fn find_hpet() -> KernelResult<Hpet> {
+    xsdt()?
+        .hpet()
+        .ok_or(kernel_error!("Not Found HPET"))
+}
+
+
+pub fn find_mcfg() -> KernelResult<Mcfg> {
+    xsdt()?
+        .mcfg()
+        .ok_or(kernel_error!("Not Found MCFG"))
+}
+
+
+pub fn find_facs() -> KernelResult<Facs> {
+    xsdt()?
+        .facs()
+        .ok_or(kernel_error!("Not Found FACS"))
+}
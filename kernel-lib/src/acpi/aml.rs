@@ -0,0 +1,135 @@
+use alloc::vec::Vec;
+
+const ZERO_OP: u8 = 0x00;
+const ONE_OP: u8 = 0x01;
+const NAME_OP: u8 = 0x08;
+const BYTE_PREFIX: u8 = 0x0A;
+const WORD_PREFIX: u8 = 0x0B;
+const DWORD_PREFIX: u8 = 0x0C;
+const QWORD_PREFIX: u8 = 0x0E;
+const PACKAGE_OP: u8 = 0x12;
+const ROOT_CHAR: u8 = b'\\';
+const ONES_OP: u8 = 0xFF;
+
+
+/// Finds `Name (name, Package () { ... })` in the AML and returns the integers at its head.
+///
+/// The bytes are scanned without interpreting the AML,
+/// which is enough for the objects defined with constants such as `\_S5`.
+/// The elements after the first one which isn't an integer constant are omitted.
+pub fn find_package_integers(aml: &[u8], name: &[u8; 4]) -> Option<Vec<u64>> {
+    (0..aml.len()).find_map(|index| parse_named_package(&aml[index..], name))
+}
+
+
+fn parse_named_package(bytes: &[u8], name: &[u8; 4]) -> Option<Vec<u64>> {
+    let bytes = bytes.strip_prefix(&[NAME_OP])?;
+    let bytes = bytes
+        .strip_prefix(&[ROOT_CHAR])
+        .unwrap_or(bytes);
+    let bytes = bytes
+        .strip_prefix(name.as_slice())?
+        .strip_prefix(&[PACKAGE_OP])?;
+
+    // The package length counts the bytes encoding itself.
+    let (package_len, len_bytes) = parse_pkg_length(bytes)?;
+    let (&num_elements, mut elements) = bytes
+        .get(len_bytes..package_len)?
+        .split_first()?;
+
+    let mut integers = Vec::with_capacity(num_elements as usize);
+    for _ in 0..num_elements {
+        let Some((integer, size)) = parse_integer(elements) else {
+            break;
+        };
+        integers.push(integer);
+        elements = &elements[size..];
+    }
+
+    Some(integers)
+}
+
+
+/// Returns the package length and the number of bytes encoding it.
+fn parse_pkg_length(bytes: &[u8]) -> Option<(usize, usize)> {
+    let lead = *bytes.first()?;
+    let follows = (lead >> 6) as usize;
+    if follows == 0 {
+        return Some(((lead & 0x3F) as usize, 1));
+    }
+
+    let len = bytes
+        .get(1..=follows)?
+        .iter()
+        .enumerate()
+        .fold((lead & 0x0F) as usize, |len, (index, byte)| {
+            len | (*byte as usize) << (4 + 8 * index)
+        });
+
+    Some((len, follows + 1))
+}
+
+
+/// Returns the integer constant and the number of bytes encoding it.
+fn parse_integer(bytes: &[u8]) -> Option<(u64, usize)> {
+    match *bytes.first()? {
+        ZERO_OP => Some((0, 1)),
+        ONE_OP => Some((1, 1)),
+        ONES_OP => Some((u64::MAX, 1)),
+        BYTE_PREFIX => read_le(bytes, 1),
+        WORD_PREFIX => read_le(bytes, 2),
+        DWORD_PREFIX => read_le(bytes, 4),
+        QWORD_PREFIX => read_le(bytes, 8),
+        _ => None,
+    }
+}
+
+
+fn read_le(bytes: &[u8], size: usize) -> Option<(u64, usize)> {
+    let integer = bytes
+        .get(1..=size)?
+        .iter()
+        .rev()
+        .fold(0, |integer, byte| integer << 8 | *byte as u64);
+
+    Some((integer, size + 1))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::acpi::aml::{find_package_integers, parse_pkg_length};
+
+    #[test]
+    fn it_find_s5_of_qemu() {
+        // Scope (\_SB) { ... } Name (_S5, Package (0x04) { Zero, Zero, Zero, Zero })
+        let aml = [
+            0x10, 0x05, 0x5C, 0x5F, 0x53, 0x42, 0x5F, 0x08, 0x5F, 0x53, 0x35, 0x5F, 0x12, 0x06,
+            0x04, 0x00, 0x00, 0x00, 0x00,
+        ];
+
+        assert_eq!(find_package_integers(&aml, b"_S5_"), Some(vec![0, 0, 0, 0]));
+    }
+
+
+    #[test]
+    fn it_find_rooted_name_with_prefixed_integers() {
+        // Name (\_S5, Package (0x04) { 0x07, 0x0107, One, Method... })
+        let aml = [
+            0x08, 0x5C, 0x5F, 0x53, 0x35, 0x5F, 0x12, 0x09, 0x04, 0x0A, 0x07, 0x0B, 0x07, 0x01,
+            0x01, 0x14,
+        ];
+
+        assert_eq!(find_package_integers(&aml, b"_S5_"), Some(vec![7, 0x107, 1]));
+        assert_eq!(find_package_integers(&aml, b"_S4_"), None);
+    }
+
+
+    #[test]
+    fn it_parse_pkg_length() {
+        assert_eq!(parse_pkg_length(&[0x3F]), Some((0x3F, 1)));
+        assert_eq!(parse_pkg_length(&[0x4A, 0x12]), Some((0x12A, 2)));
+        assert_eq!(parse_pkg_length(&[0x81, 0x23, 0x01]), Some((0x1231, 3)));
+        assert_eq!(parse_pkg_length(&[0x81, 0x23]), None);
+    }
+}
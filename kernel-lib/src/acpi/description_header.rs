@@ -1,5 +1,8 @@
+use alloc::string::String;
+
 use volatile_bits::VolatileBitsReadable;
 
+use crate::acpi::volatile_chars::CharBuff;
 use crate::error::KernelResult;
 
 mod length;
@@ -8,6 +11,13 @@ mod signature;
 
 pub const SIZE: u64 = 4 + 4 + 1 + 1 + 6 + 8 + 4 + 4 + 4;
 
+const REVISION_OFFSET: u64 = 8;
+const OEM_ID_OFFSET: u64 = 10;
+const OEM_TABLE_ID_OFFSET: u64 = 16;
+const OEM_REVISION_OFFSET: u64 = 24;
+
+
+/// The header common to the ACPI system description tables.
 #[derive(Debug, Clone)]
 pub struct DescriptionHeader {
     addr: u64,
@@ -45,6 +55,56 @@ impl DescriptionHeader {
     }
 
 
+    pub fn signature(&self) -> String {
+        self.signature.as_string()
+    }
+
+
+    /// Returns the revision of the table's structure, which differs per table.
+    pub fn revision(&self) -> u8 {
+        unsafe { ((self.addr + REVISION_OFFSET) as *const u8).read_volatile() }
+    }
+
+
+    pub fn oem_id(&self) -> String {
+        CharBuff::<6>::new(self.addr + OEM_ID_OFFSET)
+            .as_string()
+            .trim_end()
+            .into()
+    }
+
+
+    pub fn oem_table_id(&self) -> String {
+        CharBuff::<8>::new(self.addr + OEM_TABLE_ID_OFFSET)
+            .as_string()
+            .trim_end()
+            .into()
+    }
+
+
+    pub fn oem_revision(&self) -> u32 {
+        unsafe { ((self.addr + OEM_REVISION_OFFSET) as *const u32).read_unaligned() }
+    }
+
+
+    /// Returns true if all the bytes of the table sum to zero.
+    pub fn valid_checksum(&self) -> bool {
+        let bytes =
+            unsafe { core::slice::from_raw_parts(self.addr as *const u8, self.length() as usize) };
+
+        bytes
+            .iter()
+            .fold(0u8, |sum, b| sum.wrapping_add(*b))
+            == 0
+    }
+
+
+    /// Returns the length of the whole table including the header.
+    pub fn length(&self) -> u64 {
+        self.length.read_volatile() as u64
+    }
+
+
     pub fn count(&self) -> u64 {
         let len = self.length.read_volatile() as u64;
         (len - SIZE) / core::mem::size_of::<u64>() as u64
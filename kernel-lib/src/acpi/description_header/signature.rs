@@ -1,3 +1,5 @@
+use alloc::string::String;
+
 use crate::acpi::volatile_chars::CharBuff;
 use crate::error::KernelResult;
 
@@ -23,4 +25,9 @@ impl Signature {
     pub fn valid(&self, sig: &str) -> bool {
         self.0.equal(sig)
     }
+
+
+    pub fn as_string(&self) -> String {
+        self.0.as_string()
+    }
 }
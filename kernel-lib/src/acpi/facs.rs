@@ -0,0 +1,104 @@
+use crate::acpi::volatile_chars::CharBuff;
+use crate::error::KernelResult;
+
+const LENGTH_OFFSET: u64 = 4;
+const HARDWARE_SIGNATURE_OFFSET: u64 = 8;
+const FIRMWARE_WAKING_VECTOR_OFFSET: u64 = 12;
+const GLOBAL_LOCK_OFFSET: u64 = 16;
+const FLAGS_OFFSET: u64 = 20;
+const X_FIRMWARE_WAKING_VECTOR_OFFSET: u64 = 24;
+const VERSION_OFFSET: u64 = 32;
+
+
+/// Firmware ACPI Control Structure
+///
+/// Pointed by the FADT, and has no description header unlike the other tables.
+#[derive(Debug, Clone)]
+pub struct Facs {
+    addr: u64,
+}
+
+
+impl Facs {
+    pub fn new(addr: u64) -> KernelResult<Self> {
+        CharBuff::<4>::new_with_check(addr, "FACS")?;
+
+        Ok(Self { addr })
+    }
+
+
+    #[inline]
+    pub fn addr(&self) -> u64 {
+        self.addr
+    }
+
+
+    pub fn length(&self) -> u32 {
+        self.read(LENGTH_OFFSET)
+    }
+
+
+    /// Returns the value which changes when the hardware configuration changes across sleep.
+    pub fn hardware_signature(&self) -> u32 {
+        self.read(HARDWARE_SIGNATURE_OFFSET)
+    }
+
+
+    /// Returns the 64-bit waking vector if set, otherwise the 32-bit one.
+    pub fn firmware_waking_vector(&self) -> u64 {
+        match self.read::<u64>(X_FIRMWARE_WAKING_VECTOR_OFFSET) {
+            0 => self.read::<u32>(FIRMWARE_WAKING_VECTOR_OFFSET) as u64,
+            vector => vector,
+        }
+    }
+
+
+    pub fn global_lock(&self) -> u32 {
+        self.read(GLOBAL_LOCK_OFFSET)
+    }
+
+
+    pub fn flags(&self) -> u32 {
+        self.read(FLAGS_OFFSET)
+    }
+
+
+    pub fn version(&self) -> u8 {
+        self.read(VERSION_OFFSET)
+    }
+
+
+    fn read<T>(&self, offset: u64) -> T {
+        unsafe { ((self.addr + offset) as *const T).read_volatile() }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::acpi::facs::Facs;
+
+    #[test]
+    fn it_read_fields() {
+        let mut buff = [0u64; 8];
+        let bytes = unsafe { core::slice::from_raw_parts_mut(buff.as_mut_ptr().cast::<u8>(), 64) };
+        bytes[0..4].copy_from_slice(b"FACS");
+        bytes[4] = 64;
+        bytes[12] = 0x10;
+        bytes[32] = 2;
+
+        let facs = Facs::new(buff.as_ptr() as u64).unwrap();
+
+        assert_eq!(facs.length(), 64);
+        assert_eq!(facs.firmware_waking_vector(), 0x10);
+        assert_eq!(facs.version(), 2);
+    }
+
+
+    #[test]
+    fn it_fail_without_signature() {
+        let buff = [0u64; 8];
+
+        assert!(Facs::new(buff.as_ptr() as u64).is_err());
+    }
+}
@@ -3,6 +3,7 @@ use volatile_bits::{volatile_bit_field, VolatileBitsReadable};
 use crate::acpi::fadt::flags::Flags;
 use crate::acpi::fadt::pm_timer_block::PmTimerBlock;
 use crate::io::asm::io_in32;
+use crate::timer::pm_timer::PmTimer;
 
 pub mod flags;
 pub mod pm_timer_block;
@@ -16,6 +17,15 @@ pub struct Fadt {
 
 
 impl Fadt {
+    pub fn pm_timer(&self) -> PmTimer {
+        PmTimer::new(
+            self.pm_timer_block
+                .read_volatile() as u16,
+            self.is_count_32_bits(),
+        )
+    }
+
+
     pub fn wait_milli_for(&self, milli: u32) {
         const FREQ: u32 = 3579545;
 
@@ -0,0 +1,123 @@
+use crate::error::KernelResult;
+use crate::io::asm::{io_in16, io_in32, io_in8, io_out16, io_out32, io_out8};
+use crate::kernel_bail;
+
+pub const SYSTEM_MEMORY: u8 = 0;
+pub const SYSTEM_IO: u8 = 1;
+
+
+/// Generic Address Structure
+///
+/// Tells where a register is and how wide it is.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct GenericAddress {
+    pub address_space: u8,
+    pub bit_width: u8,
+    pub bit_offset: u8,
+    pub access_size: u8,
+    pub address: u64,
+}
+
+
+impl GenericAddress {
+    pub const SIZE: u64 = 12;
+
+
+    /// Reads the structure at `addr`, which may not be aligned.
+    pub fn new(addr: u64) -> Self {
+        let bytes = unsafe { (addr as *const [u8; Self::SIZE as usize]).read_unaligned() };
+        Self::from_bytes(&bytes)
+    }
+
+
+    pub fn from_bytes(bytes: &[u8; Self::SIZE as usize]) -> Self {
+        Self {
+            address_space: bytes[0],
+            bit_width: bytes[1],
+            bit_offset: bytes[2],
+            access_size: bytes[3],
+            address: u64::from_le_bytes(bytes[4..].try_into().unwrap()),
+        }
+    }
+
+
+    /// Returns the register on the I/O port.
+    pub const fn io_port(port: u16, bit_width: u8) -> Self {
+        Self {
+            address_space: SYSTEM_IO,
+            bit_width,
+            bit_offset: 0,
+            access_size: 0,
+            address: port as u64,
+        }
+    }
+
+
+    #[inline]
+    pub const fn is_null(&self) -> bool {
+        self.address == 0
+    }
+
+
+    pub fn read(&self) -> KernelResult<u64> {
+        match (self.address_space, self.bit_width) {
+            (SYSTEM_MEMORY, 8) => Ok(unsafe { self.memory::<u8>().read_volatile() } as u64),
+            (SYSTEM_MEMORY, 16) => Ok(unsafe { self.memory::<u16>().read_volatile() } as u64),
+            (SYSTEM_MEMORY, 32) => Ok(unsafe { self.memory::<u32>().read_volatile() } as u64),
+            (SYSTEM_MEMORY, 64) => Ok(unsafe { self.memory::<u64>().read_volatile() }),
+            (SYSTEM_IO, 8) => Ok(io_in8(self.address as u16) as u64),
+            (SYSTEM_IO, 16) => Ok(io_in16(self.address as u16) as u64),
+            (SYSTEM_IO, 32) => Ok(io_in32(self.address as u16) as u64),
+            _ => kernel_bail!("Unsupported register {:?}", self),
+        }
+    }
+
+
+    pub fn write(&self, value: u64) -> KernelResult {
+        match (self.address_space, self.bit_width) {
+            (SYSTEM_MEMORY, 8) => unsafe { self.memory::<u8>().write_volatile(value as u8) },
+            (SYSTEM_MEMORY, 16) => unsafe { self.memory::<u16>().write_volatile(value as u16) },
+            (SYSTEM_MEMORY, 32) => unsafe { self.memory::<u32>().write_volatile(value as u32) },
+            (SYSTEM_MEMORY, 64) => unsafe { self.memory::<u64>().write_volatile(value) },
+            (SYSTEM_IO, 8) => io_out8(self.address as u16, value as u8),
+            (SYSTEM_IO, 16) => io_out16(self.address as u16, value as u16),
+            (SYSTEM_IO, 32) => io_out32(self.address as u16, value as u32),
+            _ => return kernel_bail!("Unsupported register {:?}", self),
+        }
+
+        Ok(())
+    }
+
+
+    #[inline]
+    fn memory<T>(&self) -> *mut T {
+        self.address as *mut T
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::acpi::generic_address::{GenericAddress, SYSTEM_IO};
+
+    #[test]
+    fn it_parse_reset_register_of_qemu() {
+        let bytes = [0x01, 0x08, 0x00, 0x00, 0xF9, 0x0C, 0, 0, 0, 0, 0, 0];
+
+        assert_eq!(GenericAddress::from_bytes(&bytes), GenericAddress::io_port(0xCF9, 8));
+    }
+
+
+    #[test]
+    fn it_reject_unsupported_width() {
+        let register = GenericAddress {
+            address_space: SYSTEM_IO,
+            bit_width: 64,
+            bit_offset: 0,
+            access_size: 0,
+            address: 0xCF9,
+        };
+
+        assert!(register.write(0).is_err());
+    }
+}
@@ -0,0 +1,110 @@
+use crate::acpi::description_header;
+use crate::acpi::description_header::DescriptionHeader;
+use crate::acpi::generic_address::GenericAddress;
+use crate::error::KernelResult;
+
+const EVENT_TIMER_BLOCK_ID_OFFSET: u64 = description_header::SIZE;
+const BASE_ADDRESS_OFFSET: u64 = EVENT_TIMER_BLOCK_ID_OFFSET + 4;
+const HPET_NUMBER_OFFSET: u64 = BASE_ADDRESS_OFFSET + GenericAddress::SIZE;
+const MINIMUM_TICK_OFFSET: u64 = HPET_NUMBER_OFFSET + 1;
+
+
+/// High Precision Event Timer Description Table
+#[derive(Debug, Clone)]
+pub struct Hpet {
+    addr: u64,
+    header: DescriptionHeader,
+}
+
+
+impl Hpet {
+    pub fn new(addr: u64) -> KernelResult<Self> {
+        Ok(Self {
+            addr,
+            header: DescriptionHeader::new_with_check(addr, "HPET")?,
+        })
+    }
+
+
+    #[inline]
+    pub fn header(&self) -> &DescriptionHeader {
+        &self.header
+    }
+
+
+    /// Returns the physical address of the HPET registers.
+    pub fn base_address(&self) -> u64 {
+        GenericAddress::new(self.addr + BASE_ADDRESS_OFFSET).address
+    }
+
+
+    pub fn event_timer_block_id(&self) -> EventTimerBlockId {
+        EventTimerBlockId(self.read(EVENT_TIMER_BLOCK_ID_OFFSET))
+    }
+
+
+    /// Returns the sequence number of the HPET in the system.
+    pub fn hpet_number(&self) -> u8 {
+        self.read(HPET_NUMBER_OFFSET)
+    }
+
+
+    /// Returns the minimum number of ticks which can be set to a comparator in periodic mode.
+    pub fn minimum_tick(&self) -> u16 {
+        self.read(MINIMUM_TICK_OFFSET)
+    }
+
+
+    fn read<T>(&self, offset: u64) -> T {
+        unsafe { ((self.addr + offset) as *const T).read_unaligned() }
+    }
+}
+
+
+/// The copy of the HPET's general capabilities register.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct EventTimerBlockId(pub u32);
+
+
+impl EventTimerBlockId {
+    pub const fn hardware_revision(&self) -> u8 {
+        self.0 as u8
+    }
+
+
+    pub const fn comparators(&self) -> u8 {
+        ((self.0 >> 8) & 0x1F) as u8 + 1
+    }
+
+
+    pub const fn is_64bit_counter(&self) -> bool {
+        (self.0 >> 13) & 1 == 1
+    }
+
+
+    pub const fn is_legacy_replacement_capable(&self) -> bool {
+        (self.0 >> 15) & 1 == 1
+    }
+
+
+    pub const fn pci_vendor_id(&self) -> u16 {
+        (self.0 >> 16) as u16
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::acpi::hpet::EventTimerBlockId;
+
+    #[test]
+    fn it_decode_event_timer_block_id() {
+        let id = EventTimerBlockId(0x8086_A201);
+
+        assert_eq!(id.hardware_revision(), 1);
+        assert_eq!(id.comparators(), 3);
+        assert!(id.is_64bit_counter());
+        assert!(id.is_legacy_replacement_capable());
+        assert_eq!(id.pci_vendor_id(), 0x8086);
+    }
+}
@@ -0,0 +1,283 @@
+use alloc::vec::Vec;
+
+use crate::acpi::description_header;
+use crate::acpi::description_header::DescriptionHeader;
+use crate::error::KernelResult;
+
+/// The local APIC address and the flags precede the entries.
+const ENTRIES_OFFSET: u64 = description_header::SIZE + 4 + 4;
+
+const PROCESSOR_LOCAL_APIC: u8 = 0;
+const IO_APIC: u8 = 1;
+const INTERRUPT_SOURCE_OVERRIDE: u8 = 2;
+const LOCAL_APIC_ADDRESS_OVERRIDE: u8 = 5;
+
+const PROCESSOR_ENABLED: u32 = 0b01;
+const PROCESSOR_ONLINE_CAPABLE: u32 = 0b10;
+
+
+/// Multiple APIC Description Table
+///
+/// Describes the interrupt controllers of the system.
+#[derive(Debug, Clone)]
+pub struct Madt {
+    addr: u64,
+    header: DescriptionHeader,
+}
+
+
+/// An entry of the MADT.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum MadtEntry {
+    LocalApic {
+        processor_id: u8,
+        apic_id: u8,
+        flags: u32,
+    },
+    IoApic(IoApicEntry),
+    InterruptSourceOverride(InterruptSourceOverride),
+    LocalApicAddressOverride {
+        address: u64,
+    },
+    /// The entries of the other types, with the type.
+    Unknown(u8),
+}
+
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct IoApicEntry {
+    pub id: u8,
+    pub address: u32,
+    /// The first global system interrupt the I/O APIC handles.
+    pub global_system_interrupt_base: u32,
+}
+
+
+/// Tells that an ISA interrupt is connected to another global system interrupt,
+/// or has a polarity or a trigger mode other than the ISA's ones.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct InterruptSourceOverride {
+    pub bus: u8,
+    /// The ISA interrupt number.
+    pub source: u8,
+    pub global_system_interrupt: u32,
+    pub flags: u16,
+}
+
+
+impl Madt {
+    pub fn new(addr: u64) -> KernelResult<Self> {
+        Ok(Self {
+            addr,
+            header: DescriptionHeader::new_with_check(addr, "APIC")?,
+        })
+    }
+
+
+    #[inline]
+    pub fn header(&self) -> &DescriptionHeader {
+        &self.header
+    }
+
+
+    /// Returns the physical address of the local APICs.
+    pub fn local_apic_address(&self) -> u64 {
+        self.entries()
+            .find_map(|entry| match entry {
+                MadtEntry::LocalApicAddressOverride { address } => Some(address),
+                _ => None,
+            })
+            .unwrap_or_else(|| {
+                let addr = (self.addr + description_header::SIZE) as *const u32;
+                unsafe { addr.read_unaligned() as u64 }
+            })
+    }
+
+
+    /// Returns the local APIC ids of the processors which are enabled or can be enabled.
+    pub fn local_apic_ids(&self) -> Vec<u8> {
+        self.entries()
+            .filter_map(|entry| match entry {
+                MadtEntry::LocalApic { apic_id, flags, .. }
+                    if flags & (PROCESSOR_ENABLED | PROCESSOR_ONLINE_CAPABLE) != 0 =>
+                {
+                    Some(apic_id)
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+
+    pub fn io_apics(&self) -> Vec<IoApicEntry> {
+        self.entries()
+            .filter_map(|entry| match entry {
+                MadtEntry::IoApic(io_apic) => Some(io_apic),
+                _ => None,
+            })
+            .collect()
+    }
+
+
+    pub fn interrupt_source_overrides(&self) -> Vec<InterruptSourceOverride> {
+        self.entries()
+            .filter_map(|entry| match entry {
+                MadtEntry::InterruptSourceOverride(source_override) => Some(source_override),
+                _ => None,
+            })
+            .collect()
+    }
+
+
+    pub fn entries(&self) -> MadtEntries<'_> {
+        let len = self
+            .header
+            .length()
+            .saturating_sub(ENTRIES_OFFSET);
+        let entries = (self.addr + ENTRIES_OFFSET) as *const u8;
+
+        MadtEntries::new(unsafe { core::slice::from_raw_parts(entries, len as usize) })
+    }
+}
+
+
+/// Iterates the variable length entries of the MADT.
+#[derive(Debug, Clone)]
+pub struct MadtEntries<'a> {
+    bytes: &'a [u8],
+}
+
+
+impl<'a> MadtEntries<'a> {
+    #[inline]
+    pub const fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes }
+    }
+}
+
+
+impl<'a> Iterator for MadtEntries<'a> {
+    type Item = MadtEntry;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let [entry_type, len, ..] = *self.bytes else {
+            return None;
+        };
+        let len = len as usize;
+        if len < 2 || self.bytes.len() < len {
+            self.bytes = &[];
+            return None;
+        }
+
+        let entry = &self.bytes[..len];
+        self.bytes = &self.bytes[len..];
+
+        Some(parse_entry(entry_type, entry).unwrap_or(MadtEntry::Unknown(entry_type)))
+    }
+}
+
+
+fn parse_entry(entry_type: u8, entry: &[u8]) -> Option<MadtEntry> {
+    match entry_type {
+        PROCESSOR_LOCAL_APIC => Some(MadtEntry::LocalApic {
+            processor_id: *entry.get(2)?,
+            apic_id: *entry.get(3)?,
+            flags: read_u32(entry, 4)?,
+        }),
+        IO_APIC => Some(MadtEntry::IoApic(IoApicEntry {
+            id: *entry.get(2)?,
+            address: read_u32(entry, 4)?,
+            global_system_interrupt_base: read_u32(entry, 8)?,
+        })),
+        INTERRUPT_SOURCE_OVERRIDE => Some(MadtEntry::InterruptSourceOverride(
+            InterruptSourceOverride {
+                bus: *entry.get(2)?,
+                source: *entry.get(3)?,
+                global_system_interrupt: read_u32(entry, 4)?,
+                flags: u16::from_le_bytes(entry.get(8..10)?.try_into().ok()?),
+            },
+        )),
+        LOCAL_APIC_ADDRESS_OVERRIDE => Some(MadtEntry::LocalApicAddressOverride {
+            address: u64::from_le_bytes(entry.get(4..12)?.try_into().ok()?),
+        }),
+        _ => None,
+    }
+}
+
+
+fn read_u32(entry: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(
+        entry
+            .get(offset..offset + 4)?
+            .try_into()
+            .ok()?,
+    ))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use crate::acpi::madt::{IoApicEntry, InterruptSourceOverride, MadtEntries, MadtEntry};
+
+    #[test]
+    fn it_parse_entries() {
+        let bytes = [
+            // Processor Local APIC
+            0, 8, 0, 1, 1, 0, 0, 0,
+            // I/O APIC
+            1, 12, 2, 0, 0x00, 0x00, 0xC0, 0xFE, 0, 0, 0, 0,
+            // Interrupt Source Override
+            2, 10, 0, 0, 2, 0, 0, 0, 0x05, 0x00,
+            // Local APIC NMI
+            4, 6, 0xFF, 0x05, 0x00, 1,
+        ];
+
+        let entries: Vec<MadtEntry> = MadtEntries::new(&bytes).collect();
+
+        assert_eq!(
+            entries,
+            [
+                MadtEntry::LocalApic {
+                    processor_id: 0,
+                    apic_id: 1,
+                    flags: 1
+                },
+                MadtEntry::IoApic(IoApicEntry {
+                    id: 2,
+                    address: 0xFEC0_0000,
+                    global_system_interrupt_base: 0
+                }),
+                MadtEntry::InterruptSourceOverride(InterruptSourceOverride {
+                    bus: 0,
+                    source: 0,
+                    global_system_interrupt: 2,
+                    flags: 5
+                }),
+                MadtEntry::Unknown(4),
+            ]
+        );
+    }
+
+
+    #[test]
+    fn it_stop_at_broken_entry() {
+        let bytes = [0, 8, 0, 1, 1, 0, 0, 0, 1, 0, 0, 0];
+
+        assert_eq!(MadtEntries::new(&bytes).count(), 1);
+    }
+
+
+    #[test]
+    fn it_parse_local_apic_address_override() {
+        let bytes = [5, 12, 0, 0, 0x00, 0x00, 0xE0, 0xFE, 0, 0, 0, 0];
+
+        assert_eq!(
+            MadtEntries::new(&bytes).next(),
+            Some(MadtEntry::LocalApicAddressOverride {
+                address: 0xFEE0_0000
+            })
+        );
+    }
+}
@@ -0,0 +1,96 @@
+use alloc::vec::Vec;
+
+use crate::acpi::description_header;
+use crate::acpi::description_header::DescriptionHeader;
+use crate::error::KernelResult;
+
+/// The entries follow 8 reserved bytes after the header.
+const ENTRIES_OFFSET: u64 = description_header::SIZE + 8;
+const ENTRY_SIZE: usize = 16;
+
+
+/// PCI Express Memory Mapped Configuration Space Base Address Description Table
+#[derive(Debug, Clone)]
+pub struct Mcfg {
+    addr: u64,
+    header: DescriptionHeader,
+}
+
+
+/// The memory mapped configuration space of a range of buses.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct McfgEntry {
+    pub base_address: u64,
+    pub segment_group: u16,
+    pub start_bus: u8,
+    pub end_bus: u8,
+}
+
+
+impl Mcfg {
+    pub fn new(addr: u64) -> KernelResult<Self> {
+        Ok(Self {
+            addr,
+            header: DescriptionHeader::new_with_check(addr, "MCFG")?,
+        })
+    }
+
+
+    #[inline]
+    pub fn header(&self) -> &DescriptionHeader {
+        &self.header
+    }
+
+
+    pub fn entries(&self) -> Vec<McfgEntry> {
+        let len = self
+            .header
+            .length()
+            .saturating_sub(ENTRIES_OFFSET);
+        let entries = (self.addr + ENTRIES_OFFSET) as *const u8;
+
+        parse_entries(unsafe { core::slice::from_raw_parts(entries, len as usize) })
+    }
+}
+
+
+fn parse_entries(bytes: &[u8]) -> Vec<McfgEntry> {
+    bytes
+        .chunks_exact(ENTRY_SIZE)
+        .map(|entry| McfgEntry {
+            base_address: u64::from_le_bytes(entry[0..8].try_into().unwrap()),
+            segment_group: u16::from_le_bytes(entry[8..10].try_into().unwrap()),
+            start_bus: entry[10],
+            end_bus: entry[11],
+        })
+        .collect()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::acpi::mcfg::{parse_entries, McfgEntry};
+
+    #[test]
+    fn it_parse_entries() {
+        let bytes = [
+            0x00, 0x00, 0x00, 0xB0, 0, 0, 0, 0, 0, 0, 0x00, 0xFF, 0, 0, 0, 0,
+        ];
+
+        assert_eq!(
+            parse_entries(&bytes),
+            [McfgEntry {
+                base_address: 0xB000_0000,
+                segment_group: 0,
+                start_bus: 0,
+                end_bus: 0xFF
+            }]
+        );
+    }
+
+
+    #[test]
+    fn it_ignore_truncated_entry() {
+        assert!(parse_entries(&[0; 15]).is_empty());
+    }
+}
@@ -0,0 +1,196 @@
+use crate::acpi;
+use crate::acpi::aml::find_package_integers;
+use crate::acpi::description_header;
+use crate::acpi::description_header::DescriptionHeader;
+use crate::acpi::generic_address::GenericAddress;
+use crate::error::KernelResult;
+use crate::interrupt::asm::without_interrupt;
+use crate::io::asm::{io_in8, io_out8};
+use crate::{kernel_bail, kernel_error, serial_println};
+
+const FADT_SMI_CMD: u64 = 48;
+const FADT_ACPI_ENABLE: u64 = 52;
+const FADT_PM1A_CNT_BLK: u64 = 64;
+const FADT_PM1B_CNT_BLK: u64 = 68;
+const FADT_FLAGS: u64 = 112;
+const FADT_RESET_REG: u64 = 116;
+const FADT_RESET_VALUE: u64 = 128;
+const FADT_X_PM1A_CNT_BLK: u64 = 172;
+const FADT_X_PM1B_CNT_BLK: u64 = 184;
+
+/// Set in the FADT flags if the reset register is supported.
+const RESET_REG_SUP: u32 = 1 << 10;
+
+/// Set in the PM1 control register while the hardware is in ACPI mode.
+const SCI_EN: u64 = 1 << 0;
+const SLP_TYP_SHIFT: u64 = 10;
+const SLP_TYP_MASK: u64 = 0b111 << SLP_TYP_SHIFT;
+const SLP_EN: u64 = 1 << 13;
+
+const KBC_STATUS: u16 = 0x64;
+const KBC_COMMAND: u16 = 0x64;
+const KBC_INPUT_FULL: u8 = 0b10;
+const KBC_PULSE_RESET: u8 = 0xFE;
+
+/// How many times the registers are polled before giving up.
+const POLL_COUNT: usize = 1_000_000;
+
+
+/// Resets the machine with the reset register in the FADT,
+/// or by pulsing the reset line of the keyboard controller if the register can't be used.
+pub fn reboot() -> ! {
+    x86_64::instructions::interrupts::disable();
+
+    if let Err(e) = reset_by_register() {
+        serial_println!("Falling back to the keyboard controller: {:?}", e);
+    }
+    pulse_reset_line();
+
+    common_lib::assembly::hlt_forever();
+}
+
+
+/// Turns the machine off by entering the sleep state S5.
+///
+/// Returns only if the machine couldn't be turned off.
+pub fn shutdown() -> KernelResult {
+    let fadt = fadt()?;
+    let dsdt = acpi::xsdt()?
+        .dsdt()
+        .ok_or(kernel_error!("Not Found DSDT"))?;
+    let (slp_typ_a, slp_typ_b) = sleep_type_s5(&dsdt)?;
+
+    let pm1a = pm1_control(&fadt, FADT_PM1A_CNT_BLK, FADT_X_PM1A_CNT_BLK)
+        .ok_or(kernel_error!("Not Found PM1a control register"))?;
+    let pm1b = pm1_control(&fadt, FADT_PM1B_CNT_BLK, FADT_X_PM1B_CNT_BLK);
+
+    enable_acpi_mode(&fadt, &pm1a)?;
+
+    // Interrupts are restored as they were if the machine doesn't turn off.
+    without_interrupt(|| {
+        enter_sleep_state(&pm1a, slp_typ_a)?;
+        if let Some(pm1b) = pm1b {
+            enter_sleep_state(&pm1b, slp_typ_b)?;
+        }
+
+        for _ in 0..POLL_COUNT {
+            core::hint::spin_loop();
+        }
+        kernel_bail!("The machine didn't turn off")
+    })
+}
+
+
+fn fadt() -> KernelResult<DescriptionHeader> {
+    acpi::xsdt()?
+        .find_table("FACP")
+        .ok_or(kernel_error!("Not Found FADT"))
+}
+
+
+fn reset_by_register() -> KernelResult {
+    let fadt = fadt()?;
+    let flags: u32 = read(&fadt, FADT_FLAGS).unwrap_or(0);
+    if flags & RESET_REG_SUP == 0 {
+        return kernel_bail!("Reset register is not supported");
+    }
+
+    let (Some(register), Some(value)) = (
+        read::<[u8; GenericAddress::SIZE as usize]>(&fadt, FADT_RESET_REG),
+        read::<u8>(&fadt, FADT_RESET_VALUE),
+    ) else {
+        return kernel_bail!("FADT has no reset register");
+    };
+
+    GenericAddress::from_bytes(&register).write(value as u64)?;
+
+    for _ in 0..POLL_COUNT {
+        core::hint::spin_loop();
+    }
+    kernel_bail!("The machine didn't reset")
+}
+
+
+fn pulse_reset_line() {
+    for _ in 0..POLL_COUNT {
+        if io_in8(KBC_STATUS) & KBC_INPUT_FULL == 0 {
+            break;
+        }
+    }
+
+    io_out8(KBC_COMMAND, KBC_PULSE_RESET);
+}
+
+
+/// Returns the values of `SLP_TYPa` and `SLP_TYPb` for S5 from `\_S5` in the DSDT.
+fn sleep_type_s5(dsdt: &DescriptionHeader) -> KernelResult<(u8, u8)> {
+    let aml_len = dsdt
+        .length()
+        .saturating_sub(description_header::SIZE);
+    let aml = unsafe {
+        core::slice::from_raw_parts(
+            (dsdt.addr() + description_header::SIZE) as *const u8,
+            aml_len as usize,
+        )
+    };
+
+    match find_package_integers(aml, b"_S5_").as_deref() {
+        Some([slp_typ_a, slp_typ_b, ..]) => Ok((*slp_typ_a as u8, *slp_typ_b as u8)),
+        Some([slp_typ]) => Ok((*slp_typ as u8, *slp_typ as u8)),
+        _ => kernel_bail!("Not Found \\_S5 in DSDT"),
+    }
+}
+
+
+/// Returns the extended register if the FADT has one, or the I/O port of the legacy field.
+fn pm1_control(fadt: &DescriptionHeader, offset: u64, x_offset: u64) -> Option<GenericAddress> {
+    let extended = read::<[u8; GenericAddress::SIZE as usize]>(fadt, x_offset)
+        .map(|bytes| GenericAddress::from_bytes(&bytes))
+        .filter(|register| !register.is_null());
+
+    extended.or_else(|| {
+        let port: u32 = read(fadt, offset)?;
+        (port != 0).then_some(GenericAddress::io_port(port as u16, 16))
+    })
+}
+
+
+/// Asks the firmware to hand the power management over through the SMI command port,
+/// if the hardware isn't in ACPI mode yet.
+fn enable_acpi_mode(fadt: &DescriptionHeader, pm1a: &GenericAddress) -> KernelResult {
+    if pm1a.read()? & SCI_EN != 0 {
+        return Ok(());
+    }
+
+    let smi_cmd: u32 = read(fadt, FADT_SMI_CMD).unwrap_or(0);
+    let acpi_enable: u8 = read(fadt, FADT_ACPI_ENABLE).unwrap_or(0);
+    if smi_cmd == 0 || acpi_enable == 0 {
+        return kernel_bail!("ACPI mode can't be enabled");
+    }
+
+    io_out8(smi_cmd as u16, acpi_enable);
+    for _ in 0..POLL_COUNT {
+        if pm1a.read()? & SCI_EN != 0 {
+            return Ok(());
+        }
+        core::hint::spin_loop();
+    }
+
+    kernel_bail!("ACPI mode wasn't enabled")
+}
+
+
+fn enter_sleep_state(pm1: &GenericAddress, slp_typ: u8) -> KernelResult {
+    let control = pm1.read()? & !SLP_TYP_MASK;
+    pm1.write(control | (slp_typ as u64) << SLP_TYP_SHIFT | SLP_EN)
+}
+
+
+/// Reads the field of the FADT, or returns `None` if the FADT is too old to have it.
+fn read<T: Copy>(fadt: &DescriptionHeader, offset: u64) -> Option<T> {
+    if fadt.length() < offset + core::mem::size_of::<T>() as u64 {
+        return None;
+    }
+
+    Some(unsafe { ((fadt.addr() + offset) as *const T).read_unaligned() })
+}
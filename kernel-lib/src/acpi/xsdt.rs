@@ -1,8 +1,18 @@
 use crate::acpi::description_header;
 use crate::acpi::description_header::DescriptionHeader;
+use crate::acpi::facs::Facs;
 use crate::acpi::fadt::Fadt;
+use crate::acpi::hpet::Hpet;
+use crate::acpi::madt::Madt;
+use crate::acpi::mcfg::Mcfg;
 use crate::error::KernelResult;
 
+/// The offsets of the 32-bit and 64-bit pointers to the FACS in the FADT.
+const FADT_FIRMWARE_CTRL: (u64, u64) = (36, 132);
+
+/// The offsets of the 32-bit and 64-bit pointers to the DSDT in the FADT.
+const FADT_DSDT: (u64, u64) = (40, 140);
+
 #[derive(Debug, Clone)]
 pub struct Xsdt {
     index: u64,
@@ -21,10 +31,54 @@ impl Xsdt {
     }
 
 
+    #[inline]
+    pub fn header(&self) -> &DescriptionHeader {
+        &self.header
+    }
+
+
+    /// Returns the first table with the signature among the ones the XSDT points to.
+    pub fn find_table(mut self, signature: &str) -> Option<DescriptionHeader> {
+        self.find(|header| header.valid_signature(signature))
+    }
+
+
     pub fn fadt(mut self) -> Option<Fadt> {
         self.find(|header| header.valid_signature("FACP"))
             .map(|header| Fadt::from(header.addr()))
     }
+
+
+    pub fn madt(self) -> Option<Madt> {
+        self.find_table("APIC")
+            .and_then(|header| Madt::new(header.addr()).ok())
+    }
+
+
+    pub fn hpet(self) -> Option<Hpet> {
+        self.find_table("HPET")
+            .and_then(|header| Hpet::new(header.addr()).ok())
+    }
+
+
+    pub fn mcfg(self) -> Option<Mcfg> {
+        self.find_table("MCFG")
+            .and_then(|header| Mcfg::new(header.addr()).ok())
+    }
+
+
+    /// Returns the FACS the FADT points to.
+    pub fn facs(self) -> Option<Facs> {
+        let fadt = self.find_table("FACP")?;
+        Facs::new(read_fadt_pointer(&fadt, FADT_FIRMWARE_CTRL)?).ok()
+    }
+
+
+    /// Returns the Differentiated System Description Table the FADT points to.
+    pub fn dsdt(self) -> Option<DescriptionHeader> {
+        let fadt = self.find_table("FACP")?;
+        DescriptionHeader::new_with_check(read_fadt_pointer(&fadt, FADT_DSDT)?, "DSDT").ok()
+    }
 }
 
 
@@ -45,3 +99,17 @@ impl Iterator for Xsdt {
         Some(header)
     }
 }
+
+
+/// Reads the 64-bit pointer if the FADT is long enough and it's set, otherwise the 32-bit one.
+fn read_fadt_pointer(fadt: &DescriptionHeader, (offset32, offset64): (u64, u64)) -> Option<u64> {
+    let read = |offset: u64| unsafe { ((fadt.addr() + offset) as *const u64).read_unaligned() };
+
+    let pointer = if offset64 + 8 <= fadt.length() && read(offset64) != 0 {
+        read(offset64)
+    } else {
+        read(offset32) & 0xFFFF_FFFF
+    };
+
+    (pointer != 0).then_some(pointer)
+}
@@ -1,11 +1,30 @@
+use spin::Mutex;
+
 use common_lib::math::unit::{gib, kib};
 
+use crate::allocator::bitmap_memory_manager::BitmapMemoryFrameManager;
+use crate::interrupt::asm::without_interrupt;
+
 pub mod allocate_map;
 pub mod bitmap_memory_allocator;
 pub mod bitmap_memory_manager;
 pub mod memory_map;
+pub mod stats;
 
 
 pub const FRAME_SIZE: usize = kib(4);
 
 pub const MAX_MEMORY_SIZE: usize = gib(10);
+
+
+/// The physical frame manager shared by the whole kernel.
+static FRAME_MANAGER: Mutex<BitmapMemoryFrameManager> =
+    Mutex::new(BitmapMemoryFrameManager::new());
+
+
+/// Runs `f` with the frame manager locked.
+///
+/// Interrupts are disabled meanwhile, since interrupt handlers may grow the heap.
+pub fn with_frame_manager<T>(f: impl FnOnce(&mut BitmapMemoryFrameManager) -> T) -> T {
+    without_interrupt(|| f(&mut FRAME_MANAGER.lock()))
+}
@@ -34,11 +34,19 @@ impl AllocateMap {
     }
 
 
-    pub fn new() -> Self {
+    pub const fn new() -> Self {
         Self {
             allocate_map_buff: [0; ALLOCATE_MAP_BUFF_SIZE],
         }
     }
+
+
+    pub fn mark_allocate_all(&mut self) {
+        self.allocate_map_buff
+            .fill(u128::MAX);
+    }
+
+
     pub fn mark_allocate_multi_frames(
         &mut self,
         base_frame_id: usize,
@@ -240,6 +248,16 @@ mod tests {
     }
 
 
+    #[test]
+    fn it_mark_allocate_all() {
+        let mut map = AllocateMap::new();
+        map.mark_allocate_all();
+
+        assert!(map.is_not_allocatable_frame(0));
+        assert!(map.is_not_allocatable_frame(MAX_FRAME_COUNT - 1));
+    }
+
+
     #[test]
     fn it_over_frames() {
         let mut map = AllocateMap::new();
@@ -1,104 +1,189 @@
 use core::alloc::{GlobalAlloc, Layout};
-use core::cell::OnceCell;
-use core::ptr::null_mut;
+use core::ptr::{null_mut, NonNull};
 
+use linked_list_allocator::Heap;
 use spin::Mutex;
 
-use common_lib::math::Align;
-use common_lib::physical_address::PhysicalAddress;
+use common_lib::math::frame_count_from_bytes;
+use common_lib::math::unit::mib;
 
-use crate::allocator::bitmap_memory_manager::BitmapMemoryFrameManager;
-use crate::allocator::memory_map::frame_iterable::MemoryMapFrameIterable;
-use crate::allocator::memory_map::frame_range::FrameRange;
-use crate::error::{AllocateReason, KernelError, KernelResult};
+use crate::allocator::stats::HeapStats;
+use crate::allocator::{with_frame_manager, FRAME_SIZE};
+use crate::error::{AllocateReason, KernelResult};
+use crate::interrupt::asm::without_interrupt;
 
-pub struct BitmapFrameAllocator(OnceCell<Mutex<Allocator>>);
+/// The heap grows by at least this size whenever it runs out of memory.
+const HEAP_GROW_SIZE: usize = mib(4);
 
+const MAX_HEAP_SEGMENTS: usize = 64;
+
+
+/// The global allocator whose memory is taken from the frame manager on demand.
+///
+/// Frames handed out by the frame manager may not be contiguous,
+/// so the heap consists of several segments, each managed by its own [`Heap`].
+pub struct BitmapFrameAllocator(Mutex<Segments>);
 
-unsafe impl Sync for BitmapFrameAllocator {}
 
 impl BitmapFrameAllocator {
     pub const fn uninit() -> BitmapFrameAllocator {
-        Self(OnceCell::new())
+        Self(Mutex::new(Segments::new()))
     }
 
 
-    pub fn init_heap(
-        &mut self,
-        frames: usize,
-        memory_map: impl MemoryMapFrameIterable,
-    ) -> KernelResult {
-        let mut manager = BitmapMemoryFrameManager::new(memory_map)?;
-
-        let frame_range = manager
-            .allocate_frames(frames)
-            .unwrap();
+    pub fn init_heap(&self, frames: usize) -> KernelResult {
+        without_interrupt(|| {
+            self.0
+                .lock()
+                .grow(frames * FRAME_SIZE)
+        })
+    }
 
-        let addr = frame_range
-            .base()
-            .base_phys_addr()
-            .raw();
 
-        self.0
-            .set(Mutex::new(Allocator { frame_range, addr }))
-            .map_err(|_| KernelError::FailedAllocate(AllocateReason::InitializeGlobalAllocator))
+    pub fn stats(&self) -> HeapStats {
+        without_interrupt(|| self.0.lock().stats())
     }
 }
 
 
 unsafe impl GlobalAlloc for BitmapFrameAllocator {
     unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
-        let mut allocator = self.0.get().unwrap().lock();
+        without_interrupt(|| {
+            let mut segments = self.0.lock();
+            if let Some(ptr) = segments.allocate(layout) {
+                return ptr;
+            }
+
+            // Enough for the layout even if the new segment doesn't extend the last one.
+            let bytes = layout.size() + layout.align() + FRAME_SIZE;
+            if segments
+                .grow(bytes.max(HEAP_GROW_SIZE))
+                .is_err()
+            {
+                return null_mut();
+            }
+
+            segments
+                .allocate(layout)
+                .unwrap_or(null_mut())
+        })
+    }
 
-        if allocator
-            .align_up(layout.align())
-            .is_err()
-        {
-            return null_mut();
-        }
 
-        if allocator.is_not_allocatable(layout.size()) {
-            return null_mut();
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        without_interrupt(|| {
+            self.0
+                .lock()
+                .deallocate(ptr, layout)
+        })
+    }
+}
+
+
+struct Segments {
+    heaps: [Heap; MAX_HEAP_SEGMENTS],
+    len: usize,
+    high_water_mark: usize,
+    live_allocations: usize,
+    total_allocations: u64,
+}
+
+
+// The heaps only point to the frames owned by them.
+unsafe impl Send for Segments {}
+
+
+impl Segments {
+    const fn new() -> Self {
+        const EMPTY: Heap = Heap::empty();
+        Self {
+            heaps: [EMPTY; MAX_HEAP_SEGMENTS],
+            len: 0,
+            high_water_mark: 0,
+            live_allocations: 0,
+            total_allocations: 0,
         }
+    }
 
-        let ptr = allocator.addr as *mut u8;
-        allocator.addr += layout.size() as u64;
 
-        ptr
+    fn allocate(&mut self, layout: Layout) -> Option<*mut u8> {
+        let ptr = self.heaps[..self.len]
+            .iter_mut()
+            .rev()
+            .find_map(|heap| heap.allocate_first_fit(layout).ok())?;
+
+        self.live_allocations += 1;
+        self.total_allocations += 1;
+        self.high_water_mark = self.high_water_mark.max(self.used());
+        Some(ptr.as_ptr())
     }
 
 
-    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
-        if !ptr.is_null() {
-            core::ptr::write_bytes(ptr, 0, layout.size());
-            core::ptr::drop_in_place(ptr);
+    unsafe fn deallocate(&mut self, ptr: *mut u8, layout: Layout) {
+        let Some(ptr) = NonNull::new(ptr) else {
+            return;
+        };
+
+        if let Some(heap) = self.heaps[..self.len]
+            .iter_mut()
+            .find(|heap| heap.bottom() <= ptr.as_ptr() && ptr.as_ptr() < heap.top())
+        {
+            heap.deallocate(ptr, layout);
+            self.live_allocations -= 1;
         }
     }
-}
 
 
-struct Allocator {
-    frame_range: FrameRange,
-    addr: u64,
-}
-
-impl Allocator {
-    pub fn align_up(&mut self, align: usize) -> KernelResult {
-        self.addr = self
-            .addr
-            .align_up(align)
-            .ok_or(KernelError::NumSizeOver)?;
-        Ok(())
+    fn used(&self) -> usize {
+        self.heaps[..self.len]
+            .iter()
+            .map(Heap::used)
+            .sum()
     }
 
 
-    pub fn is_not_allocatable(&self, bytes: usize) -> bool {
-        !self.is_allocatable(bytes)
+    fn stats(&self) -> HeapStats {
+        HeapStats {
+            size: self.heaps[..self.len]
+                .iter()
+                .map(Heap::size)
+                .sum(),
+            used: self.used(),
+            high_water_mark: self.high_water_mark,
+            segments: self.len,
+            live_allocations: self.live_allocations,
+            total_allocations: self.total_allocations,
+        }
     }
 
 
-    pub fn is_allocatable(&self, bytes: usize) -> bool {
-        self.frame_range
-            .is_contain_address(PhysicalAddress::new(self.addr + bytes as u64))
+    /// Takes frames of at least `bytes` from the frame manager and adds them to the heap.
+    fn grow(&mut self, bytes: usize) -> KernelResult {
+        let frames = frame_count_from_bytes(bytes, FRAME_SIZE);
+        let frame_range = with_frame_manager(|manager| manager.allocate_frames(frames))
+            .ok_or(AllocateReason::OutOfFrames { frames })?;
+
+        let bottom = frame_range
+            .base()
+            .base_phys_addr()
+            .raw() as *mut u8;
+        let size = frames * FRAME_SIZE;
+
+        if let Some(last) = self.heaps[..self.len]
+            .last_mut()
+            .filter(|heap| heap.top() == bottom)
+        {
+            unsafe { last.extend(size) };
+            return Ok(());
+        }
+
+        if self.len == MAX_HEAP_SEGMENTS {
+            with_frame_manager(|manager| manager.free_frames(frame_range.base().id(), frames))?;
+            return Err(AllocateReason::OutOfFrames { frames }.into());
+        }
+
+        unsafe { self.heaps[self.len].init(bottom, size) };
+        self.len += 1;
+        Ok(())
     }
 }
@@ -1,32 +1,73 @@
 use core::alloc::Layout;
 
+use uefi::table::boot::{MemoryDescriptor, MemoryType};
+
 use common_lib::math::frame_count_from_bytes;
+use common_lib::physical_address::PhysicalAddress;
 
-use crate::allocator::allocate_map::AllocateMap;
-use crate::allocator::memory_map::frame_iterable::MemoryMapFrameIterable;
+use crate::allocator::allocate_map::{AllocateMap, MAX_FRAME_COUNT};
+use crate::allocator::memory_map::frame::Frame;
 use crate::allocator::memory_map::frame_range::FrameRange;
+use crate::allocator::stats::FrameStats;
 use crate::allocator::FRAME_SIZE;
 use crate::error::KernelResult;
 
-pub struct BitmapMemoryFrameManager<MemoryMap>
-where
-    MemoryMap: MemoryMapFrameIterable,
-{
+/// Manages physical frames with a bitmap indexed by `physical address / FRAME_SIZE`.
+///
+/// Every frame starts out used; only the conventional regions of the memory map are freed,
+/// so holes and reserved ranges are never handed out.
+#[derive(Debug)]
+pub struct BitmapMemoryFrameManager {
     allocate_map: AllocateMap,
-    memory_map: MemoryMap,
+    end_frame_id: usize,
+    total_frames: usize,
+    used_frames: usize,
 }
 
 
-impl<MemoryMap> BitmapMemoryFrameManager<MemoryMap>
-where
-    MemoryMap: MemoryMapFrameIterable,
-{
-    pub fn new(memory_map: MemoryMap) -> KernelResult<BitmapMemoryFrameManager<MemoryMap>> {
-        let allocate_map = AllocateMap::new();
-        Ok(Self {
-            allocate_map,
-            memory_map,
-        })
+impl BitmapMemoryFrameManager {
+    pub const fn new() -> Self {
+        Self {
+            allocate_map: AllocateMap::new(),
+            end_frame_id: 0,
+            total_frames: 0,
+            used_frames: 0,
+        }
+    }
+
+
+    pub fn init<'a>(
+        &mut self,
+        memory_map: impl IntoIterator<Item = &'a MemoryDescriptor>,
+    ) -> KernelResult {
+        self.allocate_map.mark_allocate_all();
+        self.end_frame_id = 0;
+        self.total_frames = 0;
+        self.used_frames = 0;
+
+        for descriptor in memory_map
+            .into_iter()
+            .filter(|descriptor| descriptor.ty == MemoryType::CONVENTIONAL)
+        {
+            let base_frame_id = descriptor.phys_start as usize / FRAME_SIZE;
+            // `AllocateMap` can free up to, but not including, its last frame.
+            let end_frame_id =
+                (base_frame_id + descriptor.page_count as usize).min(MAX_FRAME_COUNT - 1);
+            if end_frame_id <= base_frame_id {
+                continue;
+            }
+
+            self.allocate_map
+                .free_multi_frames(base_frame_id, end_frame_id - base_frame_id)?;
+            self.end_frame_id = self.end_frame_id.max(end_frame_id);
+        }
+
+        // Null pointers must never be handed out as valid memory.
+        self.allocate_map.mark_allocate_frame(0)?;
+        self.total_frames = (0..self.end_frame_id)
+            .filter(|id| self.allocate_map.is_allocatable_frame(*id))
+            .count();
+        Ok(())
     }
 
 
@@ -36,64 +77,231 @@ where
     }
 
 
+    /// Allocates physically contiguous frames.
     pub fn allocate_frames(&mut self, frames: usize) -> Option<FrameRange> {
-        let mut frame_id = 0;
-
+        if frames == 0 {
+            return None;
+        }
 
-        loop {
-            self.return_none_if_over_frames(frame_id, frames)?;
-            if self
-                .allocate_map
-                .is_allocatable_multi_frames(frame_id, frames)
+        let mut frame_id = 0;
+        while frame_id + frames <= self.end_frame_id {
+            match (frame_id..frame_id + frames)
+                .rev()
+                .find(|id| self.allocate_map.is_not_allocatable_frame(*id))
             {
-                break;
+                Some(used_id) => frame_id = used_id + 1,
+                None => {
+                    self.allocate_map
+                        .mark_allocate_multi_frames(frame_id, frames)
+                        .ok()?;
+                    self.used_frames += frames;
+
+                    return Some(FrameRange::new(
+                        frame_at(frame_id),
+                        frame_at(frame_id + (frames - 1)),
+                    ));
+                }
             }
-            frame_id += 1;
         }
 
+        None
+    }
+
 
+    pub fn free_frames(&mut self, frame_id: usize, frames: usize) -> KernelResult {
         self.allocate_map
-            .mark_allocate_multi_frames(frame_id, frames)
-            .ok()?;
-
-        let base_frame = self
-            .memory_map
-            .frame_at(frame_id)?;
+            .free_multi_frames(frame_id, frames)?;
+        self.used_frames = self.used_frames.saturating_sub(frames);
+        Ok(())
+    }
 
-        let end_frame = self
-            .memory_map
-            .frame_at(frame_id + (frames - 1))?;
 
-        Some(FrameRange::new(base_frame, end_frame))
+    pub fn is_allocated_frame(&self, frame_id: usize) -> bool {
+        self.allocate_map
+            .is_not_allocatable_frame(frame_id)
     }
 
-    pub fn free_frames(&mut self, frame_id: usize, layout: Layout) -> KernelResult {
-        self.allocate_map
-            .free_multi_frames(frame_id, frame_count_from_bytes(layout.size(), FRAME_SIZE))?;
 
-        Ok(())
+    pub fn stats(&self) -> FrameStats {
+        FrameStats {
+            total_frames: self.total_frames,
+            used_frames: self.used_frames,
+            largest_free_frames: self.largest_free_frames(),
+        }
     }
 
 
-    pub fn memory_map_mut(&mut self) -> &mut MemoryMap {
-        &mut self.memory_map
+    /// Returns the length of the longest run of free frames,
+    /// which bounds the largest block the heap can grow by at once.
+    fn largest_free_frames(&self) -> usize {
+        let mut largest = 0;
+        let mut run = 0;
+        for frame_id in 0..self.end_frame_id {
+            if self.allocate_map.is_allocatable_frame(frame_id) {
+                run += 1;
+                largest = largest.max(run);
+            } else {
+                run = 0;
+            }
+        }
+
+        largest
     }
+}
 
 
-    fn return_none_if_over_frames(&self, base_frame_id: usize, frames: usize) -> Option<()> {
-        self.return_none_if_over_id(base_frame_id + frames)
+impl Default for BitmapMemoryFrameManager {
+    fn default() -> Self {
+        Self::new()
     }
+}
 
-    fn return_none_if_over_id(&self, frame_id: usize) -> Option<()> {
-        if self
-            .memory_map
-            .last_id()
-            .unwrap()
-            < frame_id
-        {
-            None
-        } else {
-            Some(())
+
+fn frame_at(frame_id: usize) -> Frame {
+    let base = (frame_id * FRAME_SIZE) as u64;
+    Frame::new(
+        frame_id,
+        PhysicalAddress::new(base),
+        PhysicalAddress::new(base + FRAME_SIZE as u64),
+    )
+}
+
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    use uefi::table::boot::{MemoryAttribute, MemoryDescriptor, MemoryType};
+
+    use crate::allocator::bitmap_memory_manager::BitmapMemoryFrameManager;
+    use crate::allocator::FRAME_SIZE;
+
+    fn descriptor(ty: MemoryType, phys_start: u64, page_count: u64) -> MemoryDescriptor {
+        MemoryDescriptor {
+            ty,
+            phys_start,
+            virt_start: 0,
+            page_count,
+            att: MemoryAttribute::empty(),
         }
     }
+
+
+    fn manager(memory_map: Vec<MemoryDescriptor>) -> Box<BitmapMemoryFrameManager> {
+        let mut manager = Box::<BitmapMemoryFrameManager>::default();
+        manager
+            .init(memory_map.iter())
+            .unwrap();
+        manager
+    }
+
+
+    #[test]
+    fn it_allocate_from_conventional() {
+        let mut manager = manager(vec![
+            descriptor(MemoryType::RESERVED, 0, 4),
+            descriptor(MemoryType::CONVENTIONAL, 0x4000, 4),
+        ]);
+
+        let range = manager
+            .allocate_frames(2)
+            .unwrap();
+        assert_eq!(range.base().id(), 4);
+        assert_eq!(
+            range
+                .address_range()
+                .end
+                .raw(),
+            0x6000
+        );
+    }
+
+
+    #[test]
+    fn it_not_allocate_frame_zero() {
+        let mut manager = manager(vec![descriptor(MemoryType::CONVENTIONAL, 0, 2)]);
+
+        assert_eq!(
+            manager
+                .allocate_frames(1)
+                .unwrap()
+                .base()
+                .id(),
+            1
+        );
+        assert!(manager
+            .allocate_frames(1)
+            .is_none());
+    }
+
+
+    #[test]
+    fn it_skip_holes_and_reserved_regions() {
+        let mut manager = manager(vec![
+            descriptor(MemoryType::CONVENTIONAL, 0x1000, 2),
+            descriptor(MemoryType::LOADER_DATA, 0x3000, 1),
+            descriptor(MemoryType::CONVENTIONAL, 0x4000, 2),
+            descriptor(MemoryType::CONVENTIONAL, 0x8000, 3),
+        ]);
+
+        let range = manager
+            .allocate_frames(3)
+            .unwrap();
+        assert_eq!(range.base().id(), 8);
+        assert!(manager.is_allocated_frame(3));
+        assert!(manager.is_allocated_frame(6));
+        assert!(manager
+            .allocate_frames(3)
+            .is_none());
+    }
+
+
+    #[test]
+    fn it_reuse_freed_frames() {
+        let mut manager = manager(vec![descriptor(MemoryType::CONVENTIONAL, 0x1000, 4)]);
+
+        let range = manager
+            .allocate_frames(4)
+            .unwrap();
+        assert!(manager
+            .allocate_frames(1)
+            .is_none());
+
+        manager
+            .free_frames(range.base().id(), 4)
+            .unwrap();
+        assert_eq!(
+            manager
+                .allocate_frames(4)
+                .unwrap()
+                .base()
+                .base_phys_addr()
+                .raw(),
+            FRAME_SIZE as u64
+        );
+    }
+
+
+    #[test]
+    fn it_count_frames() {
+        let mut manager = manager(vec![
+            descriptor(MemoryType::CONVENTIONAL, 0, 4),
+            descriptor(MemoryType::RESERVED, 0x4000, 1),
+            descriptor(MemoryType::CONVENTIONAL, 0x5000, 5),
+        ]);
+
+        let range = manager
+            .allocate_frames(2)
+            .unwrap();
+        let stats = manager.stats();
+        assert_eq!(stats.total_frames, 8);
+        assert_eq!(stats.used_frames, 2);
+        assert_eq!(stats.largest_free_frames, 5);
+
+        manager
+            .free_frames(range.base().id(), 2)
+            .unwrap();
+        assert_eq!(manager.stats().used_frames, 0);
+    }
 }
@@ -0,0 +1,119 @@
+use core::fmt::{Display, Formatter};
+
+use common_lib::math::unit::kib;
+
+use crate::allocator::FRAME_SIZE;
+
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub struct FrameStats {
+    /// The frames usable as conventional memory.
+    pub total_frames: usize,
+    pub used_frames: usize,
+    /// The longest run of physically contiguous free frames.
+    pub largest_free_frames: usize,
+}
+
+
+impl FrameStats {
+    #[inline]
+    pub const fn free_frames(&self) -> usize {
+        self.total_frames - self.used_frames
+    }
+}
+
+
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub struct HeapStats {
+    /// The bytes taken from the frame manager.
+    pub size: usize,
+    pub used: usize,
+    /// The largest `used` the heap has ever reached.
+    pub high_water_mark: usize,
+    pub segments: usize,
+    pub live_allocations: usize,
+    pub total_allocations: u64,
+}
+
+
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub struct MemoryStats {
+    pub frames: FrameStats,
+    pub heap: HeapStats,
+}
+
+
+impl MemoryStats {
+    #[inline]
+    pub const fn new(frames: FrameStats, heap: HeapStats) -> Self {
+        Self { frames, heap }
+    }
+}
+
+
+impl Display for MemoryStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        let frames = &self.frames;
+        let heap = &self.heap;
+        writeln!(
+            f,
+            "Frames: {}/{} KiB used, {} KiB free, largest {} KiB",
+            frames_to_kib(frames.used_frames),
+            frames_to_kib(frames.total_frames),
+            frames_to_kib(frames.free_frames()),
+            frames_to_kib(frames.largest_free_frames)
+        )?;
+        writeln!(
+            f,
+            "Heap: {}/{} KiB used, peak {} KiB, {} segments",
+            heap.used / kib(1),
+            heap.size / kib(1),
+            heap.high_water_mark / kib(1),
+            heap.segments
+        )?;
+        write!(
+            f,
+            "Allocations: {} live, {} total",
+            heap.live_allocations, heap.total_allocations
+        )
+    }
+}
+
+
+#[inline]
+fn frames_to_kib(frames: usize) -> usize {
+    frames * FRAME_SIZE / kib(1)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+
+    use crate::allocator::stats::{FrameStats, HeapStats, MemoryStats};
+
+    #[test]
+    fn it_display_stats() {
+        let stats = MemoryStats::new(
+            FrameStats {
+                total_frames: 256,
+                used_frames: 16,
+                largest_free_frames: 200,
+            },
+            HeapStats {
+                size: 64 * 1024,
+                used: 2048,
+                high_water_mark: 4096,
+                segments: 1,
+                live_allocations: 3,
+                total_allocations: 10,
+            },
+        );
+
+        assert_eq!(
+            stats.to_string(),
+            "Frames: 64/1024 KiB used, 960 KiB free, largest 800 KiB\n\
+             Heap: 2/64 KiB used, peak 4 KiB, 1 segments\n\
+             Allocations: 3 live, 10 total"
+        );
+    }
+}
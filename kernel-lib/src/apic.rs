@@ -1,3 +1,5 @@
+use core::sync::atomic::{AtomicU64, Ordering};
+
 use volatile_bits::volatile_address;
 
 use crate::apic::current_count::CurrentCount;
@@ -12,9 +14,13 @@ pub mod current_count;
 pub mod device_config;
 pub mod end_of_interrupt;
 pub mod initial_count;
+pub mod io_apic;
 pub mod local_apic_id;
 pub mod lvt_timer;
 
+/// The address of the local APIC registers, which the MADT may relocate.
+static LOCAL_APIC_BASE: AtomicU64 = AtomicU64::new(0xFEE00000);
+
 #[volatile_address]
 pub struct LocalApicRegistersAddr(u64);
 
@@ -31,11 +37,19 @@ pub struct LocalApicRegisters {
 
 impl Default for LocalApicRegistersAddr {
     fn default() -> Self {
-        LocalApicRegistersAddr::from(0xFEE00000)
+        LocalApicRegistersAddr::from(LOCAL_APIC_BASE.load(Ordering::Relaxed))
     }
 }
 
 
+/// Sets the address of the local APIC registers found in the MADT.
+///
+/// Must be called before the local APIC is used.
+pub fn set_local_apic_base(addr: u64) {
+    LOCAL_APIC_BASE.store(addr, Ordering::Relaxed);
+}
+
+
 impl LocalApicRegisters {
     pub fn new(local_apic_addr: LocalApicRegistersAddr) -> Self {
         Self {
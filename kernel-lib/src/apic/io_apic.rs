@@ -0,0 +1,324 @@
+use alloc::vec::Vec;
+use core::cell::OnceCell;
+
+use crate::acpi::madt::{InterruptSourceOverride, Madt};
+use crate::error::KernelResult;
+use crate::io::asm::io_out8;
+use crate::{kernel_bail, kernel_error};
+
+/// The IRQ of the programmable interval timer.
+pub const IRQ_PIT: u8 = 0;
+
+/// The IRQ of the PS/2 keyboard.
+pub const IRQ_KEYBOARD: u8 = 1;
+
+/// The IRQ of the serial port COM1.
+pub const IRQ_COM1: u8 = 4;
+
+/// The IRQ of the PS/2 mouse.
+pub const IRQ_MOUSE: u8 = 12;
+
+const IO_REGISTER_SELECT: u64 = 0x00;
+const IO_WINDOW: u64 = 0x10;
+
+const IO_APIC_VERSION: u32 = 0x01;
+const IO_REDIRECTION_TABLE: u32 = 0x10;
+
+const PIC_MASTER_DATA: u16 = 0x21;
+const PIC_SLAVE_DATA: u16 = 0xA1;
+
+static IO_APICS: GlobalIoApics = GlobalIoApics(OnceCell::new());
+
+
+/// Finds the I/O APICs in the MADT and masks all of their interrupts.
+///
+/// The legacy PICs are masked too, since the I/O APICs receive the same IRQs.
+pub fn init_io_apics(madt: &Madt) -> KernelResult {
+    disable_pic();
+
+    let io_apics = IoApics::new(madt);
+    io_apics.mask_all();
+
+    IO_APICS
+        .0
+        .set(io_apics)
+        .map_err(|_| kernel_error!("I/O APICs are already initialized"))
+}
+
+
+/// Delivers the ISA interrupt `irq` to the local APIC of `destination` with `vector`.
+pub fn route_irq(irq: u8, vector: u8, destination: u8) -> KernelResult {
+    IO_APICS
+        .0
+        .get()
+        .ok_or(kernel_error!("I/O APICs are not initialized"))?
+        .route_irq(irq, vector, destination)
+}
+
+
+/// Stops delivering the ISA interrupt `irq`.
+pub fn mask_irq(irq: u8) -> KernelResult {
+    IO_APICS
+        .0
+        .get()
+        .ok_or(kernel_error!("I/O APICs are not initialized"))?
+        .mask_irq(irq)
+}
+
+
+struct GlobalIoApics(OnceCell<IoApics>);
+
+
+unsafe impl Sync for GlobalIoApics {}
+
+
+/// The I/O APICs with the interrupt source overrides of the ISA interrupts.
+#[derive(Debug)]
+struct IoApics {
+    io_apics: Vec<IoApic>,
+    overrides: Vec<InterruptSourceOverride>,
+}
+
+
+impl IoApics {
+    fn new(madt: &Madt) -> Self {
+        Self {
+            io_apics: madt
+                .io_apics()
+                .into_iter()
+                .map(|entry| {
+                    IoApic::new(entry.address as u64, entry.global_system_interrupt_base)
+                })
+                .collect(),
+            overrides: madt.interrupt_source_overrides(),
+        }
+    }
+
+
+    fn mask_all(&self) {
+        for io_apic in &self.io_apics {
+            for index in 0..io_apic.redirection_entries() {
+                io_apic.write_redirection(index, RedirectionEntry::masked());
+            }
+        }
+    }
+
+
+    fn route_irq(&self, irq: u8, vector: u8, destination: u8) -> KernelResult {
+        let (global_system_interrupt, entry) = resolve_irq(&self.overrides, irq);
+
+        self.write(
+            global_system_interrupt,
+            entry
+                .with_vector(vector)
+                .with_destination(destination),
+        )
+    }
+
+
+    fn mask_irq(&self, irq: u8) -> KernelResult {
+        let (global_system_interrupt, _) = resolve_irq(&self.overrides, irq);
+
+        self.write(global_system_interrupt, RedirectionEntry::masked())
+    }
+
+
+    fn write(&self, global_system_interrupt: u32, entry: RedirectionEntry) -> KernelResult {
+        let Some(io_apic) = self
+            .io_apics
+            .iter()
+            .find(|io_apic| io_apic.handles(global_system_interrupt))
+        else {
+            return kernel_bail!("No I/O APIC handles GSI {global_system_interrupt}");
+        };
+
+        io_apic.write_redirection(global_system_interrupt - io_apic.base, entry);
+        Ok(())
+    }
+}
+
+
+/// Returns the global system interrupt the ISA interrupt is connected to,
+/// and the redirection entry with its polarity and trigger mode.
+fn resolve_irq(overrides: &[InterruptSourceOverride], irq: u8) -> (u32, RedirectionEntry) {
+    let Some(source_override) = overrides
+        .iter()
+        .find(|source_override| source_override.bus == 0 && source_override.source == irq)
+    else {
+        // ISA interrupts are edge triggered and active high.
+        return (irq as u32, RedirectionEntry::new());
+    };
+
+    let flags = source_override.flags;
+    let entry = RedirectionEntry::new()
+        .with_active_low(flags & 0b11 == 0b11)
+        .with_level_triggered((flags >> 2) & 0b11 == 0b11);
+
+    (source_override.global_system_interrupt, entry)
+}
+
+
+/// An I/O APIC, whose registers are accessed indirectly through a select and a window register.
+#[derive(Debug)]
+struct IoApic {
+    addr: u64,
+    /// The first global system interrupt this handles.
+    base: u32,
+}
+
+
+impl IoApic {
+    const fn new(addr: u64, base: u32) -> Self {
+        Self { addr, base }
+    }
+
+
+    fn redirection_entries(&self) -> u32 {
+        ((self.read(IO_APIC_VERSION) >> 16) & 0xFF) + 1
+    }
+
+
+    fn handles(&self, global_system_interrupt: u32) -> bool {
+        self.base <= global_system_interrupt
+            && global_system_interrupt - self.base < self.redirection_entries()
+    }
+
+
+    fn write_redirection(&self, index: u32, entry: RedirectionEntry) {
+        let value = entry.as_u64();
+        let register = IO_REDIRECTION_TABLE + index * 2;
+
+        // The high half holding the destination goes first, so the entry never points elsewhere.
+        self.write(register + 1, (value >> 32) as u32);
+        self.write(register, value as u32);
+    }
+
+
+    fn read(&self, register: u32) -> u32 {
+        unsafe {
+            ((self.addr + IO_REGISTER_SELECT) as *mut u32).write_volatile(register);
+            ((self.addr + IO_WINDOW) as *const u32).read_volatile()
+        }
+    }
+
+
+    fn write(&self, register: u32, value: u32) {
+        unsafe {
+            ((self.addr + IO_REGISTER_SELECT) as *mut u32).write_volatile(register);
+            ((self.addr + IO_WINDOW) as *mut u32).write_volatile(value);
+        }
+    }
+}
+
+
+/// An entry of the redirection table with the fixed delivery mode and the physical destination.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+struct RedirectionEntry {
+    vector: u8,
+    active_low: bool,
+    level_triggered: bool,
+    masked: bool,
+    destination: u8,
+}
+
+
+impl RedirectionEntry {
+    const fn new() -> Self {
+        Self {
+            vector: 0,
+            active_low: false,
+            level_triggered: false,
+            masked: false,
+            destination: 0,
+        }
+    }
+
+
+    const fn masked() -> Self {
+        Self {
+            masked: true,
+            ..Self::new()
+        }
+    }
+
+
+    const fn with_vector(self, vector: u8) -> Self {
+        Self { vector, ..self }
+    }
+
+
+    const fn with_active_low(self, active_low: bool) -> Self {
+        Self { active_low, ..self }
+    }
+
+
+    const fn with_level_triggered(self, level_triggered: bool) -> Self {
+        Self {
+            level_triggered,
+            ..self
+        }
+    }
+
+
+    const fn with_destination(self, destination: u8) -> Self {
+        Self {
+            destination,
+            ..self
+        }
+    }
+
+
+    const fn as_u64(&self) -> u64 {
+        self.vector as u64
+            | (self.active_low as u64) << 13
+            | (self.level_triggered as u64) << 15
+            | (self.masked as u64) << 16
+            | (self.destination as u64) << 56
+    }
+}
+
+
+fn disable_pic() {
+    io_out8(PIC_MASTER_DATA, 0xFF);
+    io_out8(PIC_SLAVE_DATA, 0xFF);
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::acpi::madt::InterruptSourceOverride;
+    use crate::apic::io_apic::{resolve_irq, RedirectionEntry, IRQ_KEYBOARD, IRQ_PIT};
+
+    #[test]
+    fn it_encode_redirection_entry() {
+        let entry = RedirectionEntry::new()
+            .with_vector(0x42)
+            .with_level_triggered(true)
+            .with_destination(3);
+
+        assert_eq!(entry.as_u64(), 0x0300_0000_0000_8042);
+        assert_eq!(RedirectionEntry::masked().as_u64(), 0x1_0000);
+    }
+
+
+    #[test]
+    fn it_resolve_overridden_irq() {
+        let overrides = [InterruptSourceOverride {
+            bus: 0,
+            source: IRQ_PIT,
+            global_system_interrupt: 2,
+            flags: 0b1111,
+        }];
+
+        let (gsi, entry) = resolve_irq(&overrides, IRQ_PIT);
+        assert_eq!(gsi, 2);
+        assert_eq!(
+            entry,
+            RedirectionEntry::new()
+                .with_active_low(true)
+                .with_level_triggered(true)
+        );
+
+        assert_eq!(resolve_irq(&overrides, IRQ_KEYBOARD), (1, RedirectionEntry::new()));
+    }
+}
@@ -0,0 +1,222 @@
+use core::arch::asm;
+use core::cell::OnceCell;
+
+use common_lib::elf::sym::demangle::Demangle;
+use common_lib::elf::sym::symbol_table::SymbolTable;
+
+use crate::error::KernelResult;
+use crate::kernel_error;
+use crate::paging::{guard, IDENTITY_MAPPED_END};
+use crate::serial_println;
+
+/// Walking stops after this many frames in case the chain of frame pointers is broken.
+const MAX_FRAMES: usize = 64;
+
+static KERNEL_SYMBOLS: KernelSymbols = KernelSymbols(OnceCell::new());
+
+
+struct KernelSymbols(OnceCell<SymbolTable<'static>>);
+
+
+unsafe impl Sync for KernelSymbols {}
+
+
+/// Reads the symbol table of the kernel ELF file, which the bootloader keeps in memory.
+///
+/// Backtraces show only the addresses until this is called.
+pub fn init(kernel_elf: &'static [u8]) -> KernelResult {
+    let symbols = SymbolTable::from_elf(kernel_elf)
+        .ok_or(kernel_error!("The kernel ELF file has no symbol table"))?;
+
+    KERNEL_SYMBOLS
+        .0
+        .set(symbols)
+        .map_err(|_| kernel_error!("Kernel symbols are already initialized"))
+}
+
+
+/// Writes the functions on the call stack over serial, starting from the caller of this.
+///
+/// The kernel must be built with frame pointers.
+#[inline(never)]
+pub fn print_backtrace() {
+    serial_println!("Backtrace:");
+    print_frames(frame_pointer(), 0);
+}
+
+
+/// Writes the function at `rip` and the ones on the call stack from `rbp` over serial.
+///
+/// Used for exceptions, where `rip` and `rbp` are of the interrupted code.
+pub fn print_backtrace_at(rip: u64, rbp: u64) {
+    serial_println!("Backtrace:");
+    print_frame(0, rip, rip);
+    print_frames(rbp, 1);
+}
+
+
+/// Returns the frame pointer saved by the prologue of the function calling this,
+/// which is the one of its caller, or the one of the interrupted code in an interrupt handler.
+#[inline(always)]
+pub fn saved_frame_pointer() -> u64 {
+    unsafe { *(frame_pointer() as *const u64) }
+}
+
+
+#[inline(always)]
+fn frame_pointer() -> u64 {
+    let rbp: u64;
+    unsafe {
+        asm!("mov {}, rbp", out(reg) rbp, options(nomem, nostack, preserves_flags));
+    }
+    rbp
+}
+
+
+fn print_frames(rbp: u64, first_index: usize) {
+    let frames = Frames::new(rbp, IDENTITY_MAPPED_END, guard::is_present);
+    for (index, return_address) in frames.enumerate() {
+        // The call may be the last instruction of the function, so look up the address before.
+        print_frame(first_index + index, return_address, return_address - 1);
+    }
+}
+
+
+fn print_frame(index: usize, addr: u64, lookup_addr: u64) {
+    let symbol = KERNEL_SYMBOLS
+        .0
+        .get()
+        .and_then(|symbols| symbols.find(lookup_addr));
+
+    match symbol {
+        Some((name, offset)) => serial_println!(
+            "  #{:<2} 0x{:016X} {}+0x{:X}",
+            index,
+            addr,
+            Demangle(name),
+            offset + (addr - lookup_addr)
+        ),
+        None => serial_println!("  #{:<2} 0x{:016X} <unknown>", index, addr),
+    }
+}
+
+
+/// Iterates the return addresses by following the frame pointers saved on the stack.
+///
+/// Stops when a frame pointer is null, not aligned, not below `end`, doesn't go up the stack,
+/// or points to memory `is_readable` rejects, such as a guard page,
+/// since reading it would fault again inside the exception handler printing the backtrace.
+struct Frames {
+    rbp: u64,
+    end: u64,
+    is_readable: fn(u64) -> bool,
+    count: usize,
+}
+
+
+impl Frames {
+    const fn new(rbp: u64, end: u64, is_readable: fn(u64) -> bool) -> Self {
+        Self {
+            rbp,
+            end,
+            is_readable,
+            count: 0,
+        }
+    }
+
+
+    fn is_valid(&self) -> bool {
+        self.rbp != 0
+            && self.rbp % 8 == 0
+            && self
+                .rbp
+                .checked_add(16)
+                .map_or(false, |frame_end| frame_end <= self.end)
+            && (self.is_readable)(self.rbp)
+            && (self.is_readable)(self.rbp + 8)
+    }
+}
+
+
+impl Iterator for Frames {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if MAX_FRAMES <= self.count || !self.is_valid() {
+            return None;
+        }
+
+        let frame = self.rbp as *const u64;
+        let (next_rbp, return_address) = unsafe { (*frame, *frame.add(1)) };
+        if return_address == 0 {
+            return None;
+        }
+
+        self.rbp = if self.rbp < next_rbp { next_rbp } else { 0 };
+        self.count += 1;
+        Some(return_address)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use crate::backtrace::Frames;
+
+    #[test]
+    fn it_follow_frame_pointers() {
+        let mut stack = [0u64; 6];
+        let base = stack.as_ptr() as u64;
+        stack[0] = base + 16;
+        stack[1] = 0x1000;
+        stack[2] = base + 32;
+        stack[3] = 0x2000;
+        stack[4] = 0;
+        stack[5] = 0x3000;
+
+        let frames: Vec<u64> = Frames::new(base, u64::MAX, |_| true).collect();
+
+        assert_eq!(frames, [0x1000, 0x2000, 0x3000]);
+    }
+
+
+    #[test]
+    fn it_stop_if_frame_goes_down() {
+        let mut stack = [0u64; 4];
+        let base = stack.as_ptr() as u64;
+        stack[0] = base + 16;
+        stack[1] = 0x1000;
+        stack[2] = base;
+        stack[3] = 0x2000;
+
+        let frames: Vec<u64> = Frames::new(base, u64::MAX, |_| true).collect();
+
+        assert_eq!(frames, [0x1000, 0x2000]);
+    }
+
+
+    #[test]
+    fn it_stop_at_end() {
+        let stack = [0u64, 0x1000];
+        let base = stack.as_ptr() as u64;
+
+        assert_eq!(Frames::new(base, base + 8, |_| true).count(), 0);
+    }
+
+
+    #[test]
+    fn it_stop_at_unreadable_frame() {
+        let mut stack = [0u64; 4];
+        let base = stack.as_ptr() as u64;
+        stack[0] = base + 16;
+        stack[1] = 0x1000;
+        stack[2] = 0;
+        stack[3] = 0x2000;
+
+        let first_frame_only = |addr: u64| addr % 32 < 16;
+        assert_eq!(Frames::new(base, u64::MAX, |_| true).count(), 2);
+        assert_eq!(Frames::new(base, u64::MAX, first_frame_only).count(), 1);
+    }
+}
@@ -1,6 +1,9 @@
 use core::arch::asm;
 
 use crate::control_registers::read_cr3;
+use crate::segmentation::{
+    KERNEL_CODE_SEGMENT, KERNEL_DATA_SEGMENT, USER_CODE_SEGMENT, USER_DATA_SEGMENT,
+};
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 #[repr(C, align(16))]
@@ -116,8 +119,8 @@ impl Context {
         self.set_rsi(rsi);
         self.set_cr3(read_cr3());
         self.set_flags(0x202);
-        self.set_cs(1 << 3);
-        self.set_ss(2 << 3);
+        self.set_cs(KERNEL_CODE_SEGMENT as u64);
+        self.set_ss(KERNEL_DATA_SEGMENT as u64);
         self.set_rsp(rsp);
         unsafe {
             self
@@ -250,3 +253,98 @@ pub extern "sysv64" fn asm_switch_context(_next: &Context, _current: &Context) {
         )
     }
 }
+
+
+/// Jumps to `rip` in ring 3 with `argc` and `argv` as arguments.
+///
+/// The callee-saved registers are pushed onto the kernel stack
+/// and the resulting stack pointer is written to `kernel_rsp`,
+/// which is usually RSP0 of the task state segment.
+/// This returns the exit code only when [`asm_exit_user_mode`] is called with the saved stack pointer.
+///
+/// argc = rdi
+/// argv = rsi
+/// rip = rdx
+/// rsp = rcx
+/// kernel_rsp = r8
+#[allow(unused)]
+#[naked]
+pub extern "sysv64" fn asm_enter_user_mode(
+    _argc: u64,
+    _argv: u64,
+    _rip: u64,
+    _rsp: u64,
+    _kernel_rsp: *mut u64,
+) -> i32 {
+    unsafe {
+        asm!(
+        "
+        push rbp
+        push rbx
+        push r12
+        push r13
+        push r14
+        push r15
+        mov [r8], rsp
+
+        // SS
+        push {user_ss}
+        // RSP
+        push rcx
+        // RFLAGS
+        push 0x202
+        // CS
+        push {user_cs}
+        // RIP
+        push rdx
+
+        xor eax, eax
+        xor ebx, ebx
+        xor ecx, ecx
+        xor edx, edx
+        xor ebp, ebp
+        xor r8, r8
+        xor r9, r9
+        xor r10, r10
+        xor r11, r11
+        xor r12, r12
+        xor r13, r13
+        xor r14, r14
+        xor r15, r15
+
+        iretq
+        ",
+        user_ss = const USER_DATA_SEGMENT as u64,
+        user_cs = const USER_CODE_SEGMENT as u64,
+        options(noreturn)
+        )
+    }
+}
+
+
+/// Returns from [`asm_enter_user_mode`] with the exit code.
+///
+/// kernel_rsp = rdi
+/// exit_code = rsi
+#[allow(unused)]
+#[naked]
+pub extern "sysv64" fn asm_exit_user_mode(_kernel_rsp: u64, _exit_code: i32) -> ! {
+    unsafe {
+        asm!(
+        "
+        mov rsp, rdi
+        mov eax, esi
+
+        pop r15
+        pop r14
+        pop r13
+        pop r12
+        pop rbx
+        pop rbp
+
+        ret
+        ",
+        options(noreturn)
+        )
+    }
+}
@@ -1,3 +1,4 @@
+use alloc::string::String;
 use core::num::TryFromIntError;
 
 use simple_fat::error::FatError;
@@ -39,6 +40,12 @@ pub enum KernelError {
     #[error(transparent)]
     FailedAllocate(#[from] AllocateReason),
 
+    #[error(transparent)]
+    FailedOperateFs(#[from] FsReason),
+
+    #[error(transparent)]
+    FailedOperatePaging(#[from] PagingReason),
+
     #[error(transparent)]
     TryFromIntError(#[from] TryFromIntError),
 }
@@ -95,4 +102,60 @@ pub enum AllocateReason {
 
     #[error("Over address: 0x{address:X}")]
     OverAddress { address: u64 },
+
+    #[error("No {frames} contiguous free frames")]
+    OutOfFrames { frames: usize },
+}
+
+
+#[derive(Debug, PartialEq, Error)]
+pub enum FsReason {
+    #[error("Not found: {0}")]
+    NotFound(String),
+
+    #[error("Already exists: {0}")]
+    AlreadyExists(String),
+
+    #[error("Not a directory: {0}")]
+    NotDirectory(String),
+
+    #[error("Is a directory: {0}")]
+    IsDirectory(String),
+
+    #[error("Directory not empty: {0}")]
+    DirectoryNotEmpty(String),
+
+    #[error("Invalid file name: {0}")]
+    InvalidName(String),
+
+    #[error("Invalid volume: {0}")]
+    InvalidVolume(&'static str),
+
+    #[error("No free cluster")]
+    NoFreeCluster,
+}
+
+
+#[derive(Debug, PartialEq, Error)]
+pub enum PagingReason {
+    #[error("Not in user space: 0x{0:X}")]
+    NotUserSpace(u64),
+
+    #[error("Not aligned to page: 0x{0:X}")]
+    NotAligned(u64),
+
+    #[error("Already mapped: 0x{0:X}")]
+    AlreadyMapped(u64),
+
+    #[error("Not mapped: 0x{0:X}")]
+    NotMapped(u64),
+
+    #[error("Not writable: 0x{0:X}")]
+    NotWritable(u64),
+
+    #[error("Invalid region: 0x{0:X}")]
+    InvalidRegion(u64),
+
+    #[error("No free frame")]
+    NoFreeFrame,
 }
@@ -1,76 +1,164 @@
+use ::alloc::vec::Vec;
 use core::cell::OnceCell;
 
-use simple_fat::bpb::BpbFat32;
-use simple_fat::dir::data::file::RegularFile;
-use simple_fat::dir::data::DataEntries;
-use simple_fat::error::FatDeviceError;
-use simple_fat::{Fat, FatDeviceAccessible};
+use spin::MutexGuard;
 
+use common_lib::elf::ehdr::elf_header_ptr::ElfHeaderPtr;
 use common_lib::loader::elf::ElfLoader;
 use common_lib::loader::ExecuteFileLoadable;
 
-use crate::error::KernelResult;
+use crate::error::{KernelResult, PagingReason};
 use crate::fs::alloc::FsAllocator;
+use crate::fs::volume::bpb::BiosParameterBlock;
+use crate::fs::volume::dir_entry::{DirEntry, FatTimestamp};
+use crate::fs::volume::{FatVolume, VolumeDevice};
+use crate::kernel_error;
+use crate::paging::address_space::{AddressSpace, USER_SPACE_START};
+use crate::sync::preemptive_mutex::PreemptiveMutex;
+use crate::task;
+use crate::timer::rtc;
 
 mod alloc;
+pub mod path;
+pub mod volume;
 
 static FS: FileSystem = FileSystem::uninit();
 
-pub fn init(fat_volume: *mut u8) {
-    FS.init(fat_volume);
+const BOOT_SECTOR_SIZE: usize = 512;
+
+pub fn init(fat_volume: *mut u8) -> KernelResult {
+    FS.init(fat_volume)
 }
 
 
-pub fn open_file(file_name: &str) -> KernelResult<RegularFile<BpbFat32<FatDevice>>> {
-    Ok(FS
-        .0
-        .get()
-        .unwrap()
-        .open_file(file_name)?)
+/// Returns the entry at the absolute path.
+pub fn find(path: &str) -> KernelResult<DirEntry> {
+    volume().find(path)
 }
 
 
-pub fn root_dir() -> KernelResult<DataEntries<BpbFat32<FatDevice>>> {
-    Ok(FS
-        .0
-        .get()
-        .unwrap()
-        .root_dir()?)
+pub fn read_dir(path: &str) -> KernelResult<Vec<DirEntry>> {
+    volume().read_dir(path)
+}
+
+
+pub fn read_file(path: &str) -> KernelResult<Vec<u8>> {
+    volume().read_file(path)
+}
+
+
+/// Returns the numbers of the clusters in the chain starting from `first_cluster`.
+pub fn cluster_chain(first_cluster: u32) -> KernelResult<Vec<u32>> {
+    volume().cluster_chain(first_cluster)
+}
+
+
+/// Creates an empty file.
+pub fn create_file(path: &str) -> KernelResult<DirEntry> {
+    volume().create_file(path)
+}
+
+
+/// Replaces the contents of the file, creating it if it doesn't exist.
+pub fn write_file(path: &str, buff: &[u8]) -> KernelResult<DirEntry> {
+    volume().write_file(path, buff)
+}
+
+
+/// Writes the buffer at `offset` in the file, extending it if needed.
+pub fn write_at(path: &str, offset: usize, buff: &[u8]) -> KernelResult<DirEntry> {
+    volume().write_at(path, offset, buff)
 }
 
 
-pub fn execute_elf_from_name(file_name: &str) -> KernelResult {
-    let file = open_file(file_name)?;
-    execute_elf(file)
+/// Appends to the end of the file, creating it if it doesn't exist.
+pub fn append_file(path: &str, buff: &[u8]) -> KernelResult<DirEntry> {
+    volume().append_file(path, buff)
 }
 
 
-pub fn execute_elf(file: RegularFile<BpbFat32<FatDevice>>) -> KernelResult {
-    let mut buff = file.read_boxed()?;
+pub fn truncate(path: &str, size: usize) -> KernelResult<DirEntry> {
+    volume().truncate(path, size)
+}
 
-    let entry_point_addr = ElfLoader::new().load(&mut buff, &mut FsAllocator)?;
-    let entry_point_ptr = *entry_point_addr as *const ();
-    let entry_point: extern "sysv64" fn() -> () = unsafe { core::mem::transmute(entry_point_ptr) };
 
-    entry_point();
-    Ok(())
+/// Removes a file or an empty directory.
+pub fn remove(path: &str) -> KernelResult {
+    volume().remove(path)
 }
 
 
-#[derive(Debug)]
-struct FileSystem(OnceCell<Fat<FatDevice>>);
+pub fn mkdir(path: &str) -> KernelResult<DirEntry> {
+    volume().mkdir(path)
+}
+
+
+#[inline]
+fn volume() -> MutexGuard<'static, FatVolume<FatDevice>> {
+    FS.volume
+        .get()
+        .unwrap()
+        .lock()
+}
+
+
+/// Loads the ELF at the path into a new address space and runs it in ring 3 as a new task.
+///
+/// The ELF must be linked at [`USER_SPACE_START`] or above,
+/// which the applications built for the target `apps/app.json` are.
+/// The first element of `args` is passed as `argv[0]`,
+/// so it is usually the path of the application.
+/// See [`task::app::spawn_app`] for `on_exit`.
+pub fn spawn_elf(
+    path: &str,
+    args: &[&str],
+    on_exit: impl FnOnce(u64, i32) + 'static,
+) -> KernelResult<u64> {
+    let mut buff = read_file(path)?;
+    let (load_start, _) = ElfHeaderPtr::from_file_buff(&mut buff)
+        .phdr_table()
+        .calc_load_address_range();
+    if load_start < USER_SPACE_START {
+        return Err(PagingReason::NotUserSpace(load_start).into());
+    }
+
+    let mut address_space = AddressSpace::new()?;
+    let entry_point_addr = address_space.with_activated(|address_space| {
+        let mut allocator = FsAllocator::new(address_space);
+        ElfLoader::new()
+            .load(&mut buff, &mut allocator)
+            .and_then(|entry_point_addr| {
+                allocator
+                    .into_result()
+                    .map(|_| entry_point_addr)
+            })
+    })?;
+
+    task::app::spawn_app(*entry_point_addr, args, address_space, on_exit)
+}
+
+
+struct FileSystem {
+    volume: OnceCell<PreemptiveMutex<FatVolume<FatDevice>>>,
+}
 
 
 impl FileSystem {
     pub const fn uninit() -> Self {
-        Self(OnceCell::new())
+        Self {
+            volume: OnceCell::new(),
+        }
     }
 
 
-    pub fn init(&self, fat_volume: *mut u8) {
-        self.0
-            .set(Fat::new(FatDevice::new(fat_volume)))
-            .unwrap();
+    pub fn init(&self, fat_volume: *mut u8) -> KernelResult {
+        let bpb = BiosParameterBlock::read(&FatDevice::new(fat_volume, BOOT_SECTOR_SIZE))?;
+        let mut volume = FatVolume::new(FatDevice::new(fat_volume, bpb.volume_size()))?;
+        volume.set_clock(|| FatTimestamp::from(rtc::now()));
+
+        self.volume
+            .set(PreemptiveMutex::new(volume))
+            .map_err(|_| kernel_error!("File system is already initialized"))
     }
 }
 
@@ -78,24 +166,38 @@ impl FileSystem {
 unsafe impl Sync for FileSystem {}
 
 
+/// The volume image loaded into memory by the bootloader.
 #[derive(Clone, Debug)]
 pub struct FatDevice {
     fat_volume: *mut u8,
+    size: usize,
 }
 
 
 impl FatDevice {
     #[inline]
-    pub const fn new(fat_volume: *mut u8) -> Self {
-        Self { fat_volume }
+    pub const fn new(fat_volume: *mut u8, size: usize) -> Self {
+        Self { fat_volume, size }
+    }
+
+
+    fn check_range(&self, offset: usize, bytes: usize) -> KernelResult {
+        match offset.checked_add(bytes) {
+            Some(end) if end <= self.size => Ok(()),
+            _ => Err(kernel_error!(
+                "Out of the fat device: offset = {offset:#X}, bytes = {bytes:#X}"
+            )),
+        }
     }
 }
 
 
-impl FatDeviceAccessible for FatDevice {
-    fn read(&self, buff: &mut [u8], offset: usize, bytes: usize) -> Result<(), FatDeviceError> {
+impl VolumeDevice for FatDevice {
+    fn read(&self, buff: &mut [u8], offset: usize) -> KernelResult {
+        self.check_range(offset, buff.len())?;
+
         unsafe {
-            let src = core::slice::from_raw_parts(self.fat_volume.add(offset), bytes);
+            let src = core::slice::from_raw_parts(self.fat_volume.add(offset), buff.len());
             buff.copy_from_slice(src);
         }
 
@@ -103,8 +205,15 @@ impl FatDeviceAccessible for FatDevice {
     }
 
 
-    fn write(&mut self, _buff: &[u8], _offset: usize) -> Result<(), FatDeviceError> {
-        todo!()
+    fn write(&mut self, buff: &[u8], offset: usize) -> KernelResult {
+        self.check_range(offset, buff.len())?;
+
+        unsafe {
+            let dest = core::slice::from_raw_parts_mut(self.fat_volume.add(offset), buff.len());
+            dest.copy_from_slice(buff);
+        }
+
+        Ok(())
     }
 }
 
@@ -1,22 +1,80 @@
 use alloc::vec;
 
-use common_lib::error::CommonResult;
+use common_lib::error::{CommonError, CommonResult};
 use common_lib::loader;
 
-pub struct FsAllocator;
+use crate::paging::address_space::AddressSpace;
+use crate::paging::region::{Region, RegionKind};
+use crate::paging::PAGE_SIZE_4K;
+
+/// Loads segments into the address space, which must be loaded into CR3 while loading.
+///
+/// Only the pages the contents of the file are copied to are mapped while loading,
+/// and the rest such as `.bss` is mapped on demand when first touched.
+///
+/// The callbacks of the loader can't return errors,
+/// so the first failure to map is kept and the later copies are skipped;
+/// check [`FsAllocator::into_result`] after loading.
+pub struct FsAllocator<'a> {
+    address_space: &'a mut AddressSpace,
+    error: Option<CommonError>,
+}
 
 
-impl loader::alloc::Allocatable for FsAllocator {
+impl<'a> FsAllocator<'a> {
     #[inline]
-    fn copy_mem(&self, dest: *mut u8, src: *const u8, size: usize) {
-        unsafe { dest.copy_from(src, size) }
+    pub fn new(address_space: &'a mut AddressSpace) -> Self {
+        Self {
+            address_space,
+            error: None,
+        }
     }
 
 
+    /// Returns the first error while loading.
     #[inline]
+    pub fn into_result(self) -> CommonResult {
+        self.error.map_or(Ok(()), Err)
+    }
+
+
+    /// Maps the pages and returns whether they can be written to.
+    fn map(&mut self, dest: *const u8, size: usize) -> bool {
+        if self.error.is_some() {
+            return false;
+        }
+
+        let start = dest as u64;
+        let result = self
+            .address_space
+            .map_zeroed_range(start, start + size as u64, true);
+        if result.is_err() {
+            self.error = Some(CommonError::FailedToAllocatePages(start));
+        }
+
+        self.error.is_none()
+    }
+}
+
+
+impl loader::alloc::Allocatable for FsAllocator<'_> {
+    fn copy_mem(&mut self, dest: *mut u8, src: *const u8, size: usize) {
+        if self.map(dest, size) {
+            unsafe { dest.copy_from(src, size) }
+        }
+    }
+
+
     fn set_mem(&mut self, buff: *mut u8, size: usize, value: u8) {
-        unsafe {
-            buff.write_bytes(value, size);
+        // Pages mapped on demand are zeroed when first touched.
+        if value == 0 {
+            return;
+        }
+
+        if self.map(buff, size) {
+            unsafe {
+                buff.write_bytes(value, size);
+            }
         }
     }
 
@@ -38,8 +96,13 @@ impl loader::alloc::Allocatable for FsAllocator {
     }
 
 
-    #[inline]
-    fn allocate_pages(&mut self, _phys_addr: u64, _count: usize) -> CommonResult {
-        Ok(())
+    fn allocate_pages(&mut self, phys_addr: u64, count: usize) -> CommonResult {
+        let page_mask = PAGE_SIZE_4K as u64 - 1;
+        let start = phys_addr & !page_mask;
+        let end = (phys_addr + (count * PAGE_SIZE_4K) as u64 + page_mask) & !page_mask;
+
+        self.address_space
+            .add_region(Region::new(start, end, true, RegionKind::Anonymous))
+            .map_err(|_| CommonError::FailedToAllocatePages(phys_addr))
     }
 }
@@ -0,0 +1,171 @@
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::{Display, Formatter};
+
+pub const SEPARATOR: char = '/';
+
+
+/// A normalized absolute path.
+///
+/// `.` and empty components are dropped and `..` removes the previous component,
+/// so the inner string always starts with `/` and never ends with it except for the root.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Path(String);
+
+
+impl Path {
+    #[inline]
+    pub fn root() -> Self {
+        Self(SEPARATOR.to_string())
+    }
+
+
+    /// Parses the path from the root directory.
+    ///
+    /// Relative paths are treated as if they started with `/`.
+    pub fn new(path: &str) -> Self {
+        Self::root().join(path)
+    }
+
+
+    /// Resolves the `path` with this path as the current directory.
+    ///
+    /// If the `path` is absolute, this path is ignored.
+    pub fn join(&self, path: &str) -> Self {
+        let mut names: Vec<&str> = if path.starts_with(SEPARATOR) {
+            Vec::new()
+        } else {
+            self.components().collect()
+        };
+
+        for name in path.split(SEPARATOR) {
+            match name {
+                "" | "." => {}
+                ".." => {
+                    names.pop();
+                }
+                name => names.push(name),
+            }
+        }
+
+        let mut path = String::new();
+        for name in names {
+            path.push(SEPARATOR);
+            path.push_str(name);
+        }
+
+        if path.is_empty() {
+            Self::root()
+        } else {
+            Self(path)
+        }
+    }
+
+
+    #[inline]
+    pub fn is_root(&self) -> bool {
+        self.0.len() == 1
+    }
+
+
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+
+    pub fn components(&self) -> impl Iterator<Item = &str> {
+        self.0
+            .split(SEPARATOR)
+            .filter(|name| !name.is_empty())
+    }
+
+
+    /// Returns the last component, or `None` if this is the root directory.
+    pub fn file_name(&self) -> Option<&str> {
+        self.components().last()
+    }
+
+
+    /// Returns the parent directory, or `None` if this is the root directory.
+    pub fn parent(&self) -> Option<Self> {
+        if self.is_root() {
+            None
+        } else {
+            Some(self.join(".."))
+        }
+    }
+}
+
+
+impl Default for Path {
+    fn default() -> Self {
+        Self::root()
+    }
+}
+
+
+impl Display for Path {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::fs::path::Path;
+
+    #[test]
+    fn it_new_root() {
+        assert_eq!(Path::new("/").as_str(), "/");
+        assert_eq!(Path::new("").as_str(), "/");
+        assert!(Path::new("//").is_root());
+    }
+
+
+    #[test]
+    fn it_new_absolute() {
+        assert_eq!(Path::new("/a/b/c.txt").as_str(), "/a/b/c.txt");
+        assert_eq!(Path::new("/a//b/").as_str(), "/a/b");
+        assert_eq!(Path::new("a/b").as_str(), "/a/b");
+    }
+
+
+    #[test]
+    fn it_normalize_dots() {
+        assert_eq!(Path::new("/a/./b/../c.txt").as_str(), "/a/c.txt");
+        assert_eq!(Path::new("/a/b/../..").as_str(), "/");
+        assert_eq!(Path::new("/../..").as_str(), "/");
+    }
+
+
+    #[test]
+    fn it_join_relative() {
+        let cwd = Path::new("/apps");
+
+        assert_eq!(cwd.join("hlt.elf").as_str(), "/apps/hlt.elf");
+        assert_eq!(cwd.join("./bin/../hlt.elf").as_str(), "/apps/hlt.elf");
+        assert_eq!(cwd.join("..").as_str(), "/");
+        assert_eq!(cwd.join(".").as_str(), "/apps");
+    }
+
+
+    #[test]
+    fn it_join_absolute() {
+        let cwd = Path::new("/apps");
+
+        assert_eq!(cwd.join("/docs/a.txt").as_str(), "/docs/a.txt");
+    }
+
+
+    #[test]
+    fn it_file_name_and_parent() {
+        let path = Path::new("/a/b/c.txt");
+
+        assert_eq!(path.file_name(), Some("c.txt"));
+        assert_eq!(path.parent(), Some(Path::new("/a/b")));
+        assert_eq!(Path::root().file_name(), None);
+        assert_eq!(Path::root().parent(), None);
+    }
+}
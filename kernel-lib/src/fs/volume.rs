@@ -0,0 +1,1150 @@
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::error::{FsReason, KernelError, KernelResult};
+use crate::fs::volume::bpb::BiosParameterBlock;
+use crate::fs::volume::dir_entry::{
+    exact_short_name, new_long_name_entries, new_short_entry, numbered_short_name,
+    short_name_checksum, short_name_to_string, validate_name, DirEntry, FatTimestamp,
+    LongNameBuilder, ATTR_ARCHIVE, ATTR_DIRECTORY, ATTR_LONG_NAME, ATTR_VOLUME_ID,
+    DELETED_MARK, DIR_ENTRY_SIZE,
+};
+
+pub mod bpb;
+pub mod dir_entry;
+
+const FAT_ENTRY_MASK: u32 = 0x0FFF_FFFF;
+const END_OF_CHAIN: u32 = 0x0FFF_FFFF;
+const END_OF_CHAIN_MIN: u32 = 0x0FFF_FFF8;
+
+const FS_INFO_LEAD_SIGNATURE: u32 = 0x4161_5252;
+const FS_INFO_STRUCT_SIGNATURE: u32 = 0x6141_7272;
+const FS_INFO_UNKNOWN: u32 = 0xFFFF_FFFF;
+
+
+/// The storage a [`FatVolume`] is on.
+pub trait VolumeDevice {
+    fn read(&self, buff: &mut [u8], offset: usize) -> KernelResult;
+
+
+    fn write(&mut self, buff: &[u8], offset: usize) -> KernelResult;
+}
+
+
+/// Reads and writes files on a FAT32 volume.
+///
+/// Every operation goes straight to the device.
+///
+/// Paths are absolute and separated by `/`.
+pub struct FatVolume<Device> {
+    device: Device,
+    bpb: BiosParameterBlock,
+    clock: fn() -> FatTimestamp,
+}
+
+
+impl<Device> FatVolume<Device>
+where
+    Device: VolumeDevice,
+{
+    pub fn new(device: Device) -> KernelResult<Self> {
+        let bpb = BiosParameterBlock::read(&device)?;
+
+        Ok(Self {
+            device,
+            bpb,
+            clock: FatTimestamp::default,
+        })
+    }
+
+
+    /// Changes the source of the timestamps written to directory entries.
+    #[inline]
+    pub fn set_clock(&mut self, clock: fn() -> FatTimestamp) {
+        self.clock = clock;
+    }
+
+
+    #[inline]
+    pub fn bpb(&self) -> &BiosParameterBlock {
+        &self.bpb
+    }
+
+
+    /// Returns the entry at the path.
+    ///
+    /// The root directory has no entry, so it results in [`FsReason::NotFound`].
+    pub fn find(&self, path: &str) -> KernelResult<DirEntry> {
+        let (parent, name) = split_parent(path).ok_or_else(|| not_found(path))?;
+        let dir = self.find_dir_cluster(parent)?;
+
+        self.find_in_dir(dir, name)?
+            .ok_or_else(|| not_found(path))
+    }
+
+
+    pub fn read_dir(&self, path: &str) -> KernelResult<Vec<DirEntry>> {
+        let cluster = self.find_dir_cluster(path)?;
+        self.entries(cluster)
+    }
+
+
+    pub fn read_file(&self, path: &str) -> KernelResult<Vec<u8>> {
+        let entry = self.find_file(path)?;
+        self.read_entry(&entry)
+    }
+
+
+    /// Returns the numbers of the clusters in the chain starting from `first_cluster`.
+    pub fn cluster_chain(&self, first_cluster: u32) -> KernelResult<Vec<u32>> {
+        let mut chain = Vec::new();
+        let mut cluster = first_cluster;
+
+        while self.is_data_cluster(cluster) {
+            if self.bpb.cluster_count() as usize <= chain.len() {
+                return Err(FsReason::InvalidVolume("Cluster chain has a loop").into());
+            }
+
+            chain.push(cluster);
+            cluster = self.fat_entry(cluster)?;
+        }
+
+        Ok(chain)
+    }
+
+
+    pub fn create_file(&mut self, path: &str) -> KernelResult<DirEntry> {
+        self.create_entry(path, ATTR_ARCHIVE, 0)
+    }
+
+
+    /// Creates an empty directory.
+    ///
+    /// The entry is added only after the cluster of the directory is initialized,
+    /// and the cluster is freed if anything fails, so a failed call leaves nothing behind.
+    pub fn mkdir(&mut self, path: &str) -> KernelResult<DirEntry> {
+        let (parent, _) = split_parent(path)
+            .ok_or_else(|| FsReason::AlreadyExists(String::from(path)))?;
+        let parent_cluster = self.find_dir_cluster(parent)?;
+        let cluster = self.allocate_cluster()?;
+
+        let result = self
+            .write_dot_entries(cluster, parent_cluster)
+            .and_then(|_| self.create_entry(path, ATTR_DIRECTORY, cluster));
+        if result.is_err() {
+            self.free_clusters(&[cluster])?;
+        }
+
+        result
+    }
+
+
+    /// Replaces the contents of the file, creating it if it doesn't exist.
+    ///
+    /// The contents are written to a new chain and the old chain is freed
+    /// only after the entry points to the new one,
+    /// so the file keeps its old contents if the write fails.
+    pub fn write_file(&mut self, path: &str, buff: &[u8]) -> KernelResult<DirEntry> {
+        let entry = self.find_or_create_file(path)?;
+
+        let chain = self.allocate_chain(self.clusters_for(buff.len()))?;
+
+        let mut new_entry = entry.clone();
+        new_entry.set_first_cluster(chain.first().copied().unwrap_or(0));
+        new_entry.set_file_size(buff.len());
+        new_entry.set_modified((self.clock)());
+
+        let result = self
+            .write_chain(&chain, buff)
+            .and_then(|_| self.write_entry(&new_entry));
+        if let Err(e) = result {
+            self.write_entry(&entry)?;
+            self.free_clusters(&chain)?;
+            return Err(e);
+        }
+
+        self.free_chain(entry.first_cluster())?;
+
+        Ok(new_entry)
+    }
+
+
+    /// Writes the buffer at `offset` in the file,
+    /// touching only the clusters in the range and the ones the file grows by.
+    ///
+    /// The gap is filled with zeros if `offset` is beyond the end of the file.
+    pub fn write_at(&mut self, path: &str, offset: usize, buff: &[u8]) -> KernelResult<DirEntry> {
+        let mut entry = self.find_file(path)?;
+        self.write_range(&mut entry, offset, buff)?;
+
+        Ok(entry)
+    }
+
+
+    /// Appends to the end of the file, creating it if it doesn't exist.
+    pub fn append_file(&mut self, path: &str, buff: &[u8]) -> KernelResult<DirEntry> {
+        let mut entry = self.find_or_create_file(path)?;
+        let size = entry.file_size();
+        self.write_range(&mut entry, size, buff)?;
+
+        Ok(entry)
+    }
+
+
+    /// Changes the size of the file.
+    ///
+    /// Grown bytes are filled with zeros.
+    pub fn truncate(&mut self, path: &str, size: usize) -> KernelResult<DirEntry> {
+        let mut entry = self.find_file(path)?;
+        let current = entry.file_size();
+
+        if size <= current {
+            let clusters = self.clusters_for(size);
+            self.resize_chain(&mut entry, clusters)?;
+            entry.set_file_size(size);
+            entry.set_modified((self.clock)());
+            self.write_entry(&entry)?;
+        } else {
+            self.write_range(&mut entry, current, &vec![0; size - current])?;
+        }
+
+        Ok(entry)
+    }
+
+
+    /// Removes a file or an empty directory.
+    pub fn remove(&mut self, path: &str) -> KernelResult {
+        let entry = self.find(path)?;
+        if is_dot_entry(&entry) {
+            return Err(FsReason::InvalidName(String::from(path)).into());
+        }
+
+        if entry.is_dir()
+            && self
+                .entries(entry.first_cluster())?
+                .iter()
+                .any(|child| !is_dot_entry(child))
+        {
+            return Err(FsReason::DirectoryNotEmpty(String::from(path)).into());
+        }
+
+        for offset in entry.slot_offsets() {
+            self.write(&[DELETED_MARK], offset)?;
+        }
+
+        self.free_chain(entry.first_cluster())
+    }
+
+
+    fn write_dot_entries(&mut self, cluster: u32, parent_cluster: u32) -> KernelResult {
+        let now = (self.clock)();
+        let parent_cluster = if parent_cluster == self.bpb.root_cluster() {
+            0
+        } else {
+            parent_cluster
+        };
+        let dot = new_short_entry(b".          ", ATTR_DIRECTORY, cluster, now);
+        let dot_dot = new_short_entry(b"..         ", ATTR_DIRECTORY, parent_cluster, now);
+
+        let offset = self.bpb.cluster_offset(cluster);
+        self.write(&dot, offset)?;
+        self.write(&dot_dot, offset + DIR_ENTRY_SIZE)
+    }
+
+
+    fn find_file(&self, path: &str) -> KernelResult<DirEntry> {
+        let entry = self.find(path)?;
+        if entry.is_dir() {
+            return Err(FsReason::IsDirectory(String::from(path)).into());
+        }
+
+        Ok(entry)
+    }
+
+
+    fn find_or_create_file(&mut self, path: &str) -> KernelResult<DirEntry> {
+        match self.find_file(path) {
+            Ok(entry) => Ok(entry),
+            Err(KernelError::FailedOperateFs(FsReason::NotFound(_))) => self.create_file(path),
+            Err(e) => Err(e),
+        }
+    }
+
+
+    fn find_dir_cluster(&self, path: &str) -> KernelResult<u32> {
+        let mut cluster = self.bpb.root_cluster();
+
+        for name in components(path) {
+            let entry = self
+                .find_in_dir(cluster, name)?
+                .ok_or_else(|| not_found(path))?;
+
+            if !entry.is_dir() {
+                return Err(FsReason::NotDirectory(String::from(path)).into());
+            }
+
+            cluster = match entry.first_cluster() {
+                0 => self.bpb.root_cluster(),
+                cluster => cluster,
+            };
+        }
+
+        Ok(cluster)
+    }
+
+
+    fn find_in_dir(&self, dir_cluster: u32, name: &str) -> KernelResult<Option<DirEntry>> {
+        Ok(self
+            .entries(dir_cluster)?
+            .into_iter()
+            .find(|entry| {
+                entry
+                    .name()
+                    .eq_ignore_ascii_case(name)
+                    || entry
+                        .short_name()
+                        .eq_ignore_ascii_case(name)
+            }))
+    }
+
+
+    fn entries(&self, dir_cluster: u32) -> KernelResult<Vec<DirEntry>> {
+        let mut entries = Vec::new();
+        let mut long_name = LongNameBuilder::default();
+
+        for offset in self.slot_offsets(dir_cluster)? {
+            let raw = self.read_slot(offset)?;
+
+            match raw[0] {
+                0x00 => break,
+                DELETED_MARK => long_name.clear(),
+                _ if raw[11] & 0x3F == ATTR_LONG_NAME => long_name.push(&raw, offset),
+                _ if raw[11] & ATTR_VOLUME_ID != 0 => long_name.clear(),
+                _ => {
+                    let short_name: &[u8; 11] = raw[..11].try_into().unwrap();
+                    let (name, long_name_offsets) = long_name
+                        .take(short_name)
+                        .unwrap_or_else(|| (short_name_to_string(short_name, raw[12]), Vec::new()));
+
+                    entries.push(DirEntry::new(name, raw, offset, long_name_offsets));
+                }
+            }
+        }
+
+        Ok(entries)
+    }
+
+
+    fn create_entry(&mut self, path: &str, attr: u8, first_cluster: u32) -> KernelResult<DirEntry> {
+        let (parent, name) = split_parent(path)
+            .ok_or_else(|| FsReason::AlreadyExists(String::from(path)))?;
+        validate_name(name)?;
+
+        let dir_cluster = self.find_dir_cluster(parent)?;
+        let entries = self.entries(dir_cluster)?;
+        if entries.iter().any(|entry| {
+            entry
+                .name()
+                .eq_ignore_ascii_case(name)
+                || entry
+                    .short_name()
+                    .eq_ignore_ascii_case(name)
+        }) {
+            return Err(FsReason::AlreadyExists(String::from(path)).into());
+        }
+
+        let (short_name, long_name_entries) = match exact_short_name(name) {
+            Some(short_name) => (short_name, Vec::new()),
+            None => {
+                let short_name = (1..)
+                    .map(|n| numbered_short_name(name, n))
+                    .find(|short_name| {
+                        entries
+                            .iter()
+                            .all(|entry| entry.short_name_raw() != short_name)
+                    })
+                    .unwrap();
+
+                let checksum = short_name_checksum(&short_name);
+                (short_name, new_long_name_entries(name, checksum))
+            }
+        };
+
+        let raw = new_short_entry(&short_name, attr, first_cluster, (self.clock)());
+        let slots = self.allocate_slots(dir_cluster, long_name_entries.len() + 1)?;
+        let offset = *slots.last().unwrap();
+
+        let result = long_name_entries
+            .iter()
+            .chain([&raw])
+            .zip(slots.iter())
+            .try_for_each(|(entry, offset)| self.write(entry, *offset));
+        if let Err(e) = result {
+            // The long name entries without the short entry would be orphans.
+            for offset in &slots {
+                self.write(&[DELETED_MARK], *offset)?;
+            }
+            return Err(e);
+        }
+
+        Ok(DirEntry::new(
+            String::from(name),
+            raw,
+            offset,
+            slots[..slots.len() - 1].to_vec(),
+        ))
+    }
+
+
+    /// Finds `count` consecutive free slots in the directory,
+    /// extending the directory if there are not enough.
+    fn allocate_slots(&mut self, dir_cluster: u32, count: usize) -> KernelResult<Vec<usize>> {
+        loop {
+            let mut free = Vec::with_capacity(count);
+            for offset in self.slot_offsets(dir_cluster)? {
+                let mark = self.read_slot(offset)?[0];
+
+                if mark == 0x00 || mark == DELETED_MARK {
+                    free.push(offset);
+                    if free.len() == count {
+                        return Ok(free);
+                    }
+                } else {
+                    free.clear();
+                }
+            }
+
+            let last = *self
+                .cluster_chain(dir_cluster)?
+                .last()
+                .unwrap();
+            let cluster = self.allocate_cluster()?;
+            self.set_fat_entry(last, cluster)?;
+        }
+    }
+
+
+    fn slot_offsets(&self, dir_cluster: u32) -> KernelResult<impl Iterator<Item = usize>> {
+        let bytes_per_cluster = self.bpb.bytes_per_cluster();
+        let offsets: Vec<usize> = self
+            .cluster_chain(dir_cluster)?
+            .into_iter()
+            .map(|cluster| self.bpb.cluster_offset(cluster))
+            .collect();
+
+        Ok(offsets
+            .into_iter()
+            .flat_map(move |base| (0..bytes_per_cluster).step_by(DIR_ENTRY_SIZE).map(move |i| base + i)))
+    }
+
+
+    fn read_slot(&self, offset: usize) -> KernelResult<[u8; DIR_ENTRY_SIZE]> {
+        let mut raw = [0u8; DIR_ENTRY_SIZE];
+        self.read(&mut raw, offset)?;
+
+        Ok(raw)
+    }
+
+
+    fn read_entry(&self, entry: &DirEntry) -> KernelResult<Vec<u8>> {
+        let size = entry.file_size();
+        let bytes_per_cluster = self.bpb.bytes_per_cluster();
+        let mut buff = vec![0; size];
+
+        for (chunk, cluster) in buff
+            .chunks_mut(bytes_per_cluster)
+            .zip(self.cluster_chain(entry.first_cluster())?)
+        {
+            self.read(chunk, self.bpb.cluster_offset(cluster))?;
+        }
+
+        Ok(buff)
+    }
+
+
+    fn write_range(&mut self, entry: &mut DirEntry, offset: usize, buff: &[u8]) -> KernelResult {
+        let end = offset + buff.len();
+        let bytes_per_cluster = self.bpb.bytes_per_cluster();
+
+        if entry.file_size() < offset {
+            let size = entry.file_size();
+            self.write_range(entry, size, &vec![0; offset - size])?;
+        }
+
+        let clusters = self
+            .clusters_for(end)
+            .max(self.clusters_for(entry.file_size()));
+        let chain = self.resize_chain(entry, clusters)?;
+
+        let mut written = 0;
+        while written < buff.len() {
+            let pos = offset + written;
+            let in_cluster = pos % bytes_per_cluster;
+            let len = (bytes_per_cluster - in_cluster).min(buff.len() - written);
+            let cluster = chain[pos / bytes_per_cluster];
+
+            self.write(
+                &buff[written..written + len],
+                self.bpb.cluster_offset(cluster) + in_cluster,
+            )?;
+            written += len;
+        }
+
+        entry.set_file_size(entry.file_size().max(end));
+        entry.set_modified((self.clock)());
+        self.write_entry(entry)
+    }
+
+
+    /// Grows or shrinks the chain of the entry to exactly `clusters` clusters.
+    fn resize_chain(&mut self, entry: &mut DirEntry, clusters: usize) -> KernelResult<Vec<u32>> {
+        let mut chain = self.cluster_chain(entry.first_cluster())?;
+
+        if clusters < chain.len() {
+            let rest = chain.split_off(clusters);
+            match chain.last() {
+                Some(last) => self.set_fat_entry(*last, END_OF_CHAIN)?,
+                None => entry.set_first_cluster(0),
+            }
+            self.free_chain(rest[0])?;
+        }
+
+        while chain.len() < clusters {
+            let cluster = self.allocate_cluster()?;
+            match chain.last() {
+                Some(last) => self.set_fat_entry(*last, cluster)?,
+                None => entry.set_first_cluster(cluster),
+            }
+            chain.push(cluster);
+        }
+
+        Ok(chain)
+    }
+
+
+    /// Allocates a new chain of `clusters` clusters,
+    /// freeing the clusters taken so far if it fails.
+    fn allocate_chain(&mut self, clusters: usize) -> KernelResult<Vec<u32>> {
+        let mut chain = Vec::with_capacity(clusters);
+
+        while chain.len() < clusters {
+            if let Err(e) = self.extend_chain(&mut chain) {
+                self.free_clusters(&chain)?;
+                return Err(e);
+            }
+        }
+
+        Ok(chain)
+    }
+
+
+    fn extend_chain(&mut self, chain: &mut Vec<u32>) -> KernelResult {
+        let cluster = self.allocate_cluster()?;
+        chain.push(cluster);
+
+        match chain.len() {
+            1 => Ok(()),
+            len => self.set_fat_entry(chain[len - 2], cluster),
+        }
+    }
+
+
+    /// Writes the buffer from the start of the chain.
+    fn write_chain(&mut self, chain: &[u32], buff: &[u8]) -> KernelResult {
+        for (chunk, cluster) in buff
+            .chunks(self.bpb.bytes_per_cluster())
+            .zip(chain)
+        {
+            self.write(chunk, self.bpb.cluster_offset(*cluster))?;
+        }
+
+        Ok(())
+    }
+
+
+    fn write_entry(&mut self, entry: &DirEntry) -> KernelResult {
+        self.write(entry.raw(), entry.offset())
+    }
+
+
+    /// Takes a free cluster, marks it as the end of a chain and fills it with zeros.
+    fn allocate_cluster(&mut self) -> KernelResult<u32> {
+        let count = self.bpb.cluster_count();
+        let hint = match self.fs_info_field(492)? {
+            Some(next) if 2 <= next && next < count + 2 => next,
+            _ => 2,
+        };
+
+        let cluster = (hint..count + 2)
+            .chain(2..hint)
+            .find(|cluster| matches!(self.fat_entry(*cluster), Ok(0)))
+            .ok_or(FsReason::NoFreeCluster)?;
+
+        self.set_fat_entry(cluster, END_OF_CHAIN)?;
+        self.write(
+            &vec![0; self.bpb.bytes_per_cluster()],
+            self.bpb.cluster_offset(cluster),
+        )?;
+
+        self.update_free_count(|free| free.checked_sub(1))?;
+        self.set_fs_info_field(492, cluster + 1)?;
+
+        Ok(cluster)
+    }
+
+
+    fn free_chain(&mut self, first_cluster: u32) -> KernelResult {
+        let chain = self.cluster_chain(first_cluster)?;
+        self.free_clusters(&chain)
+    }
+
+
+    fn free_clusters(&mut self, clusters: &[u32]) -> KernelResult {
+        for cluster in clusters {
+            self.set_fat_entry(*cluster, 0)?;
+        }
+
+        let freed = clusters.len() as u32;
+        self.update_free_count(|free| free.checked_add(freed))
+    }
+
+
+    #[inline]
+    fn is_data_cluster(&self, cluster: u32) -> bool {
+        (2..self.bpb.cluster_count() + 2).contains(&cluster) && cluster < END_OF_CHAIN_MIN
+    }
+
+
+    #[inline]
+    fn clusters_for(&self, bytes: usize) -> usize {
+        let bytes_per_cluster = self.bpb.bytes_per_cluster();
+        (bytes + bytes_per_cluster - 1) / bytes_per_cluster
+    }
+
+
+    fn fat_entry(&self, cluster: u32) -> KernelResult<u32> {
+        let mut buff = [0u8; 4];
+        self.read(&mut buff, self.bpb.fat_offset(0) + cluster as usize * 4)?;
+
+        Ok(u32::from_le_bytes(buff) & FAT_ENTRY_MASK)
+    }
+
+
+    /// Writes the entry to every copy of the allocation table,
+    /// keeping the reserved upper 4 bits as they are.
+    fn set_fat_entry(&mut self, cluster: u32, value: u32) -> KernelResult {
+        for index in 0..self.bpb.fats() {
+            let offset = self.bpb.fat_offset(index) + cluster as usize * 4;
+            let mut buff = [0u8; 4];
+            self.read(&mut buff, offset)?;
+
+            let entry = (u32::from_le_bytes(buff) & !FAT_ENTRY_MASK) | (value & FAT_ENTRY_MASK);
+            self.write(&entry.to_le_bytes(), offset)?;
+        }
+
+        Ok(())
+    }
+
+
+    fn update_free_count(&mut self, f: impl FnOnce(u32) -> Option<u32>) -> KernelResult {
+        if let Some(free) = self
+            .fs_info_field(488)?
+            .and_then(f)
+        {
+            self.set_fs_info_field(488, free)?;
+        }
+
+        Ok(())
+    }
+
+
+    /// Reads a field of the FSInfo sector.
+    ///
+    /// Returns `None` if the volume has no valid FSInfo or the value is unknown.
+    fn fs_info_field(&self, field_offset: usize) -> KernelResult<Option<u32>> {
+        let Some(offset) = self.valid_fs_info_offset()? else {
+            return Ok(None);
+        };
+
+        let mut buff = [0u8; 4];
+        self.read(&mut buff, offset + field_offset)?;
+
+        Ok(Some(u32::from_le_bytes(buff)).filter(|value| *value != FS_INFO_UNKNOWN))
+    }
+
+
+    fn set_fs_info_field(&mut self, field_offset: usize, value: u32) -> KernelResult {
+        if let Some(offset) = self.valid_fs_info_offset()? {
+            self.write(&value.to_le_bytes(), offset + field_offset)?;
+        }
+
+        Ok(())
+    }
+
+
+    fn valid_fs_info_offset(&self) -> KernelResult<Option<usize>> {
+        let Some(offset) = self.bpb.fs_info_offset() else {
+            return Ok(None);
+        };
+
+        let mut lead = [0u8; 4];
+        let mut structure = [0u8; 4];
+        self.read(&mut lead, offset)?;
+        self.read(&mut structure, offset + 484)?;
+
+        let valid = u32::from_le_bytes(lead) == FS_INFO_LEAD_SIGNATURE
+            && u32::from_le_bytes(structure) == FS_INFO_STRUCT_SIGNATURE;
+
+        Ok(valid.then_some(offset))
+    }
+
+
+    #[inline]
+    fn read(&self, buff: &mut [u8], offset: usize) -> KernelResult {
+        self.device.read(buff, offset)
+    }
+
+
+    #[inline]
+    fn write(&mut self, buff: &[u8], offset: usize) -> KernelResult {
+        self.device.write(buff, offset)
+    }
+}
+
+
+#[inline]
+fn not_found(path: &str) -> KernelError {
+    FsReason::NotFound(String::from(path)).into()
+}
+
+
+#[inline]
+fn is_dot_entry(entry: &DirEntry) -> bool {
+    entry.short_name_raw() == b".          " || entry.short_name_raw() == b"..         "
+}
+
+
+#[inline]
+fn components(path: &str) -> impl Iterator<Item = &str> {
+    path.split('/')
+        .filter(|name| !name.is_empty())
+}
+
+
+/// Splits the path into the parent directory and the last component.
+///
+/// Returns `None` for the root directory.
+fn split_parent(path: &str) -> Option<(&str, &str)> {
+    let path = path.trim_end_matches('/');
+    let name = components(path).last()?;
+
+    Some((&path[..path.len() - name.len()], name))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    use crate::error::{FsReason, KernelError, KernelResult};
+    use crate::fs::volume::dir_entry::{FatTimestamp, DIR_ENTRY_SIZE};
+    use crate::fs::volume::{FatVolume, VolumeDevice};
+    use crate::fs::FatDevice;
+    use crate::kernel_error;
+
+    const SECTOR: usize = 512;
+    const TOTAL_SECTORS: usize = 2048;
+    const RESERVED_SECTORS: usize = 32;
+    const FAT_SECTORS: usize = 16;
+
+
+    /// Fails the writes to `fail_offset`.
+    struct FailingDevice {
+        device: FatDevice,
+        fail_offset: usize,
+    }
+
+
+    impl VolumeDevice for FailingDevice {
+        fn read(&self, buff: &mut [u8], offset: usize) -> KernelResult {
+            self.device.read(buff, offset)
+        }
+
+
+        fn write(&mut self, buff: &[u8], offset: usize) -> KernelResult {
+            if offset == self.fail_offset {
+                return Err(kernel_error!("Injected write failure at {offset}"));
+            }
+
+            self.device.write(buff, offset)
+        }
+    }
+
+
+    fn new_volume(buff: &mut Vec<u8>) -> FatVolume<FatDevice> {
+        FatVolume::new(format(buff)).unwrap()
+    }
+
+
+    /// Formats a 1 MiB FAT32 volume with one sector per cluster.
+    fn format(buff: &mut Vec<u8>) -> FatDevice {
+        buff.clear();
+        buff.resize(TOTAL_SECTORS * SECTOR, 0);
+
+        let put_u16 = |buff: &mut Vec<u8>, offset: usize, value: u16| {
+            buff[offset..offset + 2].copy_from_slice(&value.to_le_bytes())
+        };
+        let put_u32 = |buff: &mut Vec<u8>, offset: usize, value: u32| {
+            buff[offset..offset + 4].copy_from_slice(&value.to_le_bytes())
+        };
+
+        put_u16(buff, 11, SECTOR as u16);
+        buff[13] = 1;
+        put_u16(buff, 14, RESERVED_SECTORS as u16);
+        buff[16] = 2;
+        put_u32(buff, 32, TOTAL_SECTORS as u32);
+        put_u32(buff, 36, FAT_SECTORS as u32);
+        put_u32(buff, 44, 2);
+        put_u16(buff, 48, 1);
+        buff[510] = 0x55;
+        buff[511] = 0xAA;
+
+        put_u32(buff, SECTOR, 0x4161_5252);
+        put_u32(buff, SECTOR + 484, 0x6141_7272);
+        put_u32(buff, SECTOR + 488, 1000);
+        put_u32(buff, SECTOR + 492, 3);
+
+        for fat in 0..2 {
+            let offset = (RESERVED_SECTORS + FAT_SECTORS * fat) * SECTOR;
+            put_u32(buff, offset, 0x0FFF_FFF8);
+            put_u32(buff, offset + 4, 0x0FFF_FFFF);
+            put_u32(buff, offset + 8, 0x0FFF_FFFF);
+        }
+
+        FatDevice::new(buff.as_mut_ptr(), buff.len())
+    }
+
+
+    fn names(volume: &FatVolume<FatDevice>, path: &str) -> Vec<alloc::string::String> {
+        volume
+            .read_dir(path)
+            .unwrap()
+            .iter()
+            .map(|entry| entry.name().into())
+            .collect()
+    }
+
+
+    #[test]
+    fn it_write_and_read_file() {
+        let mut buff = Vec::new();
+        let mut volume = new_volume(&mut buff);
+
+        volume
+            .write_file("/HELLO.TXT", b"hello world")
+            .unwrap();
+
+        assert_eq!(volume.read_file("/hello.txt").unwrap(), b"hello world");
+        assert_eq!(names(&volume, "/"), ["HELLO.TXT"]);
+    }
+
+
+    #[test]
+    fn it_write_file_across_clusters() {
+        let mut buff = Vec::new();
+        let mut volume = new_volume(&mut buff);
+        let data: Vec<u8> = (0..SECTOR * 3 + 10)
+            .map(|i| i as u8)
+            .collect();
+
+        let entry = volume
+            .write_file("/DATA.BIN", &data)
+            .unwrap();
+
+        assert_eq!(volume.read_file("/DATA.BIN").unwrap(), data);
+        assert_eq!(
+            volume
+                .cluster_chain(entry.first_cluster())
+                .unwrap()
+                .len(),
+            4
+        );
+    }
+
+
+    #[test]
+    fn it_overwrite_releases_clusters() {
+        let mut buff = Vec::new();
+        let mut volume = new_volume(&mut buff);
+
+        volume
+            .write_file("/A.BIN", &vec![1; SECTOR * 4])
+            .unwrap();
+        let entry = volume
+            .write_file("/A.BIN", b"short")
+            .unwrap();
+
+        assert_eq!(volume.read_file("/A.BIN").unwrap(), b"short");
+        assert_eq!(
+            volume
+                .cluster_chain(entry.first_cluster())
+                .unwrap()
+                .len(),
+            1
+        );
+        assert_eq!(volume.fs_info_field(488).unwrap(), Some(999));
+    }
+
+
+    #[test]
+    fn it_keep_old_contents_when_volume_is_full() {
+        let mut buff = Vec::new();
+        let mut volume = new_volume(&mut buff);
+        volume
+            .write_file("/A.TXT", b"old")
+            .unwrap();
+
+        let result = volume.write_file("/A.TXT", &vec![1; SECTOR * TOTAL_SECTORS]);
+
+        assert!(matches!(
+            result,
+            Err(KernelError::FailedOperateFs(FsReason::NoFreeCluster))
+        ));
+        assert_eq!(volume.read_file("/A.TXT").unwrap(), b"old");
+
+        let free_clusters = volume.bpb().cluster_count() as usize - 2;
+        volume
+            .write_file("/B.BIN", &vec![2; SECTOR * free_clusters])
+            .unwrap();
+        assert_eq!(volume.read_file("/A.TXT").unwrap(), b"old");
+    }
+
+
+    #[test]
+    fn it_append_file() {
+        let mut buff = Vec::new();
+        let mut volume = new_volume(&mut buff);
+
+        volume
+            .append_file("/LOG.TXT", b"first\n")
+            .unwrap();
+        volume
+            .append_file("/LOG.TXT", &vec![b'x'; SECTOR])
+            .unwrap();
+
+        let content = volume.read_file("/LOG.TXT").unwrap();
+        assert_eq!(content.len(), 6 + SECTOR);
+        assert_eq!(&content[..6], b"first\n");
+    }
+
+
+    #[test]
+    fn it_write_at_offset() {
+        let mut buff = Vec::new();
+        let mut volume = new_volume(&mut buff);
+        let first = volume
+            .write_file("/A.BIN", &vec![1; SECTOR * 2])
+            .unwrap();
+
+        let entry = volume
+            .write_at("/A.BIN", SECTOR - 2, b"abcd")
+            .unwrap();
+        assert_eq!(entry.first_cluster(), first.first_cluster());
+        assert_eq!(entry.file_size(), SECTOR * 2);
+
+        let content = volume.read_file("/A.BIN").unwrap();
+        assert_eq!(&content[SECTOR - 3..SECTOR + 3], b"\x01abcd\x01");
+
+        volume
+            .write_at("/A.BIN", SECTOR * 3, b"end")
+            .unwrap();
+        let content = volume.read_file("/A.BIN").unwrap();
+        assert_eq!(content.len(), SECTOR * 3 + 3);
+        assert!(content[SECTOR * 2..SECTOR * 3]
+            .iter()
+            .all(|b| *b == 0));
+        assert_eq!(&content[SECTOR * 3..], b"end");
+    }
+
+
+    #[test]
+    fn it_truncate() {
+        let mut buff = Vec::new();
+        let mut volume = new_volume(&mut buff);
+        volume
+            .write_file("/A.TXT", b"abcdef")
+            .unwrap();
+
+        volume.truncate("/A.TXT", 3).unwrap();
+        assert_eq!(volume.read_file("/A.TXT").unwrap(), b"abc");
+
+        volume.truncate("/A.TXT", 5).unwrap();
+        assert_eq!(volume.read_file("/A.TXT").unwrap(), b"abc\0\0");
+
+        let entry = volume.truncate("/A.TXT", 0).unwrap();
+        assert_eq!(entry.first_cluster(), 0);
+        assert!(volume
+            .read_file("/A.TXT")
+            .unwrap()
+            .is_empty());
+    }
+
+
+    #[test]
+    fn it_long_file_name() {
+        let mut buff = Vec::new();
+        let mut volume = new_volume(&mut buff);
+
+        volume
+            .write_file("/config file.json", b"{}")
+            .unwrap();
+        volume
+            .write_file("/config file2.json", b"[]")
+            .unwrap();
+
+        assert_eq!(names(&volume, "/"), ["config file.json", "config file2.json"]);
+        assert_eq!(
+            volume
+                .find("/config file2.json")
+                .unwrap()
+                .short_name(),
+            "CONFIG~2.JSO"
+        );
+        assert_eq!(volume.read_file("/CONFIG~2.JSO").unwrap(), b"[]");
+    }
+
+
+    #[test]
+    fn it_mkdir_and_create_in_dir() {
+        let mut buff = Vec::new();
+        let mut volume = new_volume(&mut buff);
+
+        volume.mkdir("/APPS").unwrap();
+        volume.mkdir("/APPS/data").unwrap();
+        volume
+            .write_file("/APPS/data/a.txt", b"nested")
+            .unwrap();
+
+        assert_eq!(names(&volume, "/APPS"), [".", "..", "data"]);
+        assert_eq!(names(&volume, "/APPS/data"), [".", "..", "a.txt"]);
+        assert_eq!(volume.read_file("/apps/DATA/a.txt").unwrap(), b"nested");
+        assert_eq!(names(&volume, "/APPS/data/.."), [".", "..", "data"]);
+        assert_eq!(names(&volume, "/APPS/.."), ["APPS"]);
+    }
+
+
+    #[test]
+    fn it_leave_nothing_when_mkdir_fails() {
+        let mut buff = Vec::new();
+        let device = format(&mut buff);
+        let bpb = *FatVolume::new(device.clone()).unwrap().bpb();
+
+        // The new directory takes the cluster 3, whose ".." entry fails,
+        // and then the slot of the entry in the root directory fails.
+        for fail_offset in [bpb.cluster_offset(3) + DIR_ENTRY_SIZE, bpb.cluster_offset(2)] {
+            let mut volume = FatVolume::new(FailingDevice {
+                device: device.clone(),
+                fail_offset,
+            })
+            .unwrap();
+
+            assert!(volume.mkdir("/DIR").is_err());
+            assert!(volume
+                .read_dir("/")
+                .unwrap()
+                .is_empty());
+            assert_eq!(volume.fat_entry(3).unwrap(), 0);
+            assert_eq!(volume.fs_info_field(488).unwrap(), Some(1000));
+        }
+
+        let mut volume = FatVolume::new(device).unwrap();
+        volume.mkdir("/DIR").unwrap();
+        assert_eq!(names(&volume, "/DIR"), [".", ".."]);
+    }
+
+
+    #[test]
+    fn it_extend_directory() {
+        let mut buff = Vec::new();
+        let mut volume = new_volume(&mut buff);
+
+        for i in 0..40 {
+            volume
+                .create_file(&alloc::format!("/FILE{i}.TXT"))
+                .unwrap();
+        }
+
+        assert_eq!(names(&volume, "/").len(), 40);
+        assert_eq!(volume.cluster_chain(2).unwrap().len(), 3);
+    }
+
+
+    #[test]
+    fn it_remove() {
+        let mut buff = Vec::new();
+        let mut volume = new_volume(&mut buff);
+        volume.mkdir("/DIR").unwrap();
+        volume
+            .write_file("/DIR/long name.txt", b"data")
+            .unwrap();
+
+        let result = volume.remove("/DIR");
+        assert!(matches!(
+            result,
+            Err(KernelError::FailedOperateFs(FsReason::DirectoryNotEmpty(_)))
+        ));
+
+        volume
+            .remove("/DIR/long name.txt")
+            .unwrap();
+        volume.remove("/DIR").unwrap();
+
+        assert!(names(&volume, "/").is_empty());
+        assert_eq!(volume.fs_info_field(488).unwrap(), Some(1000));
+    }
+
+
+    #[test]
+    fn it_reuse_deleted_slots() {
+        let mut buff = Vec::new();
+        let mut volume = new_volume(&mut buff);
+        volume.create_file("/A.TXT").unwrap();
+        volume.create_file("/B.TXT").unwrap();
+        volume.remove("/A.TXT").unwrap();
+
+        let entry = volume.create_file("/C.TXT").unwrap();
+
+        assert_eq!(entry.offset(), volume.bpb().cluster_offset(2));
+        assert_eq!(names(&volume, "/"), ["C.TXT", "B.TXT"]);
+    }
+
+
+    #[test]
+    fn it_error_already_exists() {
+        let mut buff = Vec::new();
+        let mut volume = new_volume(&mut buff);
+        volume.create_file("/A.TXT").unwrap();
+
+        assert!(matches!(
+            volume.create_file("/a.txt"),
+            Err(KernelError::FailedOperateFs(FsReason::AlreadyExists(_)))
+        ));
+    }
+
+
+    #[test]
+    fn it_write_timestamp() {
+        let mut buff = Vec::new();
+        let mut volume = new_volume(&mut buff);
+        volume.set_clock(|| FatTimestamp::new(2023, 7, 16, 12, 0, 0));
+
+        let entry = volume
+            .write_file("/A.TXT", b"a")
+            .unwrap();
+
+        assert_eq!(entry.modified(), FatTimestamp::new(2023, 7, 16, 12, 0, 0));
+        assert_eq!(entry.created(), FatTimestamp::new(2023, 7, 16, 12, 0, 0));
+    }
+}
@@ -0,0 +1,134 @@
+use crate::error::{FsReason, KernelResult};
+use crate::fs::volume::VolumeDevice;
+
+/// The subset of the FAT32 BIOS Parameter Block needed to locate
+/// the allocation tables and the data region.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct BiosParameterBlock {
+    bytes_per_sector: usize,
+    sectors_per_cluster: usize,
+    reserved_sectors: usize,
+    fats: usize,
+    fat_sectors: usize,
+    total_sectors: usize,
+    root_cluster: u32,
+    fs_info_sector: usize,
+}
+
+
+impl BiosParameterBlock {
+    pub fn read(device: &impl VolumeDevice) -> KernelResult<Self> {
+        let mut buff = [0u8; 512];
+        device.read(&mut buff, 0)?;
+
+        let u16_at = |offset: usize| u16::from_le_bytes([buff[offset], buff[offset + 1]]) as usize;
+        let u32_at = |offset: usize| {
+            u32::from_le_bytes([
+                buff[offset],
+                buff[offset + 1],
+                buff[offset + 2],
+                buff[offset + 3],
+            ])
+        };
+
+        if buff[510] != 0x55 || buff[511] != 0xAA {
+            return Err(FsReason::InvalidVolume("Boot signature is broken").into());
+        }
+
+        let total_sectors = match u16_at(19) {
+            0 => u32_at(32) as usize,
+            sectors => sectors,
+        };
+
+        let bpb = Self {
+            bytes_per_sector: u16_at(11),
+            sectors_per_cluster: buff[13] as usize,
+            reserved_sectors: u16_at(14),
+            fats: buff[16] as usize,
+            fat_sectors: u32_at(36) as usize,
+            total_sectors,
+            root_cluster: u32_at(44),
+            fs_info_sector: u16_at(48),
+        };
+
+        if bpb.bytes_per_sector == 0 || bpb.sectors_per_cluster == 0 || bpb.fat_sectors == 0 {
+            return Err(FsReason::InvalidVolume("Only FAT32 is supported").into());
+        }
+
+        Ok(bpb)
+    }
+
+
+    #[inline]
+    pub fn bytes_per_cluster(&self) -> usize {
+        self.bytes_per_sector * self.sectors_per_cluster
+    }
+
+
+    /// Returns the size of the whole volume in bytes.
+    #[inline]
+    pub fn volume_size(&self) -> usize {
+        self.total_sectors * self.bytes_per_sector
+    }
+
+
+    #[inline]
+    pub fn root_cluster(&self) -> u32 {
+        self.root_cluster
+    }
+
+
+    #[inline]
+    pub fn fats(&self) -> usize {
+        self.fats
+    }
+
+
+    /// Returns the byte offset of the `index`th allocation table.
+    #[inline]
+    pub fn fat_offset(&self, index: usize) -> usize {
+        (self.reserved_sectors + self.fat_sectors * index) * self.bytes_per_sector
+    }
+
+
+    /// Returns the byte offset of the first byte in the cluster.
+    #[inline]
+    pub fn cluster_offset(&self, cluster: u32) -> usize {
+        let sector = self.data_start_sector()
+            + (cluster as usize - 2) * self.sectors_per_cluster;
+
+        sector * self.bytes_per_sector
+    }
+
+
+    /// Returns the number of clusters in the data region.
+    ///
+    /// Valid cluster numbers are in `2..cluster_count() + 2`.
+    #[inline]
+    pub fn cluster_count(&self) -> u32 {
+        let data_sectors = self
+            .total_sectors
+            .saturating_sub(self.data_start_sector());
+        let clusters = data_sectors / self.sectors_per_cluster;
+        let fat_entries = self.fat_sectors * self.bytes_per_sector / 4;
+
+        clusters.min(fat_entries.saturating_sub(2)) as u32
+    }
+
+
+    /// Returns the byte offset of the FSInfo sector if the volume has one.
+    #[inline]
+    pub fn fs_info_offset(&self) -> Option<usize> {
+        if self.fs_info_sector == 0 || self.fs_info_sector == 0xFFFF {
+            None
+        } else {
+            Some(self.fs_info_sector * self.bytes_per_sector)
+        }
+    }
+
+
+    #[inline]
+    fn data_start_sector(&self) -> usize {
+        self.reserved_sectors + self.fats * self.fat_sectors
+    }
+}
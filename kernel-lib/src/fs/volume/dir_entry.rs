@@ -0,0 +1,649 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::error::{FsReason, KernelResult};
+use crate::timer::rtc::DateTime;
+
+pub const DIR_ENTRY_SIZE: usize = 32;
+
+pub const ATTR_READ_ONLY: u8 = 0x01;
+pub const ATTR_HIDDEN: u8 = 0x02;
+pub const ATTR_SYSTEM: u8 = 0x04;
+pub const ATTR_VOLUME_ID: u8 = 0x08;
+pub const ATTR_DIRECTORY: u8 = 0x10;
+pub const ATTR_ARCHIVE: u8 = 0x20;
+pub const ATTR_LONG_NAME: u8 = 0x0F;
+
+/// The first byte of a slot that has been deleted.
+pub const DELETED_MARK: u8 = 0xE5;
+
+const LONG_NAME_CHARS: usize = 13;
+const LAST_LONG_ENTRY: u8 = 0x40;
+const NT_RES_LOWER_BASE: u8 = 0x08;
+const NT_RES_LOWER_EXT: u8 = 0x10;
+
+
+/// A date and time packed the way FAT directory entries store them.
+///
+/// The resolution of the time is 2 seconds.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd)]
+pub struct FatTimestamp {
+    date: u16,
+    time: u16,
+}
+
+
+impl FatTimestamp {
+    /// 1980-01-01 00:00:00, the oldest time FAT can represent.
+    pub const EPOCH: FatTimestamp = FatTimestamp::from_raw((1 << 5) | 1, 0);
+
+
+    #[inline]
+    pub const fn from_raw(date: u16, time: u16) -> Self {
+        Self { date, time }
+    }
+
+
+    pub fn new(year: u16, month: u8, day: u8, hour: u8, minute: u8, second: u8) -> Self {
+        let date = (year.saturating_sub(1980) << 9) | ((month as u16) << 5) | day as u16;
+        let time = ((hour as u16) << 11) | ((minute as u16) << 5) | (second as u16 / 2);
+
+        Self { date, time }
+    }
+
+
+    #[inline]
+    pub const fn date(&self) -> u16 {
+        self.date
+    }
+
+
+    #[inline]
+    pub const fn time(&self) -> u16 {
+        self.time
+    }
+
+
+    #[inline]
+    pub const fn year(&self) -> u16 {
+        1980 + (self.date >> 9)
+    }
+
+
+    #[inline]
+    pub const fn month(&self) -> u8 {
+        ((self.date >> 5) & 0x0F) as u8
+    }
+
+
+    #[inline]
+    pub const fn day(&self) -> u8 {
+        (self.date & 0x1F) as u8
+    }
+
+
+    #[inline]
+    pub const fn hour(&self) -> u8 {
+        (self.time >> 11) as u8
+    }
+
+
+    #[inline]
+    pub const fn minute(&self) -> u8 {
+        ((self.time >> 5) & 0x3F) as u8
+    }
+
+
+    #[inline]
+    pub const fn second(&self) -> u8 {
+        ((self.time & 0x1F) * 2) as u8
+    }
+}
+
+
+impl Default for FatTimestamp {
+    #[inline]
+    fn default() -> Self {
+        Self::EPOCH
+    }
+}
+
+
+impl From<DateTime> for FatTimestamp {
+    /// The dates before 1980 become [`FatTimestamp::EPOCH`].
+    fn from(date_time: DateTime) -> Self {
+        if date_time.year < 1980 {
+            return Self::EPOCH;
+        }
+
+        Self::new(
+            date_time.year,
+            date_time.month,
+            date_time.day,
+            date_time.hour,
+            date_time.minute,
+            date_time.second,
+        )
+    }
+}
+
+
+impl core::fmt::Display for FatTimestamp {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+            self.year(),
+            self.month(),
+            self.day(),
+            self.hour(),
+            self.minute(),
+            self.second()
+        )
+    }
+}
+
+
+/// A file or directory found in a directory.
+///
+/// The raw short entry is kept as is so that
+/// fields this module doesn't interpret survive a rewrite.
+#[derive(Debug, Clone)]
+pub struct DirEntry {
+    name: String,
+    raw: [u8; DIR_ENTRY_SIZE],
+    offset: usize,
+    long_name_offsets: Vec<usize>,
+}
+
+
+impl DirEntry {
+    #[inline]
+    pub(crate) fn new(
+        name: String,
+        raw: [u8; DIR_ENTRY_SIZE],
+        offset: usize,
+        long_name_offsets: Vec<usize>,
+    ) -> Self {
+        Self {
+            name,
+            raw,
+            offset,
+            long_name_offsets,
+        }
+    }
+
+
+    /// Returns the long name if it exists, otherwise the short name.
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+
+    #[inline]
+    pub fn short_name(&self) -> String {
+        short_name_to_string(self.short_name_raw(), 0)
+    }
+
+
+    #[inline]
+    pub fn attr(&self) -> u8 {
+        self.raw[11]
+    }
+
+
+    #[inline]
+    pub fn is_dir(&self) -> bool {
+        self.attr() & ATTR_DIRECTORY != 0
+    }
+
+
+    #[inline]
+    pub fn first_cluster(&self) -> u32 {
+        let high = u16::from_le_bytes([self.raw[20], self.raw[21]]) as u32;
+        let low = u16::from_le_bytes([self.raw[26], self.raw[27]]) as u32;
+
+        (high << 16) | low
+    }
+
+
+    #[inline]
+    pub fn file_size(&self) -> usize {
+        u32::from_le_bytes([self.raw[28], self.raw[29], self.raw[30], self.raw[31]]) as usize
+    }
+
+
+    #[inline]
+    pub fn created(&self) -> FatTimestamp {
+        FatTimestamp::from_raw(self.u16_at(16), self.u16_at(14))
+    }
+
+
+    #[inline]
+    pub fn modified(&self) -> FatTimestamp {
+        FatTimestamp::from_raw(self.u16_at(24), self.u16_at(22))
+    }
+
+
+    #[inline]
+    pub fn accessed(&self) -> FatTimestamp {
+        FatTimestamp::from_raw(self.u16_at(18), 0)
+    }
+
+
+    /// Returns the byte offset of the short entry in the volume.
+    #[inline]
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+
+    #[inline]
+    pub(crate) fn raw(&self) -> &[u8; DIR_ENTRY_SIZE] {
+        &self.raw
+    }
+
+
+    #[inline]
+    pub(crate) fn short_name_raw(&self) -> &[u8; 11] {
+        self.raw[..11]
+            .try_into()
+            .unwrap()
+    }
+
+
+    /// Returns the byte offsets of all slots the entry occupies,
+    /// long name slots first.
+    pub(crate) fn slot_offsets(&self) -> impl Iterator<Item = usize> + '_ {
+        self.long_name_offsets
+            .iter()
+            .copied()
+            .chain(core::iter::once(self.offset))
+    }
+
+
+    pub(crate) fn set_first_cluster(&mut self, cluster: u32) {
+        self.set_u16_at(20, (cluster >> 16) as u16);
+        self.set_u16_at(26, cluster as u16);
+    }
+
+
+    pub(crate) fn set_file_size(&mut self, size: usize) {
+        self.raw[28..32].copy_from_slice(&(size as u32).to_le_bytes());
+    }
+
+
+    pub(crate) fn set_modified(&mut self, timestamp: FatTimestamp) {
+        self.set_u16_at(22, timestamp.time());
+        self.set_u16_at(24, timestamp.date());
+        self.set_u16_at(18, timestamp.date());
+    }
+
+
+    #[inline]
+    fn u16_at(&self, offset: usize) -> u16 {
+        u16::from_le_bytes([self.raw[offset], self.raw[offset + 1]])
+    }
+
+
+    #[inline]
+    fn set_u16_at(&mut self, offset: usize, value: u16) {
+        self.raw[offset..offset + 2].copy_from_slice(&value.to_le_bytes());
+    }
+}
+
+
+/// Builds a raw short entry.
+pub(crate) fn new_short_entry(
+    short_name: &[u8; 11],
+    attr: u8,
+    first_cluster: u32,
+    timestamp: FatTimestamp,
+) -> [u8; DIR_ENTRY_SIZE] {
+    let mut raw = [0u8; DIR_ENTRY_SIZE];
+    raw[..11].copy_from_slice(short_name);
+    raw[11] = attr;
+    raw[14..16].copy_from_slice(&timestamp.time().to_le_bytes());
+    raw[16..18].copy_from_slice(&timestamp.date().to_le_bytes());
+    raw[18..20].copy_from_slice(&timestamp.date().to_le_bytes());
+    raw[20..22].copy_from_slice(&((first_cluster >> 16) as u16).to_le_bytes());
+    raw[22..24].copy_from_slice(&timestamp.time().to_le_bytes());
+    raw[24..26].copy_from_slice(&timestamp.date().to_le_bytes());
+    raw[26..28].copy_from_slice(&(first_cluster as u16).to_le_bytes());
+    raw
+}
+
+
+/// Converts `NAME    EXT` into `NAME.EXT`,
+/// honoring the lowercase flags Windows stores in the NTRes byte.
+pub(crate) fn short_name_to_string(short_name: &[u8; 11], nt_res: u8) -> String {
+    let to_char = |c: &u8, lower: bool| {
+        let c = char::from(*c);
+        if lower {
+            c.to_ascii_lowercase()
+        } else {
+            c
+        }
+    };
+
+    let mut name: String = short_name[..8]
+        .iter()
+        .take_while(|c| **c != b' ')
+        .map(|c| to_char(c, nt_res & NT_RES_LOWER_BASE != 0))
+        .collect();
+
+    let ext: String = short_name[8..]
+        .iter()
+        .take_while(|c| **c != b' ')
+        .map(|c| to_char(c, nt_res & NT_RES_LOWER_EXT != 0))
+        .collect();
+
+    if !ext.is_empty() {
+        name.push('.');
+        name.push_str(&ext);
+    }
+
+    name
+}
+
+
+pub(crate) fn validate_name(name: &str) -> KernelResult {
+    const INVALID_CHARS: &[char] = &['/', '\\', ':', '*', '?', '"', '<', '>', '|'];
+
+    if name.is_empty()
+        || name == "."
+        || name == ".."
+        || 255 < name.encode_utf16().count()
+        || name
+            .chars()
+            .any(|c| c.is_control() || INVALID_CHARS.contains(&c))
+    {
+        return Err(FsReason::InvalidName(String::from(name)).into());
+    }
+
+    Ok(())
+}
+
+
+/// Returns the 8.3 form of the name if it can be stored
+/// without a long name, that is all uppercase and within 8.3.
+pub(crate) fn exact_short_name(name: &str) -> Option<[u8; 11]> {
+    let (base, ext) = split_ext(name);
+    let valid = |s: &str, max: usize| {
+        !s.is_empty() && s.len() <= max && s.bytes().all(is_short_name_char)
+    };
+
+    if !valid(base, 8) || !(ext.is_empty() || valid(ext, 3)) {
+        return None;
+    }
+
+    let mut short_name = [b' '; 11];
+    short_name[..base.len()].copy_from_slice(base.as_bytes());
+    short_name[8..8 + ext.len()].copy_from_slice(ext.as_bytes());
+
+    Some(short_name)
+}
+
+
+/// Builds the `BASIS~N` alias for a name which needs a long name entry.
+pub(crate) fn numbered_short_name(name: &str, n: usize) -> [u8; 11] {
+    let (base, ext) = split_ext(name.trim_start_matches('.'));
+    let convert = |s: &str| -> Vec<u8> {
+        s.chars()
+            .filter(|c| *c != ' ' && *c != '.')
+            .map(|c| {
+                let c = c.to_ascii_uppercase();
+                if c.is_ascii() && is_short_name_char(c as u8) {
+                    c as u8
+                } else {
+                    b'_'
+                }
+            })
+            .collect()
+    };
+
+    let mut tail = Vec::new();
+    let mut num = n;
+    while 0 < num {
+        tail.insert(0, b'0' + (num % 10) as u8);
+        num /= 10;
+    }
+    tail.insert(0, b'~');
+
+    let base = convert(base);
+    let base_len = base.len().min(8 - tail.len());
+
+    let mut short_name = [b' '; 11];
+    short_name[..base_len].copy_from_slice(&base[..base_len]);
+    short_name[base_len..base_len + tail.len()].copy_from_slice(&tail);
+
+    let ext = convert(ext);
+    let ext_len = ext.len().min(3);
+    short_name[8..8 + ext_len].copy_from_slice(&ext[..ext_len]);
+
+    short_name
+}
+
+
+pub(crate) fn short_name_checksum(short_name: &[u8; 11]) -> u8 {
+    short_name
+        .iter()
+        .fold(0u8, |sum, c| {
+            ((sum & 1) << 7)
+                .wrapping_add(sum >> 1)
+                .wrapping_add(*c)
+        })
+}
+
+
+/// Builds the long name slots in the order they are placed on disk,
+/// which is the last part of the name first.
+pub(crate) fn new_long_name_entries(name: &str, checksum: u8) -> Vec<[u8; DIR_ENTRY_SIZE]> {
+    let mut chars: Vec<u16> = name.encode_utf16().collect();
+    if chars.len() % LONG_NAME_CHARS != 0 {
+        chars.push(0);
+    }
+    while chars.len() % LONG_NAME_CHARS != 0 {
+        chars.push(0xFFFF);
+    }
+
+    let count = chars.len() / LONG_NAME_CHARS;
+
+    (0..count)
+        .rev()
+        .map(|i| {
+            let mut raw = [0u8; DIR_ENTRY_SIZE];
+            raw[0] = (i + 1) as u8;
+            if i == count - 1 {
+                raw[0] |= LAST_LONG_ENTRY;
+            }
+            raw[11] = ATTR_LONG_NAME;
+            raw[13] = checksum;
+
+            let part = &chars[i * LONG_NAME_CHARS..(i + 1) * LONG_NAME_CHARS];
+            for (c, offset) in part
+                .iter()
+                .zip(long_name_char_offsets())
+            {
+                raw[offset..offset + 2].copy_from_slice(&c.to_le_bytes());
+            }
+
+            raw
+        })
+        .collect()
+}
+
+
+/// Collects long name slots while walking a directory.
+#[derive(Debug, Default)]
+pub(crate) struct LongNameBuilder {
+    parts: Vec<(u8, [u16; LONG_NAME_CHARS])>,
+    offsets: Vec<usize>,
+    checksum: u8,
+}
+
+
+impl LongNameBuilder {
+    pub fn push(&mut self, raw: &[u8; DIR_ENTRY_SIZE], offset: usize) {
+        if raw[0] & LAST_LONG_ENTRY != 0 {
+            self.clear();
+            self.checksum = raw[13];
+        } else if self.parts.is_empty() || self.checksum != raw[13] {
+            self.clear();
+            return;
+        }
+
+        let mut part = [0u16; LONG_NAME_CHARS];
+        for (c, offset) in part
+            .iter_mut()
+            .zip(long_name_char_offsets())
+        {
+            *c = u16::from_le_bytes([raw[offset], raw[offset + 1]]);
+        }
+
+        self.parts
+            .push((raw[0] & !LAST_LONG_ENTRY, part));
+        self.offsets.push(offset);
+    }
+
+
+    /// Returns the long name and its slots if they belong to the short name.
+    pub fn take(&mut self, short_name: &[u8; 11]) -> Option<(String, Vec<usize>)> {
+        let parts = core::mem::take(&mut self.parts);
+        let offsets = core::mem::take(&mut self.offsets);
+
+        if parts.is_empty()
+            || self.checksum != short_name_checksum(short_name)
+            || parts.last()?.0 != 1
+        {
+            return None;
+        }
+
+        let chars: Vec<u16> = parts
+            .iter()
+            .rev()
+            .flat_map(|(_, part)| part.iter().copied())
+            .take_while(|c| *c != 0)
+            .collect();
+
+        Some((String::from_utf16_lossy(&chars), offsets))
+    }
+
+
+    #[inline]
+    pub fn clear(&mut self) {
+        self.parts.clear();
+        self.offsets.clear();
+    }
+}
+
+
+#[inline]
+fn long_name_char_offsets() -> impl Iterator<Item = usize> {
+    (1..11)
+        .step_by(2)
+        .chain((14..26).step_by(2))
+        .chain((28..32).step_by(2))
+}
+
+
+fn split_ext(name: &str) -> (&str, &str) {
+    match name.rfind('.') {
+        Some(0) | None => (name, ""),
+        Some(i) => (&name[..i], &name[i + 1..]),
+    }
+}
+
+
+fn is_short_name_char(c: u8) -> bool {
+    c.is_ascii_uppercase() || c.is_ascii_digit() || b"!#$%&'()-@^_`{}~".contains(&c)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::fs::volume::dir_entry::{
+        exact_short_name, new_long_name_entries, numbered_short_name, short_name_checksum,
+        short_name_to_string, FatTimestamp, LongNameBuilder,
+    };
+    use crate::timer::rtc::DateTime;
+
+    #[test]
+    fn it_exact_short_name() {
+        assert_eq!(exact_short_name("HELLO.TXT"), Some(*b"HELLO   TXT"));
+        assert_eq!(exact_short_name("KERNEL"), Some(*b"KERNEL     "));
+        assert_eq!(exact_short_name("hello.txt"), None);
+        assert_eq!(exact_short_name("LONGFILENAME.TXT"), None);
+        assert_eq!(exact_short_name("A.B.C"), None);
+    }
+
+
+    #[test]
+    fn it_numbered_short_name() {
+        assert_eq!(numbered_short_name("hello.txt", 1), *b"HELLO~1 TXT");
+        assert_eq!(numbered_short_name("long file name.text", 2), *b"LONGFI~2TEX");
+        assert_eq!(numbered_short_name(".config", 1), *b"CONFIG~1   ");
+    }
+
+
+    #[test]
+    fn it_short_name_to_string() {
+        assert_eq!(short_name_to_string(b"HELLO   TXT", 0), "HELLO.TXT");
+        assert_eq!(short_name_to_string(b"KERNEL     ", 0), "KERNEL");
+        assert_eq!(short_name_to_string(b"HELLO   TXT", 0x18), "hello.txt");
+    }
+
+
+    #[test]
+    fn it_read_long_name_written() {
+        let short_name = numbered_short_name("a long file name.txt", 1);
+        let checksum = short_name_checksum(&short_name);
+        let entries = new_long_name_entries("a long file name.txt", checksum);
+        assert_eq!(entries.len(), 2);
+
+        let mut builder = LongNameBuilder::default();
+        for (i, raw) in entries.iter().enumerate() {
+            builder.push(raw, i * 32);
+        }
+
+        let (name, offsets) = builder
+            .take(&short_name)
+            .unwrap();
+        assert_eq!(name, "a long file name.txt");
+        assert_eq!(offsets, [0, 32]);
+    }
+
+
+    #[test]
+    fn it_ignore_orphan_long_name() {
+        let entries = new_long_name_entries("hello.txt", 0);
+        let mut builder = LongNameBuilder::default();
+        builder.push(&entries[0], 0);
+
+        assert!(builder
+            .take(b"HELLO   TXT")
+            .is_none());
+    }
+
+
+    #[test]
+    fn it_timestamp() {
+        let timestamp = FatTimestamp::new(2023, 7, 16, 13, 45, 31);
+        assert_eq!(timestamp.year(), 2023);
+        assert_eq!(timestamp.month(), 7);
+        assert_eq!(timestamp.day(), 16);
+        assert_eq!(timestamp.hour(), 13);
+        assert_eq!(timestamp.minute(), 45);
+        assert_eq!(timestamp.second(), 30);
+        assert_eq!(FatTimestamp::default().year(), 1980);
+    }
+
+
+    #[test]
+    fn it_timestamp_from_date_time() {
+        let date_time = DateTime::from_unix_seconds(1_689_541_509);
+        assert_eq!(FatTimestamp::from(date_time), FatTimestamp::new(2023, 7, 16, 21, 5, 9));
+        assert_eq!(FatTimestamp::from(DateTime::from_unix_seconds(0)), FatTimestamp::EPOCH);
+    }
+}
@@ -1,6 +1,7 @@
 use crate::interrupt::interrupt_descriptor_table::InterruptDescriptorTable;
 
 pub mod asm;
+pub mod exception;
 pub mod gate_type;
 mod idt_descriptor;
 pub mod interrupt_descriptor;
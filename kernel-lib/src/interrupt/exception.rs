@@ -0,0 +1,409 @@
+use core::arch::asm;
+
+use x86_64::registers::control::{Cr0, Cr2, Cr3, Cr4};
+use x86_64::structures::idt::InterruptStackFrameValue;
+
+use crate::backtrace::print_backtrace_at;
+use crate::error::KernelResult;
+use crate::interrupt::interrupt_descriptor_attribute::InterruptDescriptorAttribute;
+use crate::interrupt::interrupt_vector::InterruptVector;
+use crate::interrupt::IDT;
+use crate::serial_println;
+use crate::task::app::{
+    exit_app, EXIT_CODE_FLOATING_POINT_ERROR, EXIT_CODE_ILLEGAL_INSTRUCTION,
+    EXIT_CODE_SEGMENTATION_FAULT,
+};
+
+/// The number of vectors reserved for CPU exceptions.
+pub const EXCEPTION_COUNT: usize = 32;
+
+const DIVIDE_ERROR: u64 = 0x00;
+const DEBUG: u64 = 0x01;
+const BREAKPOINT: u64 = 0x03;
+const INVALID_OPCODE: u64 = 0x06;
+const DOUBLE_FAULT: u64 = 0x08;
+const X87_FLOATING_POINT: u64 = 0x10;
+const MACHINE_CHECK: u64 = 0x12;
+const SIMD_FLOATING_POINT: u64 = 0x13;
+
+/// Called with the saved registers before the registers are dumped.
+///
+/// Returns true if the exception is resolved and the interrupted code can resume.
+pub type ExceptionHook = fn(&ExceptionFrame) -> bool;
+
+static mut HOOKS: [Option<ExceptionHook>; EXCEPTION_COUNT] = [None; EXCEPTION_COUNT];
+
+
+/// The registers saved by the entry points, from the lowest address.
+#[repr(C)]
+#[derive(Debug, Clone)]
+pub struct ExceptionFrame {
+    pub r15: u64,
+    pub r14: u64,
+    pub r13: u64,
+    pub r12: u64,
+    pub r11: u64,
+    pub r10: u64,
+    pub r9: u64,
+    pub r8: u64,
+    pub rbp: u64,
+    pub rdi: u64,
+    pub rsi: u64,
+    pub rdx: u64,
+    pub rcx: u64,
+    pub rbx: u64,
+    pub rax: u64,
+    pub vector: u64,
+    /// `0` for the exceptions which don't push an error code.
+    pub error_code: u64,
+    pub stack_frame: InterruptStackFrameValue,
+}
+
+
+/// Returns the name of the exception, or `"RESERVED"` for the vectors not defined.
+pub fn exception_name(vector: u64) -> &'static str {
+    match vector {
+        0x00 => "DIVIDE ERROR",
+        0x01 => "DEBUG",
+        0x02 => "NON-MASKABLE INTERRUPT",
+        0x03 => "BREAKPOINT",
+        0x04 => "OVERFLOW",
+        0x05 => "BOUND RANGE EXCEEDED",
+        0x06 => "INVALID OPCODE",
+        0x07 => "DEVICE NOT AVAILABLE",
+        0x08 => "DOUBLE FAULT",
+        0x09 => "COPROCESSOR SEGMENT OVERRUN",
+        0x0A => "INVALID TSS",
+        0x0B => "SEGMENT NOT PRESENT",
+        0x0C => "STACK SEGMENT FAULT",
+        0x0D => "GENERAL PROTECTION FAULT",
+        0x0E => "PAGE FAULT",
+        0x10 => "X87 FLOATING POINT",
+        0x11 => "ALIGNMENT CHECK",
+        0x12 => "MACHINE CHECK",
+        0x13 => "SIMD FLOATING POINT",
+        0x14 => "VIRTUALIZATION",
+        0x15 => "CONTROL PROTECTION",
+        0x1C => "HYPERVISOR INJECTION",
+        0x1D => "VMM COMMUNICATION",
+        0x1E => "SECURITY",
+        _ => "RESERVED",
+    }
+}
+
+
+/// Installs the entry points dumping the registers over serial for all the exceptions.
+///
+/// The kernel overrides the exceptions it handles by itself after calling this.
+pub fn init_exception_handlers(type_attributes: InterruptDescriptorAttribute) -> KernelResult {
+    for (vector, entry_point) in ENTRY_POINTS.iter().enumerate() {
+        unsafe {
+            IDT[vector].set_entry_point(*entry_point as usize as u64, type_attributes)?;
+        }
+    }
+
+    Ok(())
+}
+
+
+/// Lets the kernel handle the exception before the default handling of [`handle_exception`].
+///
+/// # Safety
+///
+/// Must be called before the exception can occur, since the hooks are read without locking.
+pub unsafe fn set_exception_hook(vector: InterruptVector, hook: ExceptionHook) {
+    HOOKS[vector.cast() as usize] = Some(hook);
+}
+
+
+/// Writes the control registers over serial.
+pub fn dump_control_registers() {
+    serial_println!(
+        "CR0={:016X} CR2={:016X} CR3={:016X} CR4={:016X}",
+        Cr0::read_raw(),
+        Cr2::read().as_u64(),
+        Cr3::read().0.start_address().as_u64(),
+        Cr4::read_raw()
+    );
+}
+
+
+fn dump(frame: &ExceptionFrame) {
+    serial_println!("EXCEPTION: {} (vector {})", exception_name(frame.vector), frame.vector);
+    serial_println!("Error Code: 0x{:X}", frame.error_code);
+    serial_println!("{:?}", frame.stack_frame);
+    serial_println!(
+        "RAX={:016X} RBX={:016X} RCX={:016X} RDX={:016X}",
+        frame.rax,
+        frame.rbx,
+        frame.rcx,
+        frame.rdx
+    );
+    serial_println!(
+        "RSI={:016X} RDI={:016X} RBP={:016X} R8 ={:016X}",
+        frame.rsi,
+        frame.rdi,
+        frame.rbp,
+        frame.r8
+    );
+    serial_println!(
+        "R9 ={:016X} R10={:016X} R11={:016X} R12={:016X}",
+        frame.r9,
+        frame.r10,
+        frame.r11,
+        frame.r12
+    );
+    serial_println!(
+        "R13={:016X} R14={:016X} R15={:016X}",
+        frame.r13,
+        frame.r14,
+        frame.r15
+    );
+    dump_control_registers();
+}
+
+
+/// Called from [`asm_exception_common`] with interrupts disabled.
+///
+/// Breakpoints and debug exceptions resume after the dump.
+/// Faults in ring 3 terminate the application, and the others halt the CPU.
+extern "sysv64" fn handle_exception(frame: &ExceptionFrame) {
+    if let Some(hook) = unsafe { HOOKS[frame.vector as usize] } {
+        if hook(frame) {
+            return;
+        }
+    }
+
+    dump(frame);
+
+    if frame.vector == BREAKPOINT || frame.vector == DEBUG {
+        return;
+    }
+
+    // Aborts leave the CPU in a state the application can't be exited from safely.
+    let from_user = frame.stack_frame.code_segment & 0b11 == 3;
+    if from_user && frame.vector != DOUBLE_FAULT && frame.vector != MACHINE_CHECK {
+        unsafe { exit_app(exit_code(frame.vector)) };
+    }
+
+    print_backtrace_at(frame.stack_frame.instruction_pointer.as_u64(), frame.rbp);
+    common_lib::assembly::hlt_forever();
+}
+
+
+/// Returns the exit code of an application terminated by the exception.
+fn exit_code(vector: u64) -> i32 {
+    match vector {
+        DIVIDE_ERROR | X87_FLOATING_POINT | SIMD_FLOATING_POINT => EXIT_CODE_FLOATING_POINT_ERROR,
+        INVALID_OPCODE => EXIT_CODE_ILLEGAL_INSTRUCTION,
+        _ => EXIT_CODE_SEGMENTATION_FAULT,
+    }
+}
+
+
+/// Saves the registers in the layout of [`ExceptionFrame`] and calls [`handle_exception`].
+///
+/// The entry points jump here after pushing the error code and the vector.
+#[naked]
+extern "sysv64" fn asm_exception_common() {
+    unsafe {
+        asm!(
+        "
+        push rax
+        push rbx
+        push rcx
+        push rdx
+        push rsi
+        push rdi
+        push rbp
+        push r8
+        push r9
+        push r10
+        push r11
+        push r12
+        push r13
+        push r14
+        push r15
+
+        mov rdi, rsp
+        mov rbp, rsp
+        and rsp, -16
+        call {handle}
+        mov rsp, rbp
+
+        pop r15
+        pop r14
+        pop r13
+        pop r12
+        pop r11
+        pop r10
+        pop r9
+        pop r8
+        pop rbp
+        pop rdi
+        pop rsi
+        pop rdx
+        pop rcx
+        pop rbx
+        pop rax
+
+        // The vector and the error code
+        add rsp, 16
+        iretq
+        ",
+        handle = sym handle_exception,
+        options(noreturn)
+        )
+    }
+}
+
+
+/// Defines the entry point of an exception.
+///
+/// A dummy error code is pushed for the exceptions without one,
+/// so that every frame has the same layout.
+macro_rules! exception_entry {
+    ($name: ident, $vector: literal) => {
+        #[naked]
+        extern "sysv64" fn $name() {
+            unsafe {
+                asm!(
+                "
+                push 0
+                push {vector}
+                jmp {common}
+                ",
+                vector = const $vector,
+                common = sym asm_exception_common,
+                options(noreturn)
+                )
+            }
+        }
+    };
+
+    ($name: ident, $vector: literal, error_code) => {
+        #[naked]
+        extern "sysv64" fn $name() {
+            unsafe {
+                asm!(
+                "
+                push {vector}
+                jmp {common}
+                ",
+                vector = const $vector,
+                common = sym asm_exception_common,
+                options(noreturn)
+                )
+            }
+        }
+    };
+}
+
+
+exception_entry!(asm_exception_00, 0x00);
+exception_entry!(asm_exception_01, 0x01);
+exception_entry!(asm_exception_02, 0x02);
+exception_entry!(asm_exception_03, 0x03);
+exception_entry!(asm_exception_04, 0x04);
+exception_entry!(asm_exception_05, 0x05);
+exception_entry!(asm_exception_06, 0x06);
+exception_entry!(asm_exception_07, 0x07);
+exception_entry!(asm_exception_08, 0x08, error_code);
+exception_entry!(asm_exception_09, 0x09);
+exception_entry!(asm_exception_0a, 0x0A, error_code);
+exception_entry!(asm_exception_0b, 0x0B, error_code);
+exception_entry!(asm_exception_0c, 0x0C, error_code);
+exception_entry!(asm_exception_0d, 0x0D, error_code);
+exception_entry!(asm_exception_0e, 0x0E, error_code);
+exception_entry!(asm_exception_0f, 0x0F);
+exception_entry!(asm_exception_10, 0x10);
+exception_entry!(asm_exception_11, 0x11, error_code);
+exception_entry!(asm_exception_12, 0x12);
+exception_entry!(asm_exception_13, 0x13);
+exception_entry!(asm_exception_14, 0x14);
+exception_entry!(asm_exception_15, 0x15, error_code);
+exception_entry!(asm_exception_16, 0x16);
+exception_entry!(asm_exception_17, 0x17);
+exception_entry!(asm_exception_18, 0x18);
+exception_entry!(asm_exception_19, 0x19);
+exception_entry!(asm_exception_1a, 0x1A);
+exception_entry!(asm_exception_1b, 0x1B);
+exception_entry!(asm_exception_1c, 0x1C);
+exception_entry!(asm_exception_1d, 0x1D, error_code);
+exception_entry!(asm_exception_1e, 0x1E, error_code);
+exception_entry!(asm_exception_1f, 0x1F);
+
+
+const ENTRY_POINTS: [extern "sysv64" fn(); EXCEPTION_COUNT] = [
+    asm_exception_00,
+    asm_exception_01,
+    asm_exception_02,
+    asm_exception_03,
+    asm_exception_04,
+    asm_exception_05,
+    asm_exception_06,
+    asm_exception_07,
+    asm_exception_08,
+    asm_exception_09,
+    asm_exception_0a,
+    asm_exception_0b,
+    asm_exception_0c,
+    asm_exception_0d,
+    asm_exception_0e,
+    asm_exception_0f,
+    asm_exception_10,
+    asm_exception_11,
+    asm_exception_12,
+    asm_exception_13,
+    asm_exception_14,
+    asm_exception_15,
+    asm_exception_16,
+    asm_exception_17,
+    asm_exception_18,
+    asm_exception_19,
+    asm_exception_1a,
+    asm_exception_1b,
+    asm_exception_1c,
+    asm_exception_1d,
+    asm_exception_1e,
+    asm_exception_1f,
+];
+
+
+#[cfg(test)]
+mod tests {
+    use core::mem::{size_of, MaybeUninit};
+    use core::ptr::addr_of;
+
+    use crate::interrupt::exception::{exception_name, exit_code, ExceptionFrame};
+    use crate::task::app::{EXIT_CODE_FLOATING_POINT_ERROR, EXIT_CODE_SEGMENTATION_FAULT};
+
+    #[test]
+    fn it_match_layout_pushed_by_entry_points() {
+        let frame = MaybeUninit::<ExceptionFrame>::uninit();
+        let base = frame.as_ptr() as usize;
+        let offset = |field: usize| field - base;
+
+        unsafe {
+            let frame = frame.as_ptr();
+            assert_eq!(offset(addr_of!((*frame).vector) as usize), 15 * 8);
+            assert_eq!(offset(addr_of!((*frame).error_code) as usize), 16 * 8);
+            assert_eq!(offset(addr_of!((*frame).stack_frame) as usize), 17 * 8);
+        }
+        assert_eq!(size_of::<ExceptionFrame>(), 22 * 8);
+    }
+
+
+    #[test]
+    fn it_name_exceptions() {
+        assert_eq!(exception_name(0x0D), "GENERAL PROTECTION FAULT");
+        assert_eq!(exception_name(0x0F), "RESERVED");
+    }
+
+
+    #[test]
+    fn it_exit_code_of_exception() {
+        assert_eq!(exit_code(0x00), EXIT_CODE_FLOATING_POINT_ERROR);
+        assert_eq!(exit_code(0x0D), EXIT_CODE_SEGMENTATION_FAULT);
+    }
+}
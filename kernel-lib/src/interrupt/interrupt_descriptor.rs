@@ -50,6 +50,26 @@ impl InterruptDescriptor {
     }
 
 
+    /// Sets an entry point written in assembly.
+    ///
+    /// # Safety
+    ///
+    /// The entry point must preserve all the registers and return with `iretq`.
+    pub unsafe fn set_entry_point(
+        &mut self,
+        entry_point: u64,
+        type_attributes: InterruptDescriptorAttribute,
+    ) -> KernelResult {
+        self.set_type_attributes(type_attributes);
+        self.set_offset_low(entry_point as u16);
+        self.set_offset_middle((entry_point >> 16) as u16);
+        self.set_offset_high((entry_point >> 32) as u32);
+        self.set_segment_selector(CS::get_reg().0);
+
+        Ok(())
+    }
+
+
     pub fn set_handler(
         &mut self,
         handler: InterruptHandler,
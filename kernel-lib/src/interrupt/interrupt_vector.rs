@@ -3,9 +3,11 @@
 #[derive(Debug, Clone, Copy, Ord, PartialOrd, Eq, PartialEq)]
 pub enum InterruptVector {
     Overflow = 0x04,
-    PageFault = 0x08,
+    DoubleFault = 0x08,
+    PageFault = 0x0E,
     Xhci = 0x40,
     ApicTimer = 0x41,
+    HpetTimer = 0x42,
     NotSupport,
 }
 
@@ -1,4 +1,4 @@
-use core::arch::global_asm;
+use core::arch::{asm, global_asm};
 
 use crate::io::config_address_register::ConfigAddrRegister;
 
@@ -53,3 +53,35 @@ pub fn io_out32(addr: u16, data: u32) {
 pub fn io_in32(addr: u16) -> u32 {
     unsafe { asm_io_in32(addr) }
 }
+
+
+pub fn io_out8(addr: u16, data: u8) {
+    unsafe {
+        asm!("out dx, al", in("dx") addr, in("al") data, options(nomem, nostack, preserves_flags));
+    }
+}
+
+
+pub fn io_in8(addr: u16) -> u8 {
+    let data: u8;
+    unsafe {
+        asm!("in al, dx", in("dx") addr, out("al") data, options(nomem, nostack, preserves_flags));
+    }
+    data
+}
+
+
+pub fn io_out16(addr: u16, data: u16) {
+    unsafe {
+        asm!("out dx, ax", in("dx") addr, in("ax") data, options(nomem, nostack, preserves_flags));
+    }
+}
+
+
+pub fn io_in16(addr: u16) -> u16 {
+    let data: u16;
+    unsafe {
+        asm!("in ax, dx", in("dx") addr, out("ax") data, options(nomem, nostack, preserves_flags));
+    }
+    data
+}
@@ -58,6 +58,13 @@ impl TerminalLayer {
     }
 
 
+    #[inline]
+    pub fn print(&mut self, message: &str) -> KernelResult {
+        self.text_box_layer()
+            .print(message)
+    }
+
+
     #[inline]
     pub fn history_up(&mut self) -> KernelResult {
         self.text_box_layer()
@@ -70,12 +70,24 @@ impl TextLayer {
     }
 
 
+    #[inline(always)]
+    pub fn replace(&mut self, text: &str) -> KernelResult {
+        self.text_frame.replace(text)
+    }
+
+
     #[inline(always)]
     pub fn delete_last(&mut self) {
         self.text_frame.delete_last()
     }
 
 
+    #[inline(always)]
+    pub fn print(&mut self, message: &str) -> KernelResult {
+        self.text_frame.print(message)
+    }
+
+
     #[inline(always)]
     pub fn change_colors(&mut self, colors: TextColors) -> KernelResult {
         self.text_frame
@@ -4,6 +4,11 @@ use alloc::string::{String, ToString};
 pub enum CommandAction {
     Clear,
     Output(String),
+
+    /// Outputs one page at a time, waiting for a key press between pages.
+    ///
+    /// Pressing `q` discards the remaining pages.
+    Paginate(String),
 }
 
 
@@ -11,6 +16,11 @@ impl CommandAction {
     pub fn output(message: impl Into<String>) -> Self {
         CommandAction::Output(message.into())
     }
+
+
+    pub fn paginate(message: impl Into<String>) -> Self {
+        CommandAction::Paginate(message.into())
+    }
 }
 
 
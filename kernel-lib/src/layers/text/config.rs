@@ -4,11 +4,10 @@ use alloc::vec::Vec;
 
 use common_lib::math::size::Size;
 
-use crate::fs;
 use crate::gop::console::DISPLAY_BACKGROUND_COLOR;
 use crate::gop::pixel::pixel_color::PixelColor;
 use crate::layers::text::colors::TextColors;
-use crate::layers::text::command::{Command, CommandAction, CommandResult};
+use crate::layers::text::command::{Command, CommandAction};
 
 pub struct Builder {
     colors: TextColors,
@@ -16,6 +15,7 @@ pub struct Builder {
     scrollable: bool,
     prefix: Option<char>,
     commands: Vec<Command>,
+    fallback: Option<Command>,
 }
 
 
@@ -27,6 +27,7 @@ impl Builder {
             text_unit: Size::new(8, 16),
             prefix: None,
             commands: Vec::new(),
+            fallback: None,
         }
     }
 
@@ -76,6 +77,15 @@ impl Builder {
     }
 
 
+    /// Sets the command executed when no command matches the name.
+    ///
+    /// The fallback receives the command name as the first argument.
+    pub fn fallback(mut self, command: Command) -> Self {
+        self.fallback = Some(command);
+        self
+    }
+
+
     pub fn build(self) -> TextConfig {
         TextConfig {
             scrollable: self.scrollable,
@@ -83,6 +93,7 @@ impl Builder {
             text_unit: self.text_unit,
             prefix: self.prefix,
             commands: self.commands,
+            fallback: self.fallback,
         }
     }
 }
@@ -101,6 +112,7 @@ pub struct TextConfig {
     pub scrollable: bool,
     pub prefix: Option<char>,
     commands: Vec<Command>,
+    fallback: Option<Command>,
 }
 
 
@@ -130,27 +142,13 @@ impl TextConfig {
                 command_name: command.name().to_string(),
                 action,
             })
-        } else {
+        } else if let Some(fallback) = self.fallback.as_ref() {
             Ok(Data {
                 command_name: args[0].to_string(),
-                action: execute_file_if_exists(args[0])?,
+                action: fallback.execute(&args)?,
             })
+        } else {
+            Err(format!("No such command `{}`", args[0]))
         }
     }
 }
-
-
-fn execute_file_if_exists(command: &str) -> CommandResult {
-    let file_name = if command.ends_with(".ELF") {
-        command.to_string()
-    } else {
-        format!("{command}.ELF")
-    };
-
-    if let Ok(file) = fs::open_file(&file_name) {
-        fs::execute_elf(file).map_err(|e| e.to_string())?;
-        Ok(CommandAction::Output("executed".to_string()))
-    } else {
-        Err(format!("No such command `{}`", command))
-    }
-}
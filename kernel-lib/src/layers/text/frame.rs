@@ -1,4 +1,5 @@
-use alloc::string::ToString;
+use alloc::collections::VecDeque;
+use alloc::string::{String, ToString};
 use alloc::vec::Vec;
 
 use common_lib::frame_buffer::PixelFormat;
@@ -16,6 +17,8 @@ use crate::layers::text::config;
 use crate::layers::text::config::TextConfig;
 use crate::layers::text::row::TextRow;
 
+const MORE_PROMPT: &str = "-- More --";
+
 pub struct TextFrame {
     rows: Vec<TextRow>,
     text_frame_size: Size,
@@ -23,6 +26,7 @@ pub struct TextFrame {
     char_writer: AscIICharWriter,
     config: TextConfig,
     command_history: CommandHistory,
+    pending_lines: VecDeque<String>,
 }
 
 
@@ -40,6 +44,7 @@ impl TextFrame {
             pixel_format,
             config,
             command_history: CommandHistory::new(),
+            pending_lines: VecDeque::new(),
         };
         me.add_row(true)?;
         Ok(me)
@@ -74,13 +79,73 @@ impl TextFrame {
         }
 
         for c in str.chars() {
+            if self.is_paginating() {
+                self.next_page(c)?;
+                continue;
+            }
+
             if self.write_char(self.config.colors, c)? {
                 self.execute_command_if_need()?;
-                self.new_line(true)?;
-                self.write_char(self.config.colors, c)?;
+                if !self.is_paginating() {
+                    self.new_line(true)?;
+                    self.write_char(self.config.colors, c)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+
+    /// Outputs the message above the row being edited.
+    ///
+    /// Unlike [`TextFrame::append_string`], the message is never interpreted as a command.
+    pub fn print(&mut self, message: &str) -> KernelResult {
+        if self.is_paginating() {
+            self.pending_lines
+                .extend(split_lines(message, self.text_frame_size.width()));
+            return Ok(());
+        }
+
+        let editing: Vec<char> = self
+            .rows
+            .pop()
+            .map(|row| row.texts().to_vec())
+            .unwrap_or_default();
+
+        self.new_line(false)?;
+        self.output(message, self.config.colors)?;
+        self.new_line(false)?;
+
+        for c in editing {
+            self.write_char(self.config.colors, c)?;
+        }
+
+        Ok(())
+    }
+
+
+    /// Replaces all the rows with the lines of the text, cutting off what doesn't fit.
+    pub fn replace(&mut self, text: &str) -> KernelResult {
+        self.rows.clear();
+        self.pending_lines.clear();
+
+        for line in text
+            .lines()
+            .take(self.text_frame_size.height())
+        {
+            self.add_row(false)?;
+            for c in line.chars() {
+                if self.write_char(self.config.colors, c)? {
+                    break;
+                }
             }
         }
 
+        if self.rows.is_empty() {
+            self.add_row(false)?;
+        }
+
         Ok(())
     }
 
@@ -95,7 +160,7 @@ impl TextFrame {
 
 
     pub fn delete_last(&mut self) {
-        if !self.need_back_line() {
+        if !self.is_paginating() && !self.need_back_line() {
             self.rows
                 .last_mut()
                 .unwrap()
@@ -123,6 +188,10 @@ impl TextFrame {
 
 
     pub fn history_down(&mut self) -> KernelResult {
+        if self.is_paginating() {
+            return Ok(());
+        }
+
         if let Some(command_name) = self
             .command_history
             .history_down()
@@ -136,6 +205,10 @@ impl TextFrame {
 
 
     pub fn history_up(&mut self) -> KernelResult {
+        if self.is_paginating() {
+            return Ok(());
+        }
+
         if let Some(command_name) = self
             .command_history
             .history_up()
@@ -227,6 +300,69 @@ impl TextFrame {
                 self.new_line(false)?;
                 self.output(&output, self.config.colors)?;
             }
+            CommandAction::Paginate(output) => {
+                self.pending_lines = split_lines(&output, self.text_frame_size.width());
+                self.output_page()?;
+            }
+        }
+
+        Ok(())
+    }
+
+
+    #[inline]
+    fn is_paginating(&self) -> bool {
+        !self
+            .pending_lines
+            .is_empty()
+    }
+
+
+    /// Outputs the lines that fit in the frame, leaving the last row for the more prompt.
+    fn output_page(&mut self) -> KernelResult {
+        let page_lines = self
+            .text_frame_size
+            .height()
+            .saturating_sub(1)
+            .max(1);
+
+        for _ in 0..page_lines {
+            if let Some(line) = self.pending_lines.pop_front() {
+                self.new_line(false)?;
+                self.output(&line, self.config.colors)?;
+            } else {
+                break;
+            }
+        }
+
+        if self.is_paginating() {
+            self.new_line(false)?;
+            let colors = TextColors::new(
+                self.config
+                    .colors
+                    .background(),
+                self.config
+                    .colors
+                    .foreground(),
+            );
+            self.output(MORE_PROMPT, colors)?;
+        }
+
+        Ok(())
+    }
+
+
+    fn next_page(&mut self, key: char) -> KernelResult {
+        self.rows.pop();
+
+        if key == 'q' {
+            self.pending_lines.clear();
+        } else {
+            self.output_page()?;
+        }
+
+        if !self.is_paginating() {
+            self.add_row(true)?;
         }
 
         Ok(())
@@ -304,15 +440,39 @@ impl TextFrame {
 }
 
 
+/// Splits the output into rows no longer than the frame width.
+fn split_lines(output: &str, width: usize) -> VecDeque<String> {
+    let width = width.max(1);
+    let mut lines = VecDeque::new();
+
+    for line in output.lines() {
+        let chars: Vec<char> = line.chars().collect();
+        if chars.is_empty() {
+            lines.push_back(String::new());
+            continue;
+        }
+
+        for chunk in chars.chunks(width) {
+            lines.push_back(chunk.iter().collect());
+        }
+    }
+
+    lines
+}
+
+
 #[cfg(test)]
 mod tests {
+    use alloc::string::String;
+
     use common_lib::frame_buffer::PixelFormat;
     use common_lib::math::size::Size;
 
     use crate::gop;
     use crate::gop::char::ascii_char_writer::AscIICharWriter;
+    use crate::layers::text::command::{Command, CommandAction};
     use crate::layers::text::config;
-    use crate::layers::text::frame::TextFrame;
+    use crate::layers::text::frame::{TextFrame, MORE_PROMPT};
 
     #[test]
     fn it_keeping_max_lines() {
@@ -389,4 +549,109 @@ mod tests {
 
         assert_eq!(frame.frame_buff_lines().len(), 2);
     }
+
+
+    #[test]
+    fn it_paginate_output() {
+        gop::test_init();
+
+        let config = config::Builder::new()
+            .set_scrollable()
+            .prefix('>')
+            .add_command(Command::new("cat", |_| {
+                Ok(CommandAction::paginate("1\n2\n3\n4\n5"))
+            }))
+            .build();
+
+        let mut frame = TextFrame::new(
+            AscIICharWriter::new(),
+            Size::new(20, 3),
+            PixelFormat::Rgb,
+            config,
+        )
+        .unwrap();
+
+        frame
+            .append_string("cat\n")
+            .unwrap();
+        assert_eq!(frame.pending_lines.len(), 3);
+        assert_eq!(last_row(&frame), MORE_PROMPT);
+
+        frame
+            .append_string(" ")
+            .unwrap();
+        assert_eq!(frame.pending_lines.len(), 1);
+        assert_eq!(last_row(&frame), MORE_PROMPT);
+
+        frame
+            .append_string("q")
+            .unwrap();
+        assert!(frame.pending_lines.is_empty());
+        assert_eq!(last_row(&frame), ">");
+    }
+
+
+    #[test]
+    fn it_print_above_editing_row() {
+        gop::test_init();
+
+        let config = config::Builder::new()
+            .set_scrollable()
+            .prefix('>')
+            .build();
+
+        let mut frame = TextFrame::new(
+            AscIICharWriter::new(),
+            Size::new(20, 3),
+            PixelFormat::Rgb,
+            config,
+        )
+        .unwrap();
+
+        frame
+            .append_string("ab")
+            .unwrap();
+        frame
+            .print("hello")
+            .unwrap();
+
+        assert_eq!(frame.rows.len(), 2);
+        assert_eq!(last_row(&frame), ">ab");
+    }
+
+
+    #[test]
+    fn it_replace_rows() {
+        gop::test_init();
+
+        let mut frame = TextFrame::new(
+            AscIICharWriter::new(),
+            Size::new(2, 2),
+            PixelFormat::Rgb,
+            config::Builder::new().build(),
+        )
+        .unwrap();
+
+        frame
+            .append_string("old")
+            .unwrap();
+        frame
+            .replace("abc\nd\ne")
+            .unwrap();
+
+        assert_eq!(frame.rows.len(), 2);
+        assert_eq!(frame.rows[0].texts(), ['a', 'b']);
+        assert_eq!(last_row(&frame), "d");
+    }
+
+
+    fn last_row(frame: &TextFrame) -> String {
+        frame
+            .rows
+            .last()
+            .unwrap()
+            .texts()
+            .iter()
+            .collect()
+    }
 }
@@ -80,6 +80,17 @@ impl TextBoxLayer {
     }
 
 
+    /// Outputs the message without disturbing the text being typed.
+    pub fn print(&mut self, message: &str) -> KernelResult {
+        self.text_layer()
+            .print(message)?;
+
+        self.update_text_cursor_pos();
+
+        Ok(())
+    }
+
+
     #[inline]
     pub fn history_up(&mut self) -> KernelResult {
         self.text_layer()
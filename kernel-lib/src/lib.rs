@@ -20,6 +20,7 @@ extern crate alloc;
 pub mod acpi;
 pub mod allocator;
 pub mod apic;
+pub mod backtrace;
 pub mod context;
 pub mod control_registers;
 pub mod error;
@@ -34,6 +35,7 @@ pub mod register;
 pub mod segmentation;
 pub mod serial;
 pub mod sync;
+pub mod syscall;
 pub mod task;
 pub mod timer;
 
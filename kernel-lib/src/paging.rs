@@ -1,10 +1,23 @@
 use crate::control_registers::{read_cr3, set_cr3};
 
-const PAGE_SIZE_4K: usize = 4096;
+pub mod address_space;
+mod frame;
+pub mod guard;
+pub mod region;
+
+pub const PAGE_SIZE_4K: usize = 4096;
 pub const PAGE_SIZE_2M: usize = 512 * PAGE_SIZE_4K;
 const PAGE_SIZE_1G: usize = 512 * PAGE_SIZE_2M;
 const PAGE_DIRECTORY_COUNT: usize = 64;
 
+/// The end of the memory the kernel maps to the same physical addresses.
+pub const IDENTITY_MAPPED_END: u64 = (PAGE_DIRECTORY_COUNT * PAGE_SIZE_1G) as u64;
+
+const PRESENT_BIT: u64 = 0x001;
+const WRITABLE_BIT: u64 = 0x002;
+const USER_BIT: u64 = 0x004;
+const HUGE_PAGE_BIT: u64 = 0x080;
+
 static mut PML4_TABLE: Pml4Table = Pml4Table::new();
 static mut PDR_TABLE: Pml4Table = Pml4Table::new();
 static mut PAGE_DIRECTORY: PageDirectory = PageDirectory::new();
@@ -32,16 +45,20 @@ impl Pml4Table {
 
 pub fn setup_identity_page_table() {
     unsafe {
-        PML4_TABLE.0[0] = PDR_TABLE.0.as_ptr() as u64 | 0x003;
+        PML4_TABLE.0[0] = PDR_TABLE.0.as_ptr() as u64 | PRESENT_BIT | WRITABLE_BIT;
         for pdr in 0..PAGE_DIRECTORY_COUNT {
             PDR_TABLE.0[pdr] = PAGE_DIRECTORY
                 .0
                 .as_ptr()
                 .add(pdr) as u64
-                | 0x003;
+                | PRESENT_BIT
+                | WRITABLE_BIT;
             for directory in 0..512 {
                 PAGE_DIRECTORY.0[pdr][directory] =
-                    (pdr * PAGE_SIZE_1G + directory * PAGE_SIZE_2M) as u64 | 0x083
+                    (pdr * PAGE_SIZE_1G + directory * PAGE_SIZE_2M) as u64
+                    | PRESENT_BIT
+                    | WRITABLE_BIT
+                    | HUGE_PAGE_BIT;
             }
         }
 
@@ -50,3 +67,10 @@ pub fn setup_identity_page_table() {
         assert_eq!(read_cr3(), cr3);
     }
 }
+
+
+/// Returns the entries of the identity mapped page table the kernel runs on.
+#[inline]
+fn kernel_pml4_entries() -> &'static [u64; 512] {
+    unsafe { &PML4_TABLE.0 }
+}
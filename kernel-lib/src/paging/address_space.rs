@@ -0,0 +1,437 @@
+use core::arch::asm;
+
+use crate::control_registers::{read_cr3, set_cr3};
+use crate::error::{KernelResult, PagingReason};
+use crate::paging::frame::{allocate_frame, free_frame};
+use crate::paging::region::{Region, RegionMap};
+use crate::paging::{kernel_pml4_entries, PAGE_SIZE_4K, PRESENT_BIT, USER_BIT, WRITABLE_BIT};
+
+/// The start of the upper half, which is private to each address space.
+///
+/// The lower half is shared with the kernel's identity mapping.
+pub const USER_SPACE_START: u64 = 0xFFFF_8000_0000_0000;
+
+const USER_PML4_INDEX: usize = 256;
+const ENTRY_COUNT: usize = 512;
+const PML4_LEVEL: usize = 3;
+const ADDRESS_MASK: u64 = 0x000F_FFFF_FFFF_F000;
+
+/// An available bit marking pages whose frames are freed with the mapping.
+const OWNED_BIT: u64 = 0x200;
+
+
+/// A page table whose lower half is the kernel and whose upper half is private.
+///
+/// Pages in the upper half are mapped in 4 KiB units and accessible from ring 3.
+/// Pages in the regions are mapped on demand by [`AddressSpace::map_on_demand`].
+#[derive(Debug)]
+pub struct AddressSpace {
+    pml4: u64,
+    regions: RegionMap,
+}
+
+
+impl AddressSpace {
+    pub fn new() -> KernelResult<Self> {
+        let pml4 = allocate_frame()?;
+        unsafe {
+            table_mut(pml4)[..USER_PML4_INDEX]
+                .copy_from_slice(&kernel_pml4_entries()[..USER_PML4_INDEX]);
+        }
+
+        Ok(Self {
+            pml4,
+            regions: RegionMap::new(),
+        })
+    }
+
+
+    /// Returns the value to be loaded into CR3.
+    #[inline]
+    pub fn cr3(&self) -> u64 {
+        self.pml4
+    }
+
+
+    /// Runs `f` with this address space loaded into CR3 and restores the previous one.
+    pub fn with_activated<T>(&mut self, f: impl FnOnce(&mut Self) -> T) -> T {
+        let prev = read_cr3();
+        set_cr3(self.cr3());
+
+        let result = f(self);
+
+        set_cr3(prev);
+        result
+    }
+
+
+    /// Maps the page at `virt` to the frame at `phys`.
+    ///
+    /// The frame is not freed when the page is unmapped.
+    pub fn map(&mut self, virt: u64, phys: u64, writable: bool) -> KernelResult {
+        self.map_entry(virt, phys, page_flags(writable))
+    }
+
+
+    /// Maps the page at `virt` to a newly allocated zeroed frame and returns the frame.
+    ///
+    /// The frame is freed when the page is unmapped or the address space is dropped.
+    pub fn map_zeroed(&mut self, virt: u64, writable: bool) -> KernelResult<u64> {
+        let frame = allocate_frame()?;
+        if let Err(e) = self.map_entry(virt, frame, page_flags(writable) | OWNED_BIT) {
+            unsafe { free_frame(frame) };
+            return Err(e);
+        }
+
+        Ok(frame)
+    }
+
+
+    /// Maps zeroed frames to the pages overlapping `start..end` that are not mapped yet.
+    pub fn map_zeroed_range(&mut self, start: u64, end: u64, writable: bool) -> KernelResult {
+        let mut page = align_down(start);
+        while page < end {
+            if self.translate(page).is_none() {
+                self.map_zeroed(page, writable)?;
+            }
+            page += PAGE_SIZE_4K as u64;
+        }
+
+        Ok(())
+    }
+
+
+    /// Reserves the region whose pages are mapped when first touched.
+    pub fn add_region(&mut self, region: Region) -> KernelResult {
+        check_user_page(region.start())?;
+        check_user_page(region.end())?;
+
+        self.regions.insert(region)
+    }
+
+
+    #[inline]
+    pub fn regions(&self) -> &RegionMap {
+        &self.regions
+    }
+
+
+    /// Maps a zeroed frame to the page containing `addr` if it is in a region.
+    ///
+    /// Called when a page fault occurs in this address space.
+    /// Fails if the address is out of the regions or the page is already mapped,
+    /// in which case the access is truly invalid.
+    pub fn map_on_demand(&mut self, addr: u64, write: bool) -> KernelResult {
+        let region = self
+            .regions
+            .find(addr)
+            .ok_or(PagingReason::NotMapped(addr))?;
+        if write && !region.writable() {
+            return Err(PagingReason::NotWritable(addr).into());
+        }
+
+        let writable = region.writable();
+        self.map_zeroed(align_down(addr), writable)?;
+        Ok(())
+    }
+
+
+    /// Removes the mapping of the page at `virt`.
+    pub fn unmap(&mut self, virt: u64) -> KernelResult {
+        self.unmap_entry(virt)?;
+        flush_tlb(virt);
+
+        Ok(())
+    }
+
+
+    /// Returns the physical address `virt` is mapped to.
+    pub fn translate(&self, virt: u64) -> Option<u64> {
+        if virt < USER_SPACE_START {
+            return None;
+        }
+
+        let entry = unsafe { *self.find_entry(virt)? };
+        if entry & PRESENT_BIT == 0 {
+            None
+        } else {
+            Some((entry & ADDRESS_MASK) + (virt & (PAGE_SIZE_4K as u64 - 1)))
+        }
+    }
+
+
+    fn map_entry(&mut self, virt: u64, phys: u64, flags: u64) -> KernelResult {
+        check_user_page(virt)?;
+        if phys % PAGE_SIZE_4K as u64 != 0 {
+            return Err(PagingReason::NotAligned(phys).into());
+        }
+
+        let entry = self.find_or_create_entry(virt)?;
+        unsafe {
+            if *entry & PRESENT_BIT != 0 {
+                return Err(PagingReason::AlreadyMapped(virt).into());
+            }
+
+            *entry = phys | flags;
+        }
+
+        Ok(())
+    }
+
+
+    fn unmap_entry(&mut self, virt: u64) -> KernelResult {
+        check_user_page(virt)?;
+
+        let entry = self
+            .find_entry(virt)
+            .filter(|entry| unsafe { **entry & PRESENT_BIT != 0 })
+            .ok_or(PagingReason::NotMapped(virt))?;
+
+        unsafe {
+            if *entry & OWNED_BIT != 0 {
+                free_frame(*entry & ADDRESS_MASK);
+            }
+            *entry = 0;
+        }
+
+        Ok(())
+    }
+
+
+    /// Returns the page table entry of `virt` if all the upper tables exist.
+    fn find_entry(&self, virt: u64) -> Option<*mut u64> {
+        let mut table = self.pml4;
+        for level in (1..=PML4_LEVEL).rev() {
+            let entry = unsafe { table_mut(table)[table_index(virt, level)] };
+            if entry & PRESENT_BIT == 0 {
+                return None;
+            }
+
+            table = entry & ADDRESS_MASK;
+        }
+
+        Some(unsafe { &mut table_mut(table)[table_index(virt, 0)] as *mut u64 })
+    }
+
+
+    fn find_or_create_entry(&mut self, virt: u64) -> KernelResult<*mut u64> {
+        let mut table = self.pml4;
+        for level in (1..=PML4_LEVEL).rev() {
+            let entry = unsafe { &mut table_mut(table)[table_index(virt, level)] };
+            if *entry & PRESENT_BIT == 0 {
+                *entry = allocate_frame()? | PRESENT_BIT | WRITABLE_BIT | USER_BIT;
+            }
+
+            table = *entry & ADDRESS_MASK;
+        }
+
+        Ok(unsafe { &mut table_mut(table)[table_index(virt, 0)] as *mut u64 })
+    }
+}
+
+
+impl Drop for AddressSpace {
+    fn drop(&mut self) {
+        unsafe {
+            for entry in table_mut(self.pml4)[USER_PML4_INDEX..]
+                .iter()
+                .filter(|entry| **entry & PRESENT_BIT != 0)
+            {
+                free_table(*entry & ADDRESS_MASK, PML4_LEVEL - 1);
+            }
+
+            free_frame(self.pml4);
+        }
+    }
+}
+
+
+unsafe fn free_table(table: u64, level: usize) {
+    for entry in table_mut(table)
+        .iter()
+        .filter(|entry| **entry & PRESENT_BIT != 0)
+    {
+        if 0 < level {
+            free_table(*entry & ADDRESS_MASK, level - 1);
+        } else if *entry & OWNED_BIT != 0 {
+            free_frame(*entry & ADDRESS_MASK);
+        }
+    }
+
+    free_frame(table);
+}
+
+
+#[inline]
+unsafe fn table_mut(table: u64) -> &'static mut [u64; ENTRY_COUNT] {
+    &mut *(table as *mut [u64; ENTRY_COUNT])
+}
+
+
+#[inline]
+fn table_index(virt: u64, level: usize) -> usize {
+    ((virt >> (12 + 9 * level)) & 0x1FF) as usize
+}
+
+
+#[inline]
+fn page_flags(writable: bool) -> u64 {
+    if writable {
+        PRESENT_BIT | USER_BIT | WRITABLE_BIT
+    } else {
+        PRESENT_BIT | USER_BIT
+    }
+}
+
+
+#[inline]
+fn align_down(addr: u64) -> u64 {
+    addr & !(PAGE_SIZE_4K as u64 - 1)
+}
+
+
+fn check_user_page(virt: u64) -> KernelResult {
+    if virt < USER_SPACE_START {
+        Err(PagingReason::NotUserSpace(virt).into())
+    } else if virt % PAGE_SIZE_4K as u64 != 0 {
+        Err(PagingReason::NotAligned(virt).into())
+    } else {
+        Ok(())
+    }
+}
+
+
+#[inline]
+fn flush_tlb(virt: u64) {
+    unsafe {
+        asm!("invlpg [{}]", in(reg) virt, options(nostack, preserves_flags));
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::error::{KernelError, PagingReason};
+    use crate::paging::address_space::{AddressSpace, USER_SPACE_START};
+    use crate::paging::region::{Region, RegionKind};
+
+    #[test]
+    fn it_map_and_translate() {
+        let mut space = AddressSpace::new().unwrap();
+        let frame = space
+            .map_zeroed(USER_SPACE_START + 0x3000, true)
+            .unwrap();
+
+        assert_eq!(space.translate(USER_SPACE_START + 0x3010), Some(frame + 0x10));
+        assert_eq!(space.translate(USER_SPACE_START + 0x4000), None);
+    }
+
+
+    #[test]
+    fn it_unmap_entry() {
+        let mut space = AddressSpace::new().unwrap();
+        space
+            .map_zeroed(USER_SPACE_START, true)
+            .unwrap();
+
+        space
+            .unmap_entry(USER_SPACE_START)
+            .unwrap();
+
+        assert_eq!(space.translate(USER_SPACE_START), None);
+        assert!(matches!(
+            space.unmap_entry(USER_SPACE_START),
+            Err(KernelError::FailedOperatePaging(PagingReason::NotMapped(_)))
+        ));
+    }
+
+
+    #[test]
+    fn it_failed_map_out_of_user_space() {
+        let mut space = AddressSpace::new().unwrap();
+
+        assert!(matches!(
+            space.map_zeroed(0x1000, true),
+            Err(KernelError::FailedOperatePaging(PagingReason::NotUserSpace(0x1000)))
+        ));
+    }
+
+
+    #[test]
+    fn it_failed_map_twice() {
+        let mut space = AddressSpace::new().unwrap();
+        space
+            .map_zeroed(USER_SPACE_START, true)
+            .unwrap();
+
+        assert!(matches!(
+            space.map_zeroed(USER_SPACE_START, false),
+            Err(KernelError::FailedOperatePaging(PagingReason::AlreadyMapped(_)))
+        ));
+    }
+
+
+    #[test]
+    fn it_map_zeroed_range() {
+        let mut space = AddressSpace::new().unwrap();
+        space
+            .map_zeroed_range(USER_SPACE_START + 0x0800, USER_SPACE_START + 0x2001, true)
+            .unwrap();
+
+        assert!(space.translate(USER_SPACE_START).is_some());
+        assert!(space.translate(USER_SPACE_START + 0x1000).is_some());
+        assert!(space.translate(USER_SPACE_START + 0x2000).is_some());
+        assert!(space.translate(USER_SPACE_START + 0x3000).is_none());
+    }
+
+
+    #[test]
+    fn it_map_on_demand() {
+        let mut space = AddressSpace::new().unwrap();
+        space
+            .add_region(Region::new(
+                USER_SPACE_START,
+                USER_SPACE_START + 0x4000,
+                true,
+                RegionKind::Anonymous,
+            ))
+            .unwrap();
+
+        space
+            .map_on_demand(USER_SPACE_START + 0x2345, true)
+            .unwrap();
+
+        assert!(space.translate(USER_SPACE_START + 0x2000).is_some());
+        assert!(space.translate(USER_SPACE_START + 0x1000).is_none());
+        assert!(matches!(
+            space.map_on_demand(USER_SPACE_START + 0x2000, false),
+            Err(KernelError::FailedOperatePaging(PagingReason::AlreadyMapped(_)))
+        ));
+        assert!(matches!(
+            space.map_on_demand(USER_SPACE_START + 0x4000, false),
+            Err(KernelError::FailedOperatePaging(PagingReason::NotMapped(_)))
+        ));
+    }
+
+
+    #[test]
+    fn it_failed_write_on_demand_to_read_only_region() {
+        let mut space = AddressSpace::new().unwrap();
+        space
+            .add_region(Region::new(
+                USER_SPACE_START,
+                USER_SPACE_START + 0x1000,
+                false,
+                RegionKind::Anonymous,
+            ))
+            .unwrap();
+
+        assert!(matches!(
+            space.map_on_demand(USER_SPACE_START, true),
+            Err(KernelError::FailedOperatePaging(PagingReason::NotWritable(_)))
+        ));
+        assert!(space
+            .map_on_demand(USER_SPACE_START, false)
+            .is_ok());
+    }
+}
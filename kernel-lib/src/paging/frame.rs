@@ -0,0 +1,31 @@
+use alloc::alloc::{alloc_zeroed, dealloc};
+use core::alloc::Layout;
+
+use crate::error::{KernelResult, PagingReason};
+use crate::paging::PAGE_SIZE_4K;
+
+/// Allocates a zeroed 4 KiB frame for page tables and user pages.
+///
+/// The kernel is identity mapped, so the returned address is also the physical address.
+pub(crate) fn allocate_frame() -> KernelResult<u64> {
+    let frame = unsafe { alloc_zeroed(frame_layout()) };
+    if frame.is_null() {
+        Err(PagingReason::NoFreeFrame.into())
+    } else {
+        Ok(frame as u64)
+    }
+}
+
+
+/// # Safety
+///
+/// The frame must have been returned by [`allocate_frame`] and no longer be mapped.
+pub(crate) unsafe fn free_frame(frame: u64) {
+    dealloc(frame as *mut u8, frame_layout());
+}
+
+
+#[inline]
+fn frame_layout() -> Layout {
+    Layout::from_size_align(PAGE_SIZE_4K, PAGE_SIZE_4K).unwrap()
+}
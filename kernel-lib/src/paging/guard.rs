@@ -0,0 +1,171 @@
+use core::arch::asm;
+
+use crate::error::{KernelResult, PagingReason};
+use crate::interrupt::asm::without_interrupt;
+use crate::control_registers::{read_cr3, set_cr3};
+use crate::paging::frame::{allocate_frame, free_frame};
+use crate::paging::{
+    HUGE_PAGE_BIT, IDENTITY_MAPPED_END, PAGE_DIRECTORY, PAGE_SIZE_1G, PAGE_SIZE_2M, PAGE_SIZE_4K,
+    PRESENT_BIT,
+};
+
+const ENTRY_COUNT: usize = 512;
+const ADDRESS_MASK: u64 = 0x000F_FFFF_FFFF_F000;
+
+
+/// Makes the 4 KiB page at `addr` in the kernel's identity mapping not present,
+/// so that any access to it causes a page fault.
+///
+/// The 2 MiB page containing it is split into 4 KiB pages on first use.
+pub fn install_guard_page(addr: u64) -> KernelResult {
+    set_present(addr, false)
+}
+
+
+/// Maps back the page made not present by [`install_guard_page`].
+///
+/// The 4 KiB pages are merged back into the 2 MiB page
+/// once the last guard page in it is removed.
+pub fn remove_guard_page(addr: u64) -> KernelResult {
+    set_present(addr, true)
+}
+
+
+/// Returns whether the page at `addr` in the kernel's identity mapping is present,
+/// which is false for guard pages and addresses beyond the mapping.
+///
+/// Called from exception handlers, so this neither allocates nor waits.
+pub fn is_present(addr: u64) -> bool {
+    if IDENTITY_MAPPED_END <= addr {
+        return false;
+    }
+
+    let directory = addr as usize / PAGE_SIZE_1G;
+    let index = addr as usize % PAGE_SIZE_1G / PAGE_SIZE_2M;
+
+    unsafe {
+        let directory_entry = PAGE_DIRECTORY.0[directory][index];
+        if directory_entry & HUGE_PAGE_BIT != 0 {
+            return directory_entry & PRESENT_BIT != 0;
+        }
+
+        let table = &*((directory_entry & ADDRESS_MASK) as *const [u64; ENTRY_COUNT]);
+        table[(addr as usize % PAGE_SIZE_2M) / PAGE_SIZE_4K] & PRESENT_BIT != 0
+    }
+}
+
+
+fn set_present(addr: u64, present: bool) -> KernelResult {
+    if addr % PAGE_SIZE_4K as u64 != 0 {
+        return Err(PagingReason::NotAligned(addr).into());
+    }
+    if IDENTITY_MAPPED_END <= addr {
+        return Err(PagingReason::NotMapped(addr).into());
+    }
+
+    let directory = addr as usize / PAGE_SIZE_1G;
+    let index = addr as usize % PAGE_SIZE_1G / PAGE_SIZE_2M;
+
+    without_interrupt(|| unsafe {
+        let directory_entry = &mut PAGE_DIRECTORY.0[directory][index];
+        if *directory_entry & HUGE_PAGE_BIT != 0 {
+            *directory_entry = split_huge_page(*directory_entry)?;
+        }
+
+        let table_addr = *directory_entry & ADDRESS_MASK;
+        let table = &mut *(table_addr as *mut [u64; ENTRY_COUNT]);
+        let entry = &mut table[(addr as usize % PAGE_SIZE_2M) / PAGE_SIZE_4K];
+        if present {
+            *entry |= PRESENT_BIT;
+        } else {
+            *entry &= !PRESENT_BIT;
+        }
+
+        match merged_huge_page(table) {
+            Some(huge_entry) => {
+                *directory_entry = huge_entry;
+                // Reloading CR3 also drops the cached entries of the freed page table.
+                set_cr3(read_cr3());
+                free_frame(table_addr);
+            }
+            None => flush_tlb(addr),
+        }
+
+        Ok(())
+    })
+}
+
+
+/// Creates a page table mapping the same 2 MiB as the huge page entry
+/// and returns the page directory entry pointing to it.
+fn split_huge_page(huge_entry: u64) -> KernelResult<u64> {
+    let flags = huge_entry & !ADDRESS_MASK & !HUGE_PAGE_BIT;
+    let base = huge_entry & ADDRESS_MASK;
+
+    let table = allocate_frame()?;
+    let entries = unsafe { &mut *(table as *mut [u64; ENTRY_COUNT]) };
+    for (i, entry) in entries.iter_mut().enumerate() {
+        *entry = (base + (i * PAGE_SIZE_4K) as u64) | flags;
+    }
+
+    Ok(table | flags)
+}
+
+
+/// Returns the huge page entry mapping the same 2 MiB as the page table
+/// if all the pages in it are present with the same flags.
+fn merged_huge_page(table: &[u64; ENTRY_COUNT]) -> Option<u64> {
+    let flags = table[0] & !ADDRESS_MASK;
+    let base = table[0] & ADDRESS_MASK;
+
+    let mergeable = flags & PRESENT_BIT != 0
+        && table
+            .iter()
+            .enumerate()
+            .all(|(i, entry)| *entry == (base + (i * PAGE_SIZE_4K) as u64) | flags);
+
+    mergeable.then_some(base | flags | HUGE_PAGE_BIT)
+}
+
+
+#[inline]
+fn flush_tlb(addr: u64) {
+    unsafe {
+        asm!("invlpg [{}]", in(reg) addr, options(nostack, preserves_flags));
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::paging::guard::{merged_huge_page, split_huge_page, ENTRY_COUNT};
+    use crate::paging::{HUGE_PAGE_BIT, PAGE_SIZE_2M, PRESENT_BIT, WRITABLE_BIT};
+
+    #[test]
+    fn it_split_huge_page() {
+        let base = 3 * PAGE_SIZE_2M as u64;
+        let entry = split_huge_page(base | PRESENT_BIT | WRITABLE_BIT | HUGE_PAGE_BIT).unwrap();
+
+        assert_eq!(entry & HUGE_PAGE_BIT, 0);
+        assert_eq!(entry & (PRESENT_BIT | WRITABLE_BIT), PRESENT_BIT | WRITABLE_BIT);
+
+        let table = unsafe { &*((entry & !0xFFF) as *const [u64; ENTRY_COUNT]) };
+        assert_eq!(table[0], base | PRESENT_BIT | WRITABLE_BIT);
+        assert_eq!(table[511], (base + 511 * 4096) | PRESENT_BIT | WRITABLE_BIT);
+    }
+
+
+    #[test]
+    fn it_merge_back_only_when_all_pages_are_present() {
+        let base = 3 * PAGE_SIZE_2M as u64;
+        let huge_entry = base | PRESENT_BIT | WRITABLE_BIT | HUGE_PAGE_BIT;
+        let entry = split_huge_page(huge_entry).unwrap();
+        let table = unsafe { &mut *((entry & !0xFFF) as *mut [u64; ENTRY_COUNT]) };
+
+        table[5] &= !PRESENT_BIT;
+        assert_eq!(merged_huge_page(table), None);
+
+        table[5] |= PRESENT_BIT;
+        assert_eq!(merged_huge_page(table), Some(huge_entry));
+    }
+}
@@ -0,0 +1,156 @@
+use alloc::collections::BTreeMap;
+
+use crate::error::{KernelResult, PagingReason};
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum RegionKind {
+    /// Memory such as the segments of the application, zeroed on first touch.
+    Anonymous,
+
+    /// The stack of the application, which grows down as it is touched.
+    Stack,
+}
+
+
+/// A range of virtual addresses whose pages are mapped when first touched.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Region {
+    start: u64,
+    end: u64,
+    writable: bool,
+    kind: RegionKind,
+}
+
+
+impl Region {
+    #[inline]
+    pub const fn new(start: u64, end: u64, writable: bool, kind: RegionKind) -> Self {
+        Self {
+            start,
+            end,
+            writable,
+            kind,
+        }
+    }
+
+
+    #[inline]
+    pub const fn start(&self) -> u64 {
+        self.start
+    }
+
+
+    #[inline]
+    pub const fn end(&self) -> u64 {
+        self.end
+    }
+
+
+    #[inline]
+    pub const fn writable(&self) -> bool {
+        self.writable
+    }
+
+
+    #[inline]
+    pub const fn kind(&self) -> RegionKind {
+        self.kind
+    }
+
+
+    #[inline]
+    pub const fn contains(&self, addr: u64) -> bool {
+        self.start <= addr && addr < self.end
+    }
+}
+
+
+/// Regions of an address space keyed by their start address.
+#[derive(Debug, Default)]
+pub struct RegionMap(BTreeMap<u64, Region>);
+
+
+impl RegionMap {
+    #[inline]
+    pub const fn new() -> Self {
+        Self(BTreeMap::new())
+    }
+
+
+    pub fn insert(&mut self, region: Region) -> KernelResult {
+        let overlapped = self
+            .0
+            .range(..region.end)
+            .next_back()
+            .is_some_and(|(_, prev)| region.start < prev.end);
+        if overlapped || region.end <= region.start {
+            return Err(PagingReason::InvalidRegion(region.start).into());
+        }
+
+        self.0.insert(region.start, region);
+        Ok(())
+    }
+
+
+    /// Returns the region containing `addr`.
+    pub fn find(&self, addr: u64) -> Option<&Region> {
+        self.0
+            .range(..=addr)
+            .next_back()
+            .map(|(_, region)| region)
+            .filter(|region| region.contains(addr))
+    }
+
+
+    #[inline]
+    pub fn remove(&mut self, start: u64) -> Option<Region> {
+        self.0.remove(&start)
+    }
+
+
+    #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = &Region> {
+        self.0.values()
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::paging::region::{Region, RegionKind, RegionMap};
+
+    #[test]
+    fn it_find_region() {
+        let mut regions = RegionMap::new();
+        regions
+            .insert(Region::new(0x1000, 0x3000, true, RegionKind::Anonymous))
+            .unwrap();
+        regions
+            .insert(Region::new(0x8000, 0x9000, true, RegionKind::Stack))
+            .unwrap();
+
+        assert_eq!(regions.find(0x0FFF), None);
+        assert_eq!(regions.find(0x2FFF).unwrap().start(), 0x1000);
+        assert_eq!(regions.find(0x3000), None);
+        assert_eq!(regions.find(0x8000).unwrap().kind(), RegionKind::Stack);
+    }
+
+
+    #[test]
+    fn it_failed_insert_overlapped_region() {
+        let mut regions = RegionMap::new();
+        regions
+            .insert(Region::new(0x2000, 0x4000, true, RegionKind::Anonymous))
+            .unwrap();
+
+        assert!(regions
+            .insert(Region::new(0x1000, 0x3000, true, RegionKind::Anonymous))
+            .is_err());
+        assert!(regions
+            .insert(Region::new(0x3000, 0x5000, true, RegionKind::Anonymous))
+            .is_err());
+        assert!(regions
+            .insert(Region::new(0x4000, 0x5000, true, RegionKind::Anonymous))
+            .is_ok());
+    }
+}
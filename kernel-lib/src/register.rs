@@ -1,3 +1,4 @@
+pub mod msr;
 pub mod read;
 pub mod rflags;
 
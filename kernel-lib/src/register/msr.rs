@@ -0,0 +1,42 @@
+use core::arch::asm;
+
+pub const IA32_EFER: u32 = 0xC000_0080;
+pub const IA32_STAR: u32 = 0xC000_0081;
+pub const IA32_LSTAR: u32 = 0xC000_0082;
+pub const IA32_FMASK: u32 = 0xC000_0084;
+
+
+/// Reads the model specific register.
+#[inline(always)]
+pub fn read_msr(msr: u32) -> u64 {
+    let low: u32;
+    let high: u32;
+    unsafe {
+        asm!(
+            "rdmsr",
+            in("ecx") msr,
+            out("eax") low,
+            out("edx") high,
+            options(nostack, nomem, preserves_flags)
+        );
+    }
+
+    ((high as u64) << 32) | low as u64
+}
+
+
+/// Writes the model specific register.
+///
+/// # Safety
+///
+/// Writing a wrong value can break the behavior of the processor.
+#[inline(always)]
+pub unsafe fn write_msr(msr: u32, value: u64) {
+    asm!(
+        "wrmsr",
+        in("ecx") msr,
+        in("eax") value as u32,
+        in("edx") (value >> 32) as u32,
+        options(nostack, preserves_flags)
+    );
+}
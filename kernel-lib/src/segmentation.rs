@@ -1,2 +1,13 @@
 pub mod asm;
 pub mod descriptor;
+pub mod tss;
+
+
+pub const KERNEL_CODE_SEGMENT: u16 = 1 << 3;
+pub const KERNEL_DATA_SEGMENT: u16 = 2 << 3;
+
+/// The user segments follow the kernel data segment in this order
+/// because `sysret` loads SS and CS relative to the selector in the `STAR` register.
+pub const USER_DATA_SEGMENT: u16 = (3 << 3) | 3;
+pub const USER_CODE_SEGMENT: u16 = (4 << 3) | 3;
+pub const TSS_SEGMENT: u16 = 5 << 3;
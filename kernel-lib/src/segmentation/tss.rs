@@ -0,0 +1,63 @@
+use core::ptr::{addr_of, addr_of_mut};
+
+use x86_64::structures::tss::TaskStateSegment;
+use x86_64::VirtAddr;
+
+/// The interrupt stack table entry the double fault handler runs on, counted from 1 as in the IDT.
+pub const DOUBLE_FAULT_IST_INDEX: u8 = 1;
+
+const INTERRUPT_STACK_SIZE: usize = 4096 * 5;
+
+pub(crate) static mut TSS: TaskStateSegment = TaskStateSegment::new();
+
+/// The stack the double fault handler runs on,
+/// which must be valid even when the current stack overflows.
+static mut DOUBLE_FAULT_STACK: [u8; INTERRUPT_STACK_SIZE] = [0; INTERRUPT_STACK_SIZE];
+
+
+/// Returns the task state segment to register in the GDT.
+///
+/// # Safety
+///
+/// The segment is also rewritten on every task switch, so callers must not keep
+/// any values read through the reference.
+pub unsafe fn tss() -> &'static TaskStateSegment {
+    &*addr_of_mut!(TSS)
+}
+
+
+/// Registers the stacks of the interrupt stack table.
+pub fn init_interrupt_stack_table() {
+    unsafe {
+        let stack_end = addr_of!(DOUBLE_FAULT_STACK) as u64 + INTERRUPT_STACK_SIZE as u64;
+        TSS.interrupt_stack_table[DOUBLE_FAULT_IST_INDEX as usize - 1] =
+            VirtAddr::new(stack_end & !0xF);
+    }
+}
+
+
+/// Returns the stack pointer loaded when an interrupt occurs in ring 3.
+#[inline]
+pub fn rsp0() -> u64 {
+    unsafe { rsp0_ptr().read_unaligned() }
+}
+
+
+#[inline]
+pub fn set_rsp0(rsp0: u64) {
+    unsafe {
+        rsp0_ptr().write_unaligned(rsp0);
+    }
+}
+
+
+/// Returns the address of RSP0 for assembly that switches stacks.
+///
+/// The task state segment is packed, so the address is only 4 bytes aligned.
+#[inline]
+pub fn rsp0_ptr() -> *mut u64 {
+    unsafe {
+        addr_of_mut!(TSS.privilege_stack_table)
+            .cast::<u64>()
+    }
+}
@@ -0,0 +1,170 @@
+use core::arch::asm;
+
+use common_lib::syscall::{Syscall, SyscallArgs, SyscallError, SyscallResult};
+
+use crate::interrupt::asm::sti;
+use crate::register::msr::{read_msr, write_msr, IA32_EFER, IA32_FMASK, IA32_LSTAR, IA32_STAR};
+use crate::segmentation::tss::TSS;
+use crate::segmentation::{KERNEL_CODE_SEGMENT, KERNEL_DATA_SEGMENT};
+use crate::task::app::exit_app_if_kill_requested;
+
+const EFER_SYSTEM_CALL_EXTENSIONS: u64 = 0x01;
+
+/// Interrupts, the trap flag and the direction flag are cleared on entry.
+const FMASK: u64 = 0x0200 | 0x0100 | 0x0400;
+
+pub type SyscallHandler = fn(&[u64; 6]) -> SyscallResult;
+
+pub static mut SYSCALL_TABLE: SyscallTable = SyscallTable::new();
+
+/// Holds the user stack pointer until it is pushed onto the kernel stack.
+static mut USER_RSP: u64 = 0;
+
+
+/// Enables `syscall` and `sysret` with [`SYSCALL_TABLE`] as the dispatch table.
+///
+/// # Safety
+///
+/// The GDT must have the user segments laid out as `sysret` expects.
+pub unsafe fn init() {
+    write_msr(IA32_EFER, read_msr(IA32_EFER) | EFER_SYSTEM_CALL_EXTENSIONS);
+    write_msr(
+        IA32_STAR,
+        ((KERNEL_DATA_SEGMENT as u64) << 48) | ((KERNEL_CODE_SEGMENT as u64) << 32),
+    );
+    write_msr(IA32_LSTAR, asm_syscall_entry as *const () as u64);
+    write_msr(IA32_FMASK, FMASK);
+}
+
+
+pub struct SyscallTable([Option<SyscallHandler>; Syscall::COUNT]);
+
+
+impl SyscallTable {
+    pub const fn new() -> Self {
+        Self([None; Syscall::COUNT])
+    }
+
+
+    pub fn register(&mut self, syscall: Syscall, handler: SyscallHandler) {
+        self.0[syscall as usize] = Some(handler);
+    }
+
+
+    pub fn dispatch(&self, args: &SyscallArgs) -> SyscallResult {
+        Syscall::from_number(args.number)
+            .and_then(|syscall| self.0[syscall as usize])
+            .map(|handler| handler(&args.args))
+            .unwrap_or(SyscallResult::err(SyscallError::NotSupported))
+    }
+}
+
+
+impl Default for SyscallTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+
+/// Called from [`asm_syscall_entry`] on the kernel stack of the task.
+///
+/// Interrupts are enabled again so that handlers which wait can be preempted.
+/// The application exits instead of returning if it was killed meanwhile,
+/// which is safe since the handler has released everything it locked.
+extern "sysv64" fn dispatch(args: &SyscallArgs) -> SyscallResult {
+    sti();
+
+    let result = unsafe { SYSCALL_TABLE.dispatch(args) };
+    unsafe { exit_app_if_kill_requested() };
+    result
+}
+
+
+/// Switches to the stack in RSP0 of the task state segment and calls [`dispatch`].
+///
+/// The arguments are pushed in the layout of [`SyscallArgs`].
+#[naked]
+extern "sysv64" fn asm_syscall_entry() {
+    unsafe {
+        asm!(
+        "
+        mov [rip + {user_rsp}], rsp
+        mov rsp, [rip + {tss} + 4]
+
+        push QWORD PTR [rip + {user_rsp}]
+        // RIP of the caller
+        push rcx
+        // RFLAGS of the caller
+        push r11
+        push rbp
+        mov rbp, rsp
+        and rsp, -16
+        sub rsp, 8
+
+        push r9
+        push r8
+        push r10
+        push rdx
+        push rsi
+        push rdi
+        push rax
+
+        mov rdi, rsp
+        call {dispatch}
+
+        cli
+        mov rsp, rbp
+        pop rbp
+        pop r11
+        pop rcx
+        pop rsp
+
+        sysretq
+        ",
+        user_rsp = sym USER_RSP,
+        tss = sym TSS,
+        dispatch = sym dispatch,
+        options(noreturn)
+        )
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use common_lib::syscall::{Syscall, SyscallArgs, SyscallError, SyscallResult};
+
+    use crate::syscall::SyscallTable;
+
+    #[test]
+    fn it_dispatch_registered_handler() {
+        let mut table = SyscallTable::new();
+        table.register(Syscall::GetTime, |args| SyscallResult::ok(args[0] + 1));
+
+        let result = table.dispatch(&SyscallArgs {
+            number: Syscall::GetTime as u64,
+            args: [41, 0, 0, 0, 0, 0],
+        });
+
+        assert_eq!(result, SyscallResult::ok(42));
+    }
+
+
+    #[test]
+    fn it_dispatch_unknown_number() {
+        let table = SyscallTable::new();
+
+        let unregistered = table.dispatch(&SyscallArgs {
+            number: Syscall::Write as u64,
+            args: [0; 6],
+        });
+        let unknown = table.dispatch(&SyscallArgs {
+            number: u64::MAX,
+            args: [0; 6],
+        });
+
+        assert_eq!(unregistered, SyscallResult::err(SyscallError::NotSupported));
+        assert_eq!(unknown, SyscallResult::err(SyscallError::NotSupported));
+    }
+}
@@ -1,39 +1,130 @@
-use alloc::boxed::Box;
-use alloc::collections::VecDeque;
-use alloc::vec;
+use alloc::collections::{BTreeMap, VecDeque};
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::arch::asm;
 use core::cell::OnceCell;
 use core::fmt::{Debug, Formatter};
-use core::sync::atomic::{AtomicU8, Ordering};
+use core::sync::atomic::{AtomicBool, AtomicU64, AtomicU8, Ordering};
+use core::time::Duration;
 
 use message::TaskMessage;
 
 use crate::context::arch::x86_64::Context;
-use crate::error::KernelResult;
+use crate::error::{KernelError, KernelResult, PagingReason};
 use crate::interrupt;
+use crate::paging::address_space::AddressSpace;
+use crate::{kernel_error, serial_println};
+use crate::task::builder::Builder;
+use crate::task::handle::TaskHandle;
+use crate::task::info::TaskInfo;
 use crate::task::list::TaskList;
+use crate::task::load::LoadAverage;
 use crate::task::priority_level::PriorityLevel;
 use crate::task::status::Status;
-use crate::task::status::Status::{Pending, Running, Sleep};
-
+use crate::task::stack::TaskStack;
+use crate::task::status::Status::{Exited, Pending, Running, Sleep};
+use crate::timer::{duration_to_ticks, uptime_nanos, TIME_HANDLE_MANAGER};
+
+pub mod app;
+pub mod builder;
+pub mod handle;
+pub mod info;
 mod list;
+pub mod load;
 pub mod message;
 pub mod priority_level;
-mod status;
+mod run_queue;
+mod stack;
+pub mod status;
 mod switch;
 
 
 pub static mut TASK_MANAGER: PreemptiveTaskManager = PreemptiveTaskManager::new();
 
+/// The exit code of tasks terminated by [`kill`].
+pub const EXIT_CODE_KILLED: i32 = -9;
+
+/// The stack size of kernel tasks unless [`Builder::stack_size`] sets another.
+pub const DEFAULT_STACK_SIZE: usize = 65_536;
+
+/// Exit codes not joined yet are kept for at most this many tasks.
+const MAX_EXIT_CODES: usize = 256;
+
+/// A task keeps at most this many messages not received yet,
+/// and the oldest one is dropped when another arrives.
+const MAX_PENDING_MESSAGES: usize = 1024;
+
+/// The timer ticks a task runs before the next task of the same or a higher level takes over,
+/// unless [`PreemptiveTaskManager::set_time_slice`] sets another for its level.
+const DEFAULT_TIME_SLICE: u64 = 2;
+
+
+/// Spawns a kernel task that calls `f` and exits with `0` when it returns.
+///
+/// See [`Builder`] to configure the stack size.
+pub fn spawn(
+    name: impl Into<String>,
+    priority_level: PriorityLevel,
+    f: impl FnOnce() + Send + 'static,
+) -> KernelResult<TaskHandle> {
+    Builder::new(name)
+        .priority_level(priority_level)
+        .spawn(f)
+}
+
 
 pub fn dispatch(f: impl Fn() + 'static) {
-    unsafe {
-        TASK_MANAGER
-            .send_message_at(0, TaskMessage::dispatch(f))
-            .unwrap()
+    send_message(0, TaskMessage::dispatch(f)).unwrap();
+}
+
+
+/// Sends the message to the task, waking it up if it sleeps.
+pub fn send_message(task_id: u64, message: TaskMessage) -> KernelResult {
+    unsafe { TASK_MANAGER.send_message_at(task_id, message) }
+}
+
+
+/// Takes the oldest message sent to the calling task, sleeping until one arrives.
+pub fn receive_message() -> KernelResult<TaskMessage> {
+    let task_id = current_id()?;
+
+    loop {
+        // Interrupts stay disabled until the task sleeps, so no message is missed in between.
+        interrupt::asm::cli();
+
+        match unsafe { TASK_MANAGER.receive_message_at(task_id) } {
+            Ok(Some(message)) => {
+                interrupt::asm::sti();
+                return Ok(message);
+            }
+            Ok(None) => {}
+            Err(e) => {
+                interrupt::asm::sti();
+                return Err(e);
+            }
+        }
+
+        if unsafe { TASK_MANAGER.is_kill_requested(task_id) } {
+            interrupt::asm::sti();
+            return Err(killed_error(task_id));
+        }
+        if let Err(e) = unsafe { TASK_MANAGER.sleep_at(task_id) } {
+            interrupt::asm::sti();
+            return Err(e);
+        }
     }
 }
 
 
+/// Takes the oldest message sent to the calling task without waiting.
+pub fn try_receive_message() -> KernelResult<Option<TaskMessage>> {
+    let task_id = current_id()?;
+
+    interrupt::asm::without_interrupt(|| unsafe { TASK_MANAGER.receive_message_at(task_id) })
+}
+
+
 pub fn sleep(task_id: u64) -> KernelResult {
     unsafe { TASK_MANAGER.sleep_at(task_id) }
 }
@@ -44,6 +135,128 @@ pub fn wakeup(task_id: u64) -> KernelResult {
 }
 
 
+/// Puts the calling task to sleep for at least `duration`.
+///
+/// A one-shot timer wakes the task up, so it doesn't take the CPU meanwhile.
+pub fn sleep_for(duration: Duration) -> KernelResult {
+    let task_id = current_id()?;
+    let deadline = TIME_HANDLE_MANAGER
+        .ticks()
+        .saturating_add(duration_to_ticks(duration));
+
+    // Messages may wake the task up early, so it sleeps again until the deadline.
+    loop {
+        let now = TIME_HANDLE_MANAGER.ticks();
+        if deadline <= now {
+            return Ok(());
+        }
+        if unsafe { TASK_MANAGER.is_kill_requested(task_id) } {
+            return Err(killed_error(task_id));
+        }
+
+        // The timer must not expire before the task sleeps, or nobody would wake it up.
+        interrupt::asm::without_interrupt(|| {
+            let timer_id = TIME_HANDLE_MANAGER.entry_once((deadline - now) as usize, move || {
+                let _ = wakeup(task_id);
+            });
+            let result = sleep(task_id);
+            TIME_HANDLE_MANAGER.remove(timer_id);
+            result
+        })?;
+    }
+}
+
+
+/// Terminates the calling task with the exit code.
+///
+/// Returning from the entry point of a task is the same as calling this with `0`.
+pub fn exit(exit_code: i32) -> ! {
+    let result = unsafe { TASK_MANAGER.exit(exit_code) };
+
+    panic!("Failed to exit the task: {result:?}");
+}
+
+
+/// Asks the task to terminate with [`EXIT_CODE_KILLED`].
+///
+/// The task is not torn down here, since it may hold locks that would never be released.
+/// Applications exit by themselves at the next safe point,
+/// when a system call returns or when they are preempted in ring 3;
+/// see [`app::exit_app_if_kill_requested`].
+/// Kernel tasks should check [`is_kill_requested`] and exit.
+///
+/// The task is woken up if it sleeps,
+/// and [`receive_message`] and [`sleep_for`] fail on the task from then on.
+pub fn kill(task_id: u64) -> KernelResult {
+    unsafe { TASK_MANAGER.request_kill(task_id) }
+}
+
+
+/// Returns whether [`kill`] was called on the calling task.
+pub fn is_kill_requested() -> bool {
+    current_id()
+        .map(|task_id| unsafe { TASK_MANAGER.is_kill_requested(task_id) })
+        .unwrap_or(false)
+}
+
+
+/// Waits until the task exits and returns its exit code.
+///
+/// The exit code can be taken only once.
+pub fn join(task_id: u64) -> KernelResult<i32> {
+    let current_id = current_id()?;
+    if current_id == task_id {
+        return Err(kernel_error!("A task can't join itself"));
+    }
+
+    loop {
+        // Interrupts stay disabled until the task sleeps, so the exit is not missed in between.
+        interrupt::asm::cli();
+
+        match unsafe { TASK_MANAGER.wait_exit(task_id, current_id) } {
+            Ok(Some(exit_code)) => {
+                interrupt::asm::sti();
+                return Ok(exit_code);
+            }
+            Ok(None) => {}
+            Err(e) => {
+                interrupt::asm::sti();
+                return Err(e);
+            }
+        }
+
+        if let Err(e) = unsafe { TASK_MANAGER.sleep_at(current_id) } {
+            interrupt::asm::sti();
+            return Err(e);
+        }
+    }
+}
+
+
+/// Returns whether the task runs an application in its own address space.
+pub fn is_app(task_id: u64) -> bool {
+    unsafe { TASK_MANAGER.is_app(task_id) }
+}
+
+
+/// Returns snapshots of all the tasks ordered by id.
+pub fn tasks() -> Vec<TaskInfo> {
+    unsafe { TASK_MANAGER.tasks() }
+}
+
+
+/// Returns the id of the task calling this function.
+pub fn current_id() -> KernelResult<u64> {
+    unsafe { TASK_MANAGER.running_task_id() }
+}
+
+
+#[inline]
+fn killed_error(task_id: u64) -> KernelError {
+    kernel_error!("Task {task_id} is being killed")
+}
+
+
 pub struct PreemptiveTaskManager {
     task_manager: OnceCell<TaskManager>,
 }
@@ -75,19 +288,76 @@ impl PreemptiveTaskManager {
     }
 
 
-    pub fn receive_message_at(&mut self, task_id: u64) -> Option<TaskMessage> {
+    pub fn receive_message_at(&mut self, task_id: u64) -> KernelResult<Option<TaskMessage>> {
         self.task_manager
-            .get_mut()?
+            .get_mut()
+            .ok_or(kernel_error!("Task manager is not initialized"))?
             .receive_message_at(task_id)
     }
 
 
-    pub fn new_task(&mut self, priority_level: PriorityLevel, rip: u64, rsi: u64) {
-        self.task_manager
-            .get_mut()
-            .unwrap()
-            .new_task(priority_level)
-            .init_context(rip, rsi)
+    /// Creates a task that starts at `rip` and returns its id.
+    ///
+    /// The entry point receives the task id in `rdi` and `rsi` as is.
+    pub fn new_task(&mut self, priority_level: PriorityLevel, rip: u64, rsi: u64) -> u64 {
+        interrupt::asm::without_interrupt(|| {
+            let task = self
+                .task_manager
+                .get_mut()
+                .unwrap()
+                .new_task(priority_level);
+
+            task.init_context(rip, rsi);
+            task.id
+        })
+    }
+
+
+    /// Creates a task like [`PreemptiveTaskManager::new_task`]
+    /// with the name and a stack of `stack_size` bytes.
+    pub fn new_named_task(
+        &mut self,
+        name: String,
+        priority_level: PriorityLevel,
+        stack_size: usize,
+        rip: u64,
+        rsi: u64,
+    ) -> KernelResult<u64> {
+        interrupt::asm::without_interrupt(|| {
+            let task = self
+                .task_manager
+                .get_mut()
+                .ok_or(kernel_error!("Task manager is not initialized"))?
+                .new_task_with_stack(priority_level, stack_size);
+
+            task.name = name;
+            task.init_context(rip, rsi);
+            Ok(task.id)
+        })
+    }
+
+
+    /// Creates a task like [`PreemptiveTaskManager::new_task`] that runs in `address_space`.
+    ///
+    /// The address space is switched with the task and dropped with it.
+    pub fn new_task_in(
+        &mut self,
+        priority_level: PriorityLevel,
+        rip: u64,
+        rsi: u64,
+        address_space: AddressSpace,
+    ) -> u64 {
+        interrupt::asm::without_interrupt(|| {
+            let task = self
+                .task_manager
+                .get_mut()
+                .unwrap()
+                .new_task(priority_level);
+
+            task.set_address_space(address_space);
+            task.init_context(rip, rsi);
+            task.id
+        })
     }
 
 
@@ -100,6 +370,146 @@ impl PreemptiveTaskManager {
     }
 
 
+    /// Exits the running task, which never returns if it succeeds.
+    pub fn exit(&mut self, exit_code: i32) -> KernelResult {
+        interrupt::asm::without_interrupt(|| {
+            let task_manager = self
+                .task_manager
+                .get_mut()
+                .ok_or(kernel_error!("Task manager is not initialized"))?;
+            let task_id = task_manager.running_task_id()?;
+
+            task_manager.kill(task_id, exit_code)
+        })
+    }
+
+
+    pub fn kill(&mut self, task_id: u64, exit_code: i32) -> KernelResult {
+        interrupt::asm::without_interrupt(|| {
+            self.task_manager
+                .get_mut()
+                .ok_or(kernel_error!("Task manager is not initialized"))?
+                .kill(task_id, exit_code)
+        })
+    }
+
+
+    /// See [`TaskManager::abort_running`].
+    pub fn abort_running(&mut self, exit_code: i32) -> KernelResult {
+        interrupt::asm::without_interrupt(|| {
+            self.task_manager
+                .get_mut()
+                .ok_or_else(|| kernel_error!("Task manager is not initialized"))?
+                .abort_running(exit_code)
+        })
+    }
+
+
+    /// See [`kill`].
+    pub fn request_kill(&mut self, task_id: u64) -> KernelResult {
+        interrupt::asm::without_interrupt(|| {
+            self.task_manager
+                .get_mut()
+                .ok_or(kernel_error!("Task manager is not initialized"))?
+                .request_kill(task_id)
+        })
+    }
+
+
+    /// Doesn't allocate, so this can be called from interrupt handlers.
+    pub fn is_kill_requested(&self, task_id: u64) -> bool {
+        interrupt::asm::without_interrupt(|| {
+            self.task_manager
+                .get()
+                .map_or(false, |task_manager| task_manager.is_kill_requested(task_id))
+        })
+    }
+
+
+    pub fn tasks(&mut self) -> Vec<TaskInfo> {
+        interrupt::asm::without_interrupt(|| {
+            self.task_manager
+                .get_mut()
+                .map(|task_manager| task_manager.tasks(uptime_nanos()))
+                .unwrap_or_default()
+        })
+    }
+
+
+    /// Samples the CPU time of each task into its load averages.
+    ///
+    /// Expected to be called at a fixed interval, such as every second.
+    pub fn update_loads(&mut self) {
+        interrupt::asm::without_interrupt(|| {
+            if let Some(task_manager) = self.task_manager.get_mut() {
+                task_manager.update_loads(uptime_nanos());
+            }
+        });
+    }
+
+
+    /// Names the task, shown by [`tasks`].
+    pub fn set_name(&mut self, task_id: u64, name: impl Into<String>) -> KernelResult {
+        let name = name.into();
+        interrupt::asm::without_interrupt(|| {
+            self.task_manager
+                .get_mut()
+                .ok_or(kernel_error!("Task manager is not initialized"))?
+                .set_name(task_id, name)
+        })
+    }
+
+
+    /// Charges a timer tick to the running task
+    /// and switches to the next task once its time slice is used up.
+    ///
+    /// Called on every timer interrupt,
+    /// so a failed switch is only logged and the running task keeps the CPU.
+    pub fn tick(&mut self) {
+        if let Some(task_manager) = self.task_manager.get_mut() {
+            if task_manager.count_tick() {
+                if let Err(e) = task_manager.switch_task() {
+                    serial_println!("Failed to switch tasks on the timer tick: {e:?}");
+                }
+            }
+        }
+    }
+
+
+    /// Sets how long the tasks of the level run before the others of the same level take turns.
+    pub fn set_time_slice(&mut self, priority_level: PriorityLevel, time_slice: Duration) {
+        let ticks = duration_to_ticks(time_slice).max(1);
+        interrupt::asm::without_interrupt(|| {
+            if let Some(task_manager) = self.task_manager.get_mut() {
+                task_manager.set_time_slice(priority_level, ticks);
+            }
+        });
+    }
+
+
+    /// Raises the level of the tasks which have waited to run for `threshold` by one,
+    /// so that tasks of low levels don't starve. `None` disables it, which is the default.
+    pub fn set_aging(&mut self, threshold: Option<Duration>) {
+        let ticks = threshold.map(|threshold| duration_to_ticks(threshold).max(1));
+        interrupt::asm::without_interrupt(|| {
+            if let Some(task_manager) = self.task_manager.get_mut() {
+                task_manager.set_aging(ticks);
+            }
+        });
+    }
+
+
+    /// See [`TaskManager::wait_exit`].
+    pub fn wait_exit(&mut self, task_id: u64, joiner_id: u64) -> KernelResult<Option<i32>> {
+        interrupt::asm::without_interrupt(|| {
+            self.task_manager
+                .get_mut()
+                .ok_or(kernel_error!("Task manager is not initialized"))?
+                .wait_exit(task_id, joiner_id)
+        })
+    }
+
+
     #[inline(always)]
     pub fn wakeup_at(&mut self, task_id: u64) -> KernelResult {
         self.task_manager
@@ -109,6 +519,47 @@ impl PreemptiveTaskManager {
     }
 
 
+    pub fn running_task_id(&mut self) -> KernelResult<u64> {
+        interrupt::asm::without_interrupt(|| {
+            self.task_manager
+                .get_mut()
+                .ok_or_else(|| kernel_error!("Task manager is not initialized"))?
+                .running_task_id()
+        })
+    }
+
+
+    /// Returns the id of the task whose stack guard page contains `addr`.
+    ///
+    /// Called from exception handlers, so this neither allocates nor waits.
+    pub fn stack_guard_owner(&self, addr: u64) -> Option<u64> {
+        self.task_manager
+            .get()?
+            .stack_guard_owner(addr)
+    }
+
+
+    /// Returns whether the task runs an application in its own address space.
+    ///
+    /// Called from exception handlers, so this neither allocates nor waits.
+    pub fn is_app(&self, task_id: u64) -> bool {
+        self.task_manager
+            .get()
+            .is_some_and(|task_manager| task_manager.is_app(task_id))
+    }
+
+
+    /// Maps the page containing `addr` in the address space of the running task on demand.
+    ///
+    /// See [`AddressSpace::map_on_demand`].
+    pub fn map_on_demand(&mut self, addr: u64, write: bool) -> KernelResult {
+        self.task_manager
+            .get_mut()
+            .ok_or(kernel_error!("Task manager is not initialized"))?
+            .map_on_demand(addr, write)
+    }
+
+
     #[inline(always)]
     pub fn switch(&mut self) -> KernelResult {
         interrupt::asm::without_interrupt(|| {
@@ -135,6 +586,11 @@ impl PreemptiveTaskManager {
 #[derive(Default, Debug)]
 pub struct TaskManager {
     tasks: TaskList,
+    next_id: u64,
+    exit_codes: BTreeMap<u64, i32>,
+    loads_sampled_at: u64,
+    time_slices: BTreeMap<PriorityLevel, u64>,
+    aging_threshold: Option<u64>,
 }
 
 
@@ -143,33 +599,50 @@ impl TaskManager {
     pub fn new() -> Self {
         let mut tasks = TaskList::new();
         tasks.push(Task::new_main());
-        Self { tasks }
+        Self {
+            tasks,
+            next_id: 1,
+            exit_codes: BTreeMap::new(),
+            loads_sampled_at: 0,
+            time_slices: BTreeMap::new(),
+            aging_threshold: None,
+        }
     }
 
 
     pub fn send_message_at(&mut self, task_id: u64, message: TaskMessage) -> KernelResult {
         let task = self.tasks.find_mut(task_id)?;
+        task.send_message(message);
 
         if task.status().is_sleep() {
-            task.store_status(Pending);
+            self.tasks.wakeup_at(task_id)?;
         }
 
-        task.send_message(message);
-
         Ok(())
     }
 
 
-    pub fn receive_message_at(&mut self, task_id: u64) -> Option<TaskMessage> {
-        self.tasks
-            .find_mut(task_id)
-            .unwrap()
-            .receive_message()
+    pub fn receive_message_at(&mut self, task_id: u64) -> KernelResult<Option<TaskMessage>> {
+        Ok(self
+            .tasks
+            .find_mut(task_id)?
+            .receive_message())
     }
 
 
     pub fn new_task(&mut self, priority_level: PriorityLevel) -> &mut Task {
-        let task = self.create_task(priority_level);
+        self.new_task_with_stack(priority_level, DEFAULT_STACK_SIZE)
+    }
+
+
+    pub fn new_task_with_stack(
+        &mut self,
+        priority_level: PriorityLevel,
+        stack_size: usize,
+    ) -> &mut Task {
+        self.remove_exited();
+
+        let task = self.create_task(priority_level, stack_size);
         let id = task.id;
         self.tasks.push(task);
 
@@ -189,62 +662,353 @@ impl TaskManager {
     }
 
 
-    pub fn switch_task(&mut self) -> KernelResult {
+    /// Marks the task as exited and wakes up the tasks joining it.
+    ///
+    /// If the task is running, switches to the next task and never returns.
+    pub fn kill(&mut self, task_id: u64, exit_code: i32) -> KernelResult {
+        let task = self.tasks.find_mut(task_id)?;
+        if task.status().is_exited() {
+            return Err(kernel_error!("Task {task_id} has already exited"));
+        }
+
+        let joiners = core::mem::take(&mut task.joiners);
+        let running = task.status().is_running();
+
+        self.store_exit_code(task_id, exit_code, joiners);
+
+        if running {
+            self.tasks.exit_running()
+        } else {
+            self.tasks
+                .find_ref(task_id)?
+                .store_status(Exited);
+            Ok(())
+        }
+    }
+
+
+    /// Exits the running task like [`TaskManager::kill`]
+    /// without allocating or taking locks unless it fails,
+    /// so it can be called on the stack of a fault handler.
+    ///
+    /// The exit code is kept in the task
+    /// until it's handed over to the joiners by [`TaskManager::reap_exit_codes`].
+    pub fn abort_running(&mut self, exit_code: i32) -> KernelResult {
+        self.tasks
+            .running_task_mut()?
+            .exit_code = Some(exit_code);
+
+        self.tasks.exit_running()
+    }
+
+
+    /// Hands the exit codes kept by [`TaskManager::abort_running`] over to the joiners.
+    fn reap_exit_codes(&mut self) {
+        let aborted: Vec<(u64, i32, Vec<u64>)> = self
+            .tasks
+            .iter_mut()
+            .filter(|task| task.status().is_exited())
+            .filter_map(|task| {
+                let exit_code = task.exit_code.take()?;
+                Some((task.id, exit_code, core::mem::take(&mut task.joiners)))
+            })
+            .collect();
+
+        for (task_id, exit_code, joiners) in aborted {
+            self.store_exit_code(task_id, exit_code, joiners);
+        }
+    }
+
+
+    fn store_exit_code(&mut self, task_id: u64, exit_code: i32, joiners: Vec<u64>) {
+        if self.exit_codes.len() == MAX_EXIT_CODES {
+            self.exit_codes.pop_first();
+        }
+        self.exit_codes
+            .insert(task_id, exit_code);
+
+        for joiner in joiners {
+            let _ = self.tasks.wakeup_at(joiner);
+        }
+    }
+
+
+    /// Drops the exited tasks after handing over their exit codes.
+    fn remove_exited(&mut self) {
+        self.reap_exit_codes();
+        self.tasks.remove_exited();
+    }
+
+
+    /// Marks the task to be killed at its next safe point and wakes it up if it sleeps.
+    pub fn request_kill(&mut self, task_id: u64) -> KernelResult {
+        let task = self.tasks.find_ref(task_id)?;
+        if task.status().is_exited() {
+            return Err(kernel_error!("Task {task_id} has already exited"));
+        }
+
+        task.kill_requested
+            .store(true, Ordering::Relaxed);
+        if task.status().is_sleep() {
+            self.tasks.wakeup_at(task_id)?;
+        }
+
+        Ok(())
+    }
+
+
+    pub fn is_kill_requested(&self, task_id: u64) -> bool {
         self.tasks
-            .next_switch_command()?
-            .switch_if_need(Pending);
+            .iter()
+            .any(|task| {
+                task.id == task_id
+                    && task
+                        .kill_requested
+                        .load(Ordering::Relaxed)
+            })
+    }
 
+
+    /// Returns the exit code if the task has exited,
+    /// otherwise registers `joiner_id` to be woken up when it exits.
+    pub fn wait_exit(&mut self, task_id: u64, joiner_id: u64) -> KernelResult<Option<i32>> {
+        self.reap_exit_codes();
+        if let Some(exit_code) = self.exit_codes.remove(&task_id) {
+            return Ok(Some(exit_code));
+        }
+
+        let task = self.tasks.find_mut(task_id)?;
+        if task.status().is_exited() {
+            return Err(kernel_error!("Exit code of task {task_id} is already taken"));
+        }
+        if !task.joiners.contains(&joiner_id) {
+            task.joiners.push(joiner_id);
+        }
+
+        Ok(None)
+    }
+
+
+    pub fn running_task_id(&self) -> KernelResult<u64> {
+        self.tasks.running_task_id()
+    }
+
+
+    /// Returns snapshots of the tasks taken at `now`, in nanoseconds since boot.
+    pub fn tasks(&self, now: u64) -> Vec<TaskInfo> {
+        let mut tasks: Vec<TaskInfo> = self
+            .tasks
+            .iter()
+            .map(|task| TaskInfo::new(task, now))
+            .collect();
+        tasks.sort_by_key(|task| task.id);
+        tasks
+    }
+
+
+    /// Adds the CPU time each task spent since the last call to its load averages.
+    pub fn update_loads(&mut self, now: u64) {
+        let elapsed = now.saturating_sub(self.loads_sampled_at);
+        self.loads_sampled_at = now;
+
+        for task in self.tasks.iter_mut() {
+            let cpu_time = task.cpu_time_at(now);
+            let busy = cpu_time.saturating_sub(task.sampled_cpu_time);
+
+            task.sampled_cpu_time = cpu_time;
+            task.load.update(busy, elapsed);
+        }
+    }
+
+
+    pub fn set_name(&mut self, task_id: u64, name: String) -> KernelResult {
+        self.tasks.find_mut(task_id)?.name = name;
         Ok(())
     }
 
 
-    pub fn switch_ignore_priority(&mut self) -> KernelResult {
+    /// Charges a timer tick to the running task and returns whether its time slice is used up.
+    pub fn count_tick(&mut self) -> bool {
+        self.tasks.tick();
+        if let Some(threshold) = self.aging_threshold {
+            self.tasks.age(threshold);
+        }
+
+        let Ok(task) = self.tasks.running_task_mut() else {
+            return false;
+        };
+        task.cpu_ticks
+            .fetch_add(1, Ordering::Relaxed);
+        let slice_ticks = task
+            .slice_ticks
+            .fetch_add(1, Ordering::Relaxed)
+            + 1;
+        let priority_level = task.priority_level;
+
+        self.time_slice(priority_level) <= slice_ticks
+    }
+
+
+    pub fn set_time_slice(&mut self, priority_level: PriorityLevel, ticks: u64) {
+        self.time_slices
+            .insert(priority_level, ticks);
+    }
+
+
+    pub fn set_aging(&mut self, threshold: Option<u64>) {
+        self.aging_threshold = threshold;
+    }
+
+
+    fn time_slice(&self, priority_level: PriorityLevel) -> u64 {
+        self.time_slices
+            .get(&priority_level)
+            .copied()
+            .unwrap_or(DEFAULT_TIME_SLICE)
+    }
+
+
+    pub fn stack_guard_owner(&self, addr: u64) -> Option<u64> {
+        self.tasks
+            .iter()
+            .find(|task| {
+                task.stack
+                    .guard_page()
+                    .contains(&addr)
+            })
+            .map(|task| task.id)
+    }
+
+
+    pub fn is_app(&self, task_id: u64) -> bool {
         self.tasks
-            .next_switch_command()?
-            .switch_and_pending();
+            .iter()
+            .any(|task| task.id == task_id && task.has_address_space())
+    }
+
+
+    pub fn map_on_demand(&mut self, addr: u64, write: bool) -> KernelResult {
+        self.tasks
+            .running_task_mut()?
+            .address_space_mut()
+            .ok_or(PagingReason::NotMapped(addr))?
+            .map_on_demand(addr, write)
+    }
+
+
+    pub fn switch_task(&mut self) -> KernelResult {
+        self.remove_exited();
+        if let Some(mut command) = self.tasks.preempt_command()? {
+            command.switch_and_pending();
+        }
+
+        Ok(())
+    }
+
+
+    pub fn switch_ignore_priority(&mut self) -> KernelResult {
+        self.remove_exited();
+        if let Some(mut command) = self.tasks.yield_command()? {
+            command.switch_and_pending();
+        }
 
         Ok(())
     }
 
 
     #[inline]
-    fn create_task(&self, priority_level: PriorityLevel) -> Task {
-        Task::new(self.tasks.len() as u64, priority_level)
+    fn create_task(&mut self, priority_level: PriorityLevel, stack_size: usize) -> Task {
+        let id = self.next_id;
+        self.next_id += 1;
+        Task::with_stack_size(id, priority_level, stack_size)
     }
 }
 
 
 pub struct Task {
     id: u64,
+    name: String,
     priority_level: PriorityLevel,
     context: Context,
-    stack: Box<[u8]>,
+    stack: TaskStack,
     messages: VecDeque<TaskMessage>,
     status: AtomicU8,
+    rsp0: AtomicU64,
+    address_space: Option<AddressSpace>,
+    joiners: Vec<u64>,
+    /// Set by [`TaskManager::abort_running`], which can't insert it into the exit codes.
+    exit_code: Option<i32>,
+    /// Set by [`kill`], after which the task exits at its next safe point.
+    kill_requested: AtomicBool,
+    cpu_ticks: AtomicU64,
+    /// The nanoseconds the task ran until it was last switched out.
+    cpu_time: AtomicU64,
+    /// When the task was last switched in, in nanoseconds since boot.
+    switched_in_at: AtomicU64,
+    sampled_cpu_time: u64,
+    load: LoadAverage,
+    /// The timer ticks the task has run since it was last switched in.
+    slice_ticks: AtomicU64,
 }
 
 
 impl Task {
     pub fn new_main() -> Self {
+        let stack = TaskStack::new(DEFAULT_STACK_SIZE);
+        let rsp0 = stack.end();
+
         Self {
             id: 0,
+            name: "main".to_string(),
             priority_level: PriorityLevel::new(3),
             context: Context::uninit(),
-            stack: vec![0; 65_536].into_boxed_slice(),
+            stack,
             messages: VecDeque::new(),
             status: AtomicU8::new(Running as u8),
+            rsp0: AtomicU64::new(rsp0),
+            address_space: None,
+            joiners: Vec::new(),
+            exit_code: None,
+            kill_requested: AtomicBool::new(false),
+            cpu_ticks: AtomicU64::new(0),
+            cpu_time: AtomicU64::new(0),
+            switched_in_at: AtomicU64::new(0),
+            sampled_cpu_time: 0,
+            load: LoadAverage::new(),
+            slice_ticks: AtomicU64::new(0),
         }
     }
 
 
+    #[inline]
     pub fn new(id: u64, priority_level: PriorityLevel) -> Self {
+        Self::with_stack_size(id, priority_level, DEFAULT_STACK_SIZE)
+    }
+
+
+    pub fn with_stack_size(id: u64, priority_level: PriorityLevel, stack_size: usize) -> Self {
+        let stack = TaskStack::new(stack_size);
+        let rsp0 = stack.end();
+
         Self {
             id,
+            name: format!("task{id}"),
             priority_level,
             context: Context::uninit(),
-            stack: vec![0; 65_536].into_boxed_slice(),
+            stack,
             messages: VecDeque::new(),
             status: AtomicU8::new(Pending as u8),
+            rsp0: AtomicU64::new(rsp0),
+            address_space: None,
+            joiners: Vec::new(),
+            exit_code: None,
+            kill_requested: AtomicBool::new(false),
+            cpu_ticks: AtomicU64::new(0),
+            cpu_time: AtomicU64::new(0),
+            switched_in_at: AtomicU64::new(0),
+            sampled_cpu_time: 0,
+            load: LoadAverage::new(),
+            slice_ticks: AtomicU64::new(0),
         }
     }
 
@@ -267,6 +1031,7 @@ impl Task {
             0 => Sleep,
             1 => Pending,
             2 => Running,
+            3 => Exited,
             _ => panic!("Invalid Status"),
         }
     }
@@ -278,11 +1043,105 @@ impl Task {
     }
 
 
+    #[inline(always)]
+    pub fn cpu_ticks(&self) -> u64 {
+        self.cpu_ticks
+            .load(Ordering::Relaxed)
+    }
+
+
+    /// Returns the nanoseconds the task has run until `now`, including the current run.
+    pub fn cpu_time_at(&self, now: u64) -> u64 {
+        let cpu_time = self
+            .cpu_time
+            .load(Ordering::Relaxed);
+
+        if self.status().is_running() {
+            let switched_in_at = self
+                .switched_in_at
+                .load(Ordering::Relaxed);
+            cpu_time + now.saturating_sub(switched_in_at)
+        } else {
+            cpu_time
+        }
+    }
+
+
+    /// Starts measuring the run time, called when the task is switched in at `now`.
+    #[inline(always)]
+    pub fn switch_in(&self, now: u64) {
+        self.switched_in_at
+            .store(now, Ordering::Relaxed);
+        self.restart_time_slice();
+    }
+
+
+    #[inline(always)]
+    pub fn restart_time_slice(&self) {
+        self.slice_ticks
+            .store(0, Ordering::Relaxed);
+    }
+
+
+    /// Adds the run time since [`Task::switch_in`], called when the task is switched out.
+    #[inline(always)]
+    pub fn switch_out(&self, now: u64) {
+        let switched_in_at = self
+            .switched_in_at
+            .load(Ordering::Relaxed);
+
+        self.cpu_time
+            .fetch_add(now.saturating_sub(switched_in_at), Ordering::Relaxed);
+    }
+
+
+    /// Returns the stack pointer used when an interrupt occurs while this task runs in ring 3.
+    #[inline(always)]
+    pub fn rsp0(&self) -> u64 {
+        self.rsp0
+            .load(Ordering::Relaxed)
+    }
+
+
+    #[inline(always)]
+    pub fn store_rsp0(&self, rsp0: u64) {
+        self.rsp0
+            .store(rsp0, Ordering::Relaxed);
+    }
+
+
     pub fn init_context(&mut self, rip: u64, rsi: u64) {
-        let task_end = self.stack.as_ptr_range().end as u64;
+        let task_end = self.stack.end();
         let rsp = (task_end & !0xF) - 8;
+        // The entry point returns to the trampoline, which exits the task.
+        unsafe { (rsp as *mut u64).write(asm_task_return as *const () as u64) };
         self.context
             .init_context(rip, self.id, rsi, rsp);
+
+        if let Some(address_space) = self.address_space.as_ref() {
+            self.context
+                .set_cr3(address_space.cr3());
+        }
+    }
+
+
+    /// Sets the address space loaded into CR3 while this task runs.
+    ///
+    /// Must be called before the task starts.
+    pub fn set_address_space(&mut self, address_space: AddressSpace) {
+        self.address_space = Some(address_space);
+    }
+
+
+    #[inline(always)]
+    pub fn has_address_space(&self) -> bool {
+        self.address_space.is_some()
+    }
+
+
+    #[inline(always)]
+    pub fn address_space_mut(&mut self) -> Option<&mut AddressSpace> {
+        self.address_space.as_mut()
     }
 
 
@@ -292,20 +1151,318 @@ impl Task {
 
 
     pub fn send_message(&mut self, message: TaskMessage) {
+        if MAX_PENDING_MESSAGES <= self.messages.len() {
+            self.messages.pop_front();
+        }
+
         self.messages
             .push_back(message);
     }
 }
 
 
+/// Calls [`exit`] with `0` when the entry point of a task returns.
+///
+/// The stack is realigned since `ret` leaves it as if this had been called.
+#[naked]
+extern "sysv64" fn asm_task_return() -> ! {
+    unsafe {
+        asm!(
+        "
+        and rsp, -16
+        call {exit_on_return}
+        ud2
+        ",
+        exit_on_return = sym exit_on_return,
+        options(noreturn)
+        )
+    }
+}
+
+
+extern "sysv64" fn exit_on_return() -> ! {
+    exit(0)
+}
+
+
 impl Debug for Task {
     fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         f.debug_struct("Task")
             .field("id", &self.id)
+            .field("name", &self.name)
             .field("priority_level", &self.priority_level)
             .finish()
     }
 }
 
 #[cfg(test)]
-mod tests {}
+mod tests {
+    use alloc::string::ToString;
+    use core::time::Duration;
+
+    use crate::task::message::TaskMessage;
+    use crate::task::priority_level::PriorityLevel;
+    use crate::task::status::Status::{Exited, Pending, Running, Sleep};
+    use crate::task::{TaskManager, MAX_PENDING_MESSAGES};
+
+    #[test]
+    fn it_send_message_wakeup_sleeping_task() {
+        let mut manager = TaskManager::new();
+        let id = manager
+            .new_task(PriorityLevel::new(1))
+            .id;
+        manager
+            .tasks
+            .find_ref(id)
+            .unwrap()
+            .store_status(Sleep);
+
+        manager
+            .send_message_at(id, TaskMessage::custom(7_u8))
+            .unwrap();
+
+        assert_eq!(
+            manager
+                .tasks
+                .find_ref(id)
+                .unwrap()
+                .status(),
+            Pending
+        );
+        assert_eq!(
+            manager
+                .receive_message_at(id)
+                .unwrap()
+                .and_then(TaskMessage::downcast::<u8>),
+            Some(7)
+        );
+        assert!(manager
+            .receive_message_at(id)
+            .unwrap()
+            .is_none());
+        assert!(manager
+            .receive_message_at(id + 1)
+            .is_err());
+    }
+
+
+    #[test]
+    fn it_drop_oldest_message_when_queue_is_full() {
+        let mut manager = TaskManager::new();
+        let id = manager
+            .new_task(PriorityLevel::new(1))
+            .id;
+
+        for i in 0..=MAX_PENDING_MESSAGES {
+            manager
+                .send_message_at(id, TaskMessage::custom(i))
+                .unwrap();
+        }
+
+        assert_eq!(
+            manager
+                .receive_message_at(id)
+                .unwrap()
+                .and_then(TaskMessage::downcast::<usize>),
+            Some(1)
+        );
+    }
+
+
+    #[test]
+    fn it_kill_and_wait_exit() {
+        let mut manager = TaskManager::new();
+        let id = manager
+            .new_task(PriorityLevel::new(1))
+            .id;
+        let joiner = manager
+            .new_task(PriorityLevel::new(1))
+            .id;
+
+        assert_eq!(manager.wait_exit(id, joiner).unwrap(), None);
+        manager
+            .tasks
+            .find_ref(joiner)
+            .unwrap()
+            .store_status(Sleep);
+
+        manager.kill(id, 3).unwrap();
+
+        assert_eq!(
+            manager
+                .tasks
+                .find_ref(joiner)
+                .unwrap()
+                .status(),
+            Pending
+        );
+        assert!(manager.kill(id, 4).is_err());
+        assert_eq!(manager.wait_exit(id, joiner).unwrap(), Some(3));
+        assert!(manager
+            .wait_exit(id, joiner)
+            .is_err());
+    }
+
+
+    #[test]
+    fn it_request_kill_wakes_up_without_exiting() {
+        let mut manager = TaskManager::new();
+        let id = manager
+            .new_task(PriorityLevel::new(1))
+            .id;
+        manager
+            .tasks
+            .find_ref(id)
+            .unwrap()
+            .store_status(Sleep);
+        assert!(!manager.is_kill_requested(id));
+
+        manager.request_kill(id).unwrap();
+
+        assert!(manager.is_kill_requested(id));
+        assert_eq!(
+            manager
+                .tasks
+                .find_ref(id)
+                .unwrap()
+                .status(),
+            Pending
+        );
+
+        manager.kill(id, 0).unwrap();
+        assert!(manager.request_kill(id).is_err());
+    }
+
+
+    #[test]
+    fn it_hand_over_exit_code_kept_in_aborted_task() {
+        let mut manager = TaskManager::new();
+        let id = manager
+            .new_task(PriorityLevel::new(1))
+            .id;
+        let joiner = manager
+            .new_task(PriorityLevel::new(1))
+            .id;
+
+        assert_eq!(manager.wait_exit(id, joiner).unwrap(), None);
+        manager
+            .tasks
+            .find_ref(joiner)
+            .unwrap()
+            .store_status(Sleep);
+
+        // What `abort_running` leaves behind after switching away from the task.
+        let task = manager.tasks.find_mut(id).unwrap();
+        task.exit_code = Some(11);
+        task.store_status(Exited);
+        assert!(manager.kill(id, 0).is_err());
+
+        manager.remove_exited();
+
+        assert_eq!(
+            manager
+                .tasks
+                .find_ref(joiner)
+                .unwrap()
+                .status(),
+            Pending
+        );
+        assert!(manager.tasks.find_ref(id).is_err());
+        assert_eq!(manager.wait_exit(id, joiner).unwrap(), Some(11));
+    }
+
+
+    #[test]
+    fn it_tasks_snapshot() {
+        let mut manager = TaskManager::new();
+        let id = manager
+            .new_task(PriorityLevel::new(1))
+            .id;
+        manager
+            .set_name(id, "count".to_string())
+            .unwrap();
+        manager
+            .send_message_at(id, TaskMessage::Xhci)
+            .unwrap();
+        manager.count_tick();
+
+        let tasks = manager.tasks(0);
+        assert_eq!(tasks.len(), 2);
+        assert_eq!(tasks[0].name, "main");
+        assert_eq!(tasks[0].status, Running);
+        assert_eq!(tasks[0].cpu_ticks, 1);
+        assert_eq!(tasks[1].id, id);
+        assert_eq!(tasks[1].name, "count");
+        assert_eq!(tasks[1].pending_messages, 1);
+    }
+
+
+    #[test]
+    fn it_account_cpu_time_and_loads() {
+        let mut manager = TaskManager::new();
+        let id = manager
+            .new_task(PriorityLevel::new(1))
+            .id;
+
+        let main = manager.tasks.find_ref(0).unwrap();
+        main.switch_out(300);
+        main.store_status(Pending);
+        let task = manager.tasks.find_ref(id).unwrap();
+        task.store_status(Running);
+        task.switch_in(300);
+
+        manager.update_loads(1_000);
+
+        let tasks = manager.tasks(1_500);
+        assert_eq!(tasks[0].cpu_time, Duration::from_nanos(300));
+        assert_eq!(tasks[1].cpu_time, Duration::from_nanos(1_200));
+        assert!(tasks[0].loads[0] < tasks[1].loads[0]);
+        assert_eq!(tasks[0].loads[0], 190);
+        assert_eq!(tasks[1].loads[0], 443);
+    }
+
+
+    #[test]
+    fn it_use_up_time_slice() {
+        let mut manager = TaskManager::new();
+        manager.set_time_slice(PriorityLevel::new(3), 3);
+
+        assert!(!manager.count_tick());
+        assert!(!manager.count_tick());
+        assert!(manager.count_tick());
+
+        manager
+            .tasks
+            .find_ref(0)
+            .unwrap()
+            .restart_time_slice();
+        assert!(!manager.count_tick());
+    }
+
+
+    #[test]
+    fn it_new_task_with_stack() {
+        let mut manager = TaskManager::new();
+        let task = manager.new_task_with_stack(PriorityLevel::new(1), 8192);
+
+        assert_eq!(task.stack.size(), 8192);
+        assert_eq!(task.name, "task1");
+    }
+
+
+    #[test]
+    fn it_not_reuse_id_of_exited_task() {
+        let mut manager = TaskManager::new();
+        let id = manager
+            .new_task(PriorityLevel::new(1))
+            .id;
+        manager.kill(id, 0).unwrap();
+
+        let next_id = manager
+            .new_task(PriorityLevel::new(1))
+            .id;
+
+        assert_ne!(id, next_id);
+        assert!(manager.tasks.find_ref(id).is_err());
+    }
+}
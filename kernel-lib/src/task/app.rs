@@ -0,0 +1,173 @@
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+
+use crate::context::arch::x86_64::{asm_enter_user_mode, asm_exit_user_mode};
+use crate::error::KernelResult;
+use crate::interrupt::asm::sti;
+use crate::paging::address_space::AddressSpace;
+use crate::paging::region::{Region, RegionKind};
+use crate::paging::PAGE_SIZE_2M;
+use crate::segmentation::tss;
+use crate::task::priority_level::PriorityLevel;
+use crate::task;
+use crate::task::TASK_MANAGER;
+
+/// The stack grows down from the end of the user half as it is touched.
+const USER_STACK_END: u64 = 0xFFFF_FFFF_FFFF_F000;
+const USER_STACK_MAX_SIZE: u64 = 4 * PAGE_SIZE_2M as u64;
+
+/// The exit code of applications killed because of an invalid memory access.
+pub const EXIT_CODE_SEGMENTATION_FAULT: i32 = -11;
+
+/// The exit code of applications killed because of an undefined instruction.
+pub const EXIT_CODE_ILLEGAL_INSTRUCTION: i32 = -4;
+
+/// The exit code of applications killed because of an arithmetic error such as division by zero.
+pub const EXIT_CODE_FLOATING_POINT_ERROR: i32 = -8;
+
+
+/// Spawns a task that calls the application's entry point in ring 3.
+///
+/// The entry point is called like `int main(int argc, char** argv)`
+/// with the arguments copied onto a stack reserved at the end of `address_space`.
+/// The application must already be loaded into `address_space`,
+/// which becomes the address space of the task.
+///
+/// `on_exit` is called on the application's task with its task id and exit code
+/// after the application calls [`exit_app`], then the task exits with the same code.
+///
+/// Returns the id of the spawned task.
+pub fn spawn_app(
+    entry_point: u64,
+    args: &[&str],
+    mut address_space: AddressSpace,
+    on_exit: impl FnOnce(u64, i32) + 'static,
+) -> KernelResult<u64> {
+    address_space.add_region(Region::new(
+        USER_STACK_END - USER_STACK_MAX_SIZE,
+        USER_STACK_END,
+        true,
+        RegionKind::Stack,
+    ))?;
+
+    let app = Box::new(App {
+        entry_point,
+        args: args
+            .iter()
+            .map(|arg| null_terminated(arg))
+            .collect(),
+        on_exit: Box::new(on_exit),
+    });
+
+    let task_id = unsafe {
+        TASK_MANAGER.new_task_in(
+            PriorityLevel::new(1),
+            app_task_entry as *const () as u64,
+            Box::into_raw(app) as u64,
+            address_space,
+        )
+    };
+    unsafe { TASK_MANAGER.set_name(task_id, args.first().copied().unwrap_or("app"))? };
+
+    Ok(task_id)
+}
+
+
+/// Leaves ring 3 and resumes the task spawned by [`spawn_app`] with the exit code.
+///
+/// # Safety
+///
+/// Must be called in ring 0 on the task of an application running in ring 3,
+/// such as from a system call handler.
+pub unsafe fn exit_app(exit_code: i32) -> ! {
+    asm_exit_user_mode(tss::rsp0(), exit_code)
+}
+
+
+/// Exits the running application with [`task::EXIT_CODE_KILLED`]
+/// if [`task::kill`] was called on it.
+///
+/// # Safety
+///
+/// Same as [`exit_app`], and the task must hold no locks,
+/// such as right before a system call returns or when preempted in ring 3.
+pub unsafe fn exit_app_if_kill_requested() {
+    let killed = task::current_id()
+        .map(|task_id| TASK_MANAGER.is_kill_requested(task_id))
+        .unwrap_or(false);
+
+    if killed {
+        exit_app(task::EXIT_CODE_KILLED);
+    }
+}
+
+
+struct App {
+    entry_point: u64,
+    args: Vec<Vec<u8>>,
+    on_exit: Box<dyn FnOnce(u64, i32)>,
+}
+
+
+extern "sysv64" fn app_task_entry(task_id: u64, app: u64) {
+    let app = unsafe { Box::from_raw(app as *mut App) };
+
+    let (argv, rsp) = push_args(USER_STACK_END, &app.args);
+    let exit_code = asm_enter_user_mode(
+        app.args.len() as u64,
+        argv,
+        app.entry_point,
+        rsp,
+        tss::rsp0_ptr(),
+    );
+
+    // Interrupts are still disabled if the application exited in an exception or timer handler.
+    sti();
+
+    let App { on_exit, .. } = *app;
+    on_exit(task_id, exit_code);
+
+    task::exit(exit_code)
+}
+
+
+/// Copies the arguments below `stack_end` in the address space of the running task.
+///
+/// Returns the address of `argv` and the stack pointer
+/// aligned as if the entry point had just been called.
+fn push_args(stack_end: u64, args: &[Vec<u8>]) -> (u64, u64) {
+    let mut sp = stack_end;
+
+    let mut argv: Vec<u64> = Vec::with_capacity(args.len() + 1);
+    for arg in args {
+        sp -= arg.len() as u64;
+        unsafe {
+            (sp as *mut u8).copy_from_nonoverlapping(arg.as_ptr(), arg.len());
+        }
+        argv.push(sp);
+    }
+    argv.push(0);
+
+    sp &= !0x7;
+    sp -= (argv.len() * 8) as u64;
+    let argv_addr = sp;
+    unsafe {
+        (sp as *mut u64).copy_from_nonoverlapping(argv.as_ptr(), argv.len());
+    }
+
+    // There is no caller to return to, so the return address is null.
+    sp = (sp & !0xF) - 8;
+    unsafe {
+        (sp as *mut u64).write(0);
+    }
+
+    (argv_addr, sp)
+}
+
+
+fn null_terminated(arg: &str) -> Vec<u8> {
+    let mut buff = Vec::with_capacity(arg.len() + 1);
+    buff.extend_from_slice(arg.as_bytes());
+    buff.push(0);
+    buff
+}
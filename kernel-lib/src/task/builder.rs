@@ -0,0 +1,82 @@
+use alloc::boxed::Box;
+use alloc::string::String;
+
+use crate::error::KernelResult;
+use crate::task::handle::TaskHandle;
+use crate::task::priority_level::PriorityLevel;
+use crate::task::{DEFAULT_STACK_SIZE, TASK_MANAGER};
+
+/// Smaller stacks are rounded up to this size.
+const MIN_STACK_SIZE: usize = 4096;
+
+
+/// Configures a kernel task before spawning it.
+///
+/// [`spawn`](super::spawn) covers the tasks which don't need a specific stack size.
+#[derive(Debug, Clone)]
+pub struct Builder {
+    name: String,
+    priority_level: PriorityLevel,
+    stack_size: usize,
+}
+
+
+impl Builder {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            priority_level: PriorityLevel::new(1),
+            stack_size: DEFAULT_STACK_SIZE,
+        }
+    }
+
+
+    pub fn priority_level(mut self, priority_level: PriorityLevel) -> Self {
+        self.priority_level = priority_level;
+        self
+    }
+
+
+    /// Sets the stack size in bytes.
+    pub fn stack_size(mut self, stack_size: usize) -> Self {
+        self.stack_size = stack_size.max(MIN_STACK_SIZE);
+        self
+    }
+
+
+    /// Spawns a task that calls `f` and exits with `0` when it returns.
+    ///
+    /// If the task is killed before it starts, the closure is leaked without being called.
+    pub fn spawn<F>(self, f: F) -> KernelResult<TaskHandle>
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        let f = Box::into_raw(Box::new(f));
+        let spawned = unsafe {
+            TASK_MANAGER.new_named_task(
+                self.name,
+                self.priority_level,
+                self.stack_size,
+                spawned_task_entry::<F> as *const () as u64,
+                f as u64,
+            )
+        };
+
+        match spawned {
+            Ok(task_id) => Ok(TaskHandle::new(task_id)),
+            Err(e) => {
+                drop(unsafe { Box::from_raw(f) });
+                Err(e)
+            }
+        }
+    }
+}
+
+
+extern "sysv64" fn spawned_task_entry<F>(_task_id: u64, f: u64)
+where
+    F: FnOnce() + Send + 'static,
+{
+    let f = unsafe { Box::from_raw(f as *mut F) };
+    f();
+}
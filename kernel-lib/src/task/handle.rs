@@ -0,0 +1,51 @@
+use crate::error::KernelResult;
+use crate::task;
+use crate::task::message::TaskMessage;
+
+/// A handle to a task, returned by [`spawn`](super::spawn).
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct TaskHandle {
+    id: u64,
+}
+
+
+impl TaskHandle {
+    #[inline(always)]
+    pub const fn new(id: u64) -> Self {
+        Self { id }
+    }
+
+
+    #[inline(always)]
+    pub const fn id(&self) -> u64 {
+        self.id
+    }
+
+
+    /// Sends the message to the task, waking it up if it sleeps.
+    pub fn send(&self, message: TaskMessage) -> KernelResult {
+        task::send_message(self.id, message)
+    }
+
+
+    pub fn sleep(&self) -> KernelResult {
+        task::sleep(self.id)
+    }
+
+
+    pub fn wakeup(&self) -> KernelResult {
+        task::wakeup(self.id)
+    }
+
+
+    /// See [`task::kill`].
+    pub fn kill(&self) -> KernelResult {
+        task::kill(self.id)
+    }
+
+
+    /// Waits until the task exits and returns its exit code.
+    pub fn join(self) -> KernelResult<i32> {
+        task::join(self.id)
+    }
+}
@@ -0,0 +1,40 @@
+use alloc::string::String;
+use core::time::Duration;
+
+use crate::task::priority_level::PriorityLevel;
+use crate::task::status::Status;
+use crate::task::Task;
+
+/// A snapshot of a task, see [`PreemptiveTaskManager::tasks`](super::PreemptiveTaskManager::tasks).
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct TaskInfo {
+    pub id: u64,
+    pub name: String,
+    pub priority_level: PriorityLevel,
+    pub status: Status,
+    /// The timer ticks during which the task was running.
+    pub cpu_ticks: u64,
+    /// The time the task has run in total.
+    pub cpu_time: Duration,
+    /// The CPU usage averaged over about the last 1, 5 and 15 load samples, in permille.
+    pub loads: [u32; 3],
+    /// The messages sent to the task but not received yet.
+    pub pending_messages: usize,
+}
+
+
+impl TaskInfo {
+    /// Takes the snapshot of the task at `now`, in nanoseconds since boot.
+    pub fn new(task: &Task, now: u64) -> Self {
+        Self {
+            id: task.id,
+            name: task.name.clone(),
+            priority_level: task.priority_level,
+            status: task.status(),
+            cpu_ticks: task.cpu_ticks(),
+            cpu_time: Duration::from_nanos(task.cpu_time_at(now)),
+            loads: task.load.permille(),
+            pending_messages: task.messages.len(),
+        }
+    }
+}
@@ -2,6 +2,8 @@ use alloc::vec::Vec;
 
 use crate::error::{KernelError, KernelResult};
 use crate::kernel_error;
+use crate::task::priority_level::PriorityLevel;
+use crate::task::run_queue::RunQueue;
 use crate::task::status::Status;
 use crate::task::status::Status::Pending;
 use crate::task::switch::SwitchCommand;
@@ -10,12 +12,19 @@ use crate::task::Task;
 #[derive(Default, Debug)]
 pub struct TaskList {
     tasks: Vec<Task>,
+    run_queue: RunQueue,
+    /// The timer ticks counted by [`TaskList::tick`], used to age the run queue.
+    ticks: u64,
 }
 
 
 impl TaskList {
     pub fn new() -> Self {
-        Self { tasks: Vec::new() }
+        Self {
+            tasks: Vec::new(),
+            run_queue: RunQueue::new(),
+            ticks: 0,
+        }
     }
 
 
@@ -26,26 +35,134 @@ impl TaskList {
 
 
     #[inline]
+    pub fn iter(&self) -> impl Iterator<Item = &Task> {
+        self.tasks.iter()
+    }
+
+
+    #[inline]
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Task> {
+        self.tasks.iter_mut()
+    }
+
+
     pub fn push(&mut self, task: Task) {
+        if task.status().is_pending() {
+            self.run_queue
+                .push_back(task.priority_level, task.id, self.ticks);
+        }
         self.tasks.push(task);
     }
 
 
+    #[inline]
+    pub fn tick(&mut self) {
+        self.ticks += 1;
+    }
+
+
+    /// Raises the tasks waiting in the run queue for `threshold` ticks or longer by a level,
+    /// up to the highest level of all the tasks.
+    pub fn age(&mut self, threshold: u64) {
+        let max_level = self
+            .tasks
+            .iter()
+            .map(|task| task.priority_level)
+            .max()
+            .unwrap_or_default();
+
+        self.run_queue
+            .age(self.ticks, threshold, max_level);
+    }
+
+
+    /// Drops the tasks which have exited, freeing their stacks and address spaces.
+    ///
+    /// An exited task never runs again, so its stack is no longer in use.
+    pub fn remove_exited(&mut self) {
+        for task in self
+            .tasks
+            .iter()
+            .filter(|task| task.status().is_exited())
+        {
+            self.run_queue.remove(task.id);
+        }
+
+        self.tasks
+            .retain(|task| !task.status().is_exited());
+    }
+
+
+    /// Marks the running task as exited and switches to the next task.
+    ///
+    /// Doesn't allocate unless it fails, so the task can exit on the stack of a fault handler.
+    pub fn exit_running(&mut self) -> KernelResult {
+        let running_id = self.running_task_id()?;
+        let next_id = self.pop_next()?;
+
+        SwitchCommand::new(self.find_ref(running_id)?, self.find_ref(next_id)?).switch_and_exit();
+
+        Ok(())
+    }
+
+
     pub fn wakeup_at(&mut self, task_id: u64) -> KernelResult {
         let task = self.find_where_sleeps(task_id)?;
         task.store_status(Pending);
+        let priority_level = task.priority_level;
+
+        self.run_queue
+            .push_back(priority_level, task_id, self.ticks);
 
         Ok(())
     }
 
 
-    pub fn next_switch_command(&mut self) -> KernelResult<SwitchCommand> {
-        self.sort_by_priority();
+    /// Creates the command switching to the next task when the time slice of the running task
+    /// is used up.
+    ///
+    /// The running task keeps the CPU with a new time slice
+    /// if every queued task has a lower level than it.
+    /// Otherwise it's queued behind the tasks of the same level.
+    pub fn preempt_command(&mut self) -> KernelResult<Option<SwitchCommand>> {
+        let running_id = self.running_task_id()?;
+        let running_level = self
+            .find_ref(running_id)?
+            .priority_level;
+
+        match self.front_next() {
+            Some((level, _)) if running_level <= level => {}
+            _ => {
+                self.find_ref(running_id)?
+                    .restart_time_slice();
+                return Ok(None);
+            }
+        }
+
+        self.yield_command()
+    }
 
-        Ok(SwitchCommand::new(
-            self.running_task_ref()?,
-            self.next_run_task_ref()?,
-        ))
+
+    /// Creates the command switching to the next task whatever its level is,
+    /// queueing the running task behind the tasks of the same level.
+    ///
+    /// Returns `None` if no other task is ready to run.
+    pub fn yield_command(&mut self) -> KernelResult<Option<SwitchCommand>> {
+        let running_id = self.running_task_id()?;
+        let Ok(next_id) = self.pop_next() else {
+            return Ok(None);
+        };
+
+        let running_level = self
+            .find_ref(running_id)?
+            .priority_level;
+        self.run_queue
+            .push_back(running_level, running_id, self.ticks);
+
+        Ok(Some(SwitchCommand::new(
+            self.find_ref(running_id)?,
+            self.find_ref(next_id)?,
+        )))
     }
 
 
@@ -62,15 +179,18 @@ impl TaskList {
         &mut self,
         task_id: u64,
     ) -> KernelResult<Option<SwitchCommand>> {
-        self.sort_by_priority();
-
         let task = self.sleep_and_check_running(task_id)?;
 
         if task.status().is_running() {
-            let next = self.next_run_task_ref()?;
+            let next_id = self.pop_next()?;
 
-            Ok(Some(SwitchCommand::new(task, next)))
+            Ok(Some(SwitchCommand::new(
+                self.find_ref(task_id)?,
+                self.find_ref(next_id)?,
+            )))
         } else {
+            self.run_queue.remove(task_id);
+
             Ok(None)
         }
     }
@@ -79,7 +199,9 @@ impl TaskList {
     fn sleep_and_check_running(&self, task_id: u64) -> KernelResult<&Task> {
         let task = self.find_ref(task_id)?;
 
-        if task.status().is_running() {
+        if task.status().is_exited() {
+            Err(error_not_found_task(task_id))
+        } else if task.status().is_running() {
             Ok(task)
         } else {
             task.store_status(Status::Sleep);
@@ -93,7 +215,7 @@ impl TaskList {
         self.tasks
             .iter()
             .find(|task| task.id == task_id)
-            .ok_or(error_not_found_task(task_id))
+            .ok_or_else(|| error_not_found_task(task_id))
     }
 
 
@@ -101,7 +223,21 @@ impl TaskList {
         self.tasks
             .iter_mut()
             .find(|task| task.id == task_id)
-            .ok_or(error_not_found_task(task_id))
+            .ok_or_else(|| error_not_found_task(task_id))
+    }
+
+
+    pub fn running_task_id(&self) -> KernelResult<u64> {
+        self.running_task_ref()
+            .map(|task| task.id)
+    }
+
+
+    pub(crate) fn running_task_mut(&mut self) -> KernelResult<&mut Task> {
+        self.tasks
+            .iter_mut()
+            .find(|task| task.status().is_running())
+            .ok_or_else(|| kernel_error!("No Task Running"))
     }
 
 
@@ -109,7 +245,7 @@ impl TaskList {
         self.tasks
             .iter()
             .find(|task| task.status().is_running())
-            .ok_or(kernel_error!("No Task Running"))
+            .ok_or_else(|| kernel_error!("No Task Running"))
     }
 
 
@@ -124,20 +260,33 @@ impl TaskList {
     }
 
 
-    fn sort_by_priority(&mut self) {
-        self.tasks.sort_by(|t1, t2| {
-            t2.priority_level
-                .cmp(&t1.priority_level)
-                .then_with(|| t1.status().cmp(&t2.status()))
-        });
+    /// Returns the level and the id of the task to run next without dequeuing it.
+    fn front_next(&mut self) -> Option<(PriorityLevel, u64)> {
+        loop {
+            let (level, task_id) = self.run_queue.front()?;
+            if self.is_pending(task_id) {
+                return Some((level, task_id));
+            }
+
+            self.run_queue.pop_front();
+        }
     }
 
 
-    fn next_run_task_ref(&self) -> KernelResult<&Task> {
-        self.tasks
-            .iter()
-            .find(|task| task.status().is_pending())
-            .ok_or(kernel_error!("Couldn't find a task to run next"))
+    fn pop_next(&mut self) -> KernelResult<u64> {
+        while let Some((_, task_id)) = self.run_queue.pop_front() {
+            // Tasks killed while queued are left in the queue and skipped here.
+            if self.is_pending(task_id) {
+                return Ok(task_id);
+            }
+        }
+
+        Err(kernel_error!("Couldn't find a task to run next"))
+    }
+
+
+    fn is_pending(&self, task_id: u64) -> bool {
+        matches!(self.find_ref(task_id), Ok(task) if task.status().is_pending())
     }
 }
 
@@ -150,6 +299,8 @@ fn error_not_found_task(task_id: u64) -> KernelError {
 
 #[cfg(test)]
 mod tests {
+    use alloc::vec::Vec;
+
     use crate::task::list::TaskList;
     use crate::task::priority_level::PriorityLevel;
     use crate::task::status::Status;
@@ -162,12 +313,12 @@ mod tests {
         q.push(Task::new(0, PriorityLevel::new(3)));
         q.push(Task::new(1, PriorityLevel::new(1)));
 
-        let priority_level = q
-            .next_run_task_ref()
-            .map(|task| task.priority_level)
+        let (priority_level, task_id) = q
+            .front_next()
             .unwrap();
 
         assert_eq!(priority_level, PriorityLevel::new(3));
+        assert_eq!(task_id, 0);
     }
 
 
@@ -180,13 +331,32 @@ mod tests {
         q.tasks[1].store_status(Running);
 
         let command = q
-            .next_switch_command()
+            .preempt_command()
+            .unwrap()
             .unwrap();
         assert_eq!(command.running_id(), 1);
         assert_eq!(command.next_id(), 0);
     }
 
 
+    #[test]
+    fn it_not_preempt_by_lower_level() {
+        let mut q = TaskList::new();
+        q.push(Task::new(0, PriorityLevel::new(3)));
+        q.push(Task::new(1, PriorityLevel::new(1)));
+        q.tasks[0].store_status(Running);
+
+        assert!(q
+            .preempt_command()
+            .unwrap()
+            .is_none());
+        assert!(q
+            .yield_command()
+            .unwrap()
+            .is_some());
+    }
+
+
     #[test]
     fn it_wakeup() {
         let mut q = TaskList::new();
@@ -249,36 +419,82 @@ mod tests {
 
 
     #[test]
-    fn it_should_be_stable_sort() {
+    fn it_remove_exited() {
+        let mut q = TaskList::new();
+        q.push(Task::new(0, PriorityLevel::new(1)));
+        q.push(Task::new(1, PriorityLevel::new(1)));
+        q.push(Task::new(2, PriorityLevel::new(1)));
+        q.tasks[1].store_status(Status::Exited);
+
+        q.remove_exited();
+
+        assert_eq!(q.len(), 2);
+        assert!(q.find_ref(1).is_err());
+        assert!(q.find_ref(2).is_ok());
+    }
+
+
+    #[test]
+    fn it_not_sleep_exited() {
+        let mut q = TaskList::new();
+        q.push(Task::new(0, PriorityLevel::new(1)));
+        q.tasks[0].store_status(Status::Exited);
+
+        assert!(q.sleep_at(0).is_err());
+        assert_eq!(q.tasks[0].status(), Status::Exited);
+    }
+
+
+    #[test]
+    fn it_round_robin_within_level() {
         let mut q = TaskList::new();
         q.push(Task::new_main());
         q.push(Task::new(1, PriorityLevel::new(3)));
-        q.push(Task::new(2, PriorityLevel::new(3)));
-        q.push(Task::new(3, PriorityLevel::new(1)));
-        q.push(Task::new(4, PriorityLevel::new(3)));
-        q.push(Task::new(5, PriorityLevel::new(2)));
-
-        q.sort_by_priority();
-        let task = q.next_run_task_ref().unwrap();
-        assert_eq!(task.id, 1);
-        assert_eq!(task.priority_level, PriorityLevel::new(3));
-
-        macro_rules! assert_task {
-            ($index: literal, $id: literal, $level: literal) => {
-                assert_eq!(q.tasks[$index].id, $id, "id");
-                assert_eq!(
-                    q.tasks[$index].priority_level,
-                    PriorityLevel::new($level),
-                    "level"
-                );
+        q.push(Task::new(2, PriorityLevel::new(1)));
+        q.push(Task::new(3, PriorityLevel::new(3)));
+
+        let mut running_ids = Vec::new();
+        for _ in 0..4 {
+            let (running_id, next_id) = {
+                let command = q
+                    .preempt_command()
+                    .unwrap()
+                    .unwrap();
+                (command.running_id(), command.next_id())
             };
+            // Commands switch the context, so only the statuses are switched here.
+            q.find_ref(running_id)
+                .unwrap()
+                .store_status(Status::Pending);
+            q.find_ref(next_id)
+                .unwrap()
+                .store_status(Running);
+            running_ids.push(next_id);
+        }
+
+        assert_eq!(running_ids, [1, 3, 0, 1]);
+    }
+
+
+    #[test]
+    fn it_age_starving_task() {
+        let mut q = TaskList::new();
+        q.push(Task::new_main());
+        q.push(Task::new(1, PriorityLevel::new(1)));
+        q.push(Task::new(2, PriorityLevel::new(3)));
+
+        for _ in 0..4 {
+            q.tick();
+            q.age(2);
         }
-        assert_task!(0, 1, 3);
-        assert_task!(1, 2, 3);
-        assert_task!(2, 4, 3);
-        // Running tasks are moved to the end of the same level group.
-        assert_task!(3, 0, 3);
-        assert_task!(4, 5, 2);
-        assert_task!(5, 3, 1);
+
+        let (priority_level, task_id) = q
+            .front_next()
+            .unwrap();
+        assert_eq!(priority_level, PriorityLevel::new(3));
+        assert_eq!(task_id, 2);
+
+        q.run_queue.pop_front();
+        assert_eq!(q.front_next(), Some((PriorityLevel::new(3), 1)));
     }
 }
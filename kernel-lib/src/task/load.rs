@@ -0,0 +1,93 @@
+/// The fraction bits of the fixed-point loads.
+const FSHIFT: u32 = 11;
+
+const FIXED_1: u64 = 1 << FSHIFT;
+
+/// `FIXED_1 / e^(1 / n)` for the averages over the last 1, 5 and 15 samples.
+const EXP: [u64; 3] = [753, 1677, 1916];
+
+
+/// Exponentially decaying averages of how busy a task keeps the CPU,
+/// like the load averages of Unix.
+///
+/// Integers are used since the loads are updated from the timer interrupt,
+/// which doesn't save the SSE registers.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub struct LoadAverage {
+    loads: [u64; 3],
+}
+
+
+impl LoadAverage {
+    pub const fn new() -> Self {
+        Self { loads: [0; 3] }
+    }
+
+
+    /// Adds a sample in which the task ran `busy_nanos` out of `elapsed_nanos`.
+    pub fn update(&mut self, busy_nanos: u64, elapsed_nanos: u64) {
+        if elapsed_nanos == 0 {
+            return;
+        }
+
+        let busy = busy_nanos.min(elapsed_nanos) as u128;
+        let load = (busy * FIXED_1 as u128 / elapsed_nanos as u128) as u64;
+
+        for (average, exp) in self
+            .loads
+            .iter_mut()
+            .zip(EXP)
+        {
+            let mut next = *average * exp + load * (FIXED_1 - exp);
+            // Rounds up while rising so that a full load reaches `FIXED_1`.
+            if *average <= load {
+                next += FIXED_1 - 1;
+            }
+            *average = next >> FSHIFT;
+        }
+    }
+
+
+    /// Returns the averages over the last 1, 5 and 15 samples in permille of the CPU.
+    pub fn permille(&self) -> [u32; 3] {
+        self.loads
+            .map(|load| ((load * 1000 + FIXED_1 / 2) >> FSHIFT) as u32)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::task::load::LoadAverage;
+
+    #[test]
+    fn it_converge_to_full_load() {
+        let mut load = LoadAverage::new();
+        for _ in 0..200 {
+            load.update(1_000, 1_000);
+        }
+
+        assert_eq!(load.permille(), [1000, 1000, 1000]);
+    }
+
+
+    #[test]
+    fn it_short_average_follows_faster() {
+        let mut load = LoadAverage::new();
+        load.update(500, 1_000);
+
+        let [one, five, fifteen] = load.permille();
+        assert!(five < one);
+        assert!(fifteen < five);
+        assert_eq!(one, 316);
+    }
+
+
+    #[test]
+    fn it_ignore_empty_sample() {
+        let mut load = LoadAverage::new();
+        load.update(1_000, 0);
+
+        assert_eq!(load.permille(), [0, 0, 0]);
+    }
+}
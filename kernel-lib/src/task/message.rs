@@ -1,9 +1,24 @@
 use alloc::boxed::Box;
+use core::any::Any;
+
+use common_lib::event::{AppEvent, KeyEvent, MouseEvent};
 
 pub enum TaskMessage {
     Xhci,
 
     Dispatch(Box<dyn Fn()>),
+
+    /// A key pressed while the window of the task is active.
+    Key(KeyEvent),
+
+    /// A mouse button pressed or released over the window of the task.
+    Mouse(MouseEvent),
+
+    /// Sent by [`TimeHandle::start_notify`](crate::timer::handler::TimeHandle::start_notify).
+    TimerExpired { timer_id: u64 },
+
+    /// Any payload agreed between the sender and the receiver.
+    Custom(Box<dyn Any>),
 }
 
 
@@ -12,4 +27,60 @@ impl TaskMessage {
     pub fn dispatch(f: impl Fn() + 'static) -> Self {
         TaskMessage::Dispatch(Box::new(f))
     }
+
+
+    #[inline(always)]
+    pub fn custom<T: 'static>(payload: T) -> Self {
+        TaskMessage::Custom(Box::new(payload))
+    }
+
+
+    /// Returns the payload if this is a [`TaskMessage::Custom`] holding a `T`.
+    pub fn downcast<T: 'static>(self) -> Option<T> {
+        match self {
+            TaskMessage::Custom(payload) => payload
+                .downcast::<T>()
+                .ok()
+                .map(|payload| *payload),
+            _ => None,
+        }
+    }
+
+
+    /// Converts into the event delivered to applications, if it is one.
+    pub fn into_app_event(self) -> Option<AppEvent> {
+        match self {
+            TaskMessage::Key(event) => Some(AppEvent::Key(event)),
+            TaskMessage::Mouse(event) => Some(AppEvent::Mouse(event)),
+            TaskMessage::TimerExpired { timer_id } => Some(AppEvent::TimerExpired { timer_id }),
+            _ => None,
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use common_lib::event::{AppEvent, Key, KeyEvent};
+
+    use crate::task::message::TaskMessage;
+
+    #[test]
+    fn it_downcast_custom() {
+        assert_eq!(TaskMessage::custom(3_u32).downcast::<u32>(), Some(3));
+        assert_eq!(TaskMessage::custom(3_u32).downcast::<u64>(), None);
+        assert_eq!(TaskMessage::Xhci.downcast::<u32>(), None);
+    }
+
+
+    #[test]
+    fn it_into_app_event() {
+        let event = KeyEvent::new(0, Key::Ascii('a'));
+
+        assert_eq!(
+            TaskMessage::Key(event).into_app_event(),
+            Some(AppEvent::Key(event))
+        );
+        assert_eq!(TaskMessage::dispatch(|| {}).into_app_event(), None);
+    }
 }
@@ -15,6 +15,12 @@ impl PriorityLevel{
     }
 
 
+    #[inline(always)]
+    pub const fn value(&self) -> usize {
+        self.0
+    }
+
+
 
 
 }
@@ -0,0 +1,183 @@
+use alloc::collections::{BTreeMap, VecDeque};
+use alloc::vec::Vec;
+
+use crate::task::priority_level::PriorityLevel;
+
+/// The ids of the tasks ready to run, queued per priority level.
+///
+/// Tasks of the highest level run first, in turn within the same level.
+#[derive(Default, Debug)]
+pub struct RunQueue {
+    levels: BTreeMap<PriorityLevel, VecDeque<Entry>>,
+}
+
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+struct Entry {
+    task_id: u64,
+    /// The tick at which the task was queued or last aged.
+    queued_at: u64,
+}
+
+
+impl RunQueue {
+    pub const fn new() -> Self {
+        Self {
+            levels: BTreeMap::new(),
+        }
+    }
+
+
+    /// Queues the task at the back of the level unless it's queued already.
+    pub fn push_back(&mut self, level: PriorityLevel, task_id: u64, now: u64) {
+        if self.contains(task_id) {
+            return;
+        }
+
+        self.levels
+            .entry(level)
+            .or_default()
+            .push_back(Entry {
+                task_id,
+                queued_at: now,
+            });
+    }
+
+
+    pub fn contains(&self, task_id: u64) -> bool {
+        self.levels
+            .values()
+            .flatten()
+            .any(|entry| entry.task_id == task_id)
+    }
+
+
+    /// Returns the level and the id of the task to run next.
+    pub fn front(&self) -> Option<(PriorityLevel, u64)> {
+        self.levels
+            .iter()
+            .rev()
+            .find_map(|(level, queue)| {
+                queue
+                    .front()
+                    .map(|entry| (*level, entry.task_id))
+            })
+    }
+
+
+    pub fn pop_front(&mut self) -> Option<(PriorityLevel, u64)> {
+        self.levels
+            .iter_mut()
+            .rev()
+            .find_map(|(level, queue)| {
+                queue
+                    .pop_front()
+                    .map(|entry| (*level, entry.task_id))
+            })
+    }
+
+
+    pub fn remove(&mut self, task_id: u64) {
+        for queue in self.levels.values_mut() {
+            queue.retain(|entry| entry.task_id != task_id);
+        }
+    }
+
+
+    /// Moves the tasks which have waited `threshold` ticks or longer up by a level,
+    /// so that tasks of low levels don't starve.
+    ///
+    /// Tasks are never moved above `max_level`.
+    pub fn age(&mut self, now: u64, threshold: u64, max_level: PriorityLevel) {
+        let levels: Vec<PriorityLevel> = self
+            .levels
+            .keys()
+            .rev()
+            .filter(|level| **level < max_level)
+            .copied()
+            .collect();
+
+        // Higher levels go first so that a task moves up only one level at a time.
+        for level in levels {
+            let queue = self
+                .levels
+                .get_mut(&level)
+                .unwrap();
+            let (aged, waiting): (VecDeque<Entry>, VecDeque<Entry>) = queue
+                .drain(..)
+                .partition(|entry| threshold <= now.saturating_sub(entry.queued_at));
+            *queue = waiting;
+
+            let upper = self
+                .levels
+                .entry(PriorityLevel::new(level.value() + 1))
+                .or_default();
+            upper.extend(aged.into_iter().map(|entry| Entry {
+                task_id: entry.task_id,
+                queued_at: now,
+            }));
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::task::priority_level::PriorityLevel;
+    use crate::task::run_queue::RunQueue;
+
+    #[test]
+    fn it_pop_highest_level_first() {
+        let mut queue = RunQueue::new();
+        queue.push_back(PriorityLevel::new(1), 1, 0);
+        queue.push_back(PriorityLevel::new(3), 2, 0);
+        queue.push_back(PriorityLevel::new(3), 3, 0);
+
+        assert_eq!(queue.front(), Some((PriorityLevel::new(3), 2)));
+        assert_eq!(queue.pop_front(), Some((PriorityLevel::new(3), 2)));
+        assert_eq!(queue.pop_front(), Some((PriorityLevel::new(3), 3)));
+        assert_eq!(queue.pop_front(), Some((PriorityLevel::new(1), 1)));
+        assert_eq!(queue.pop_front(), None);
+    }
+
+
+    #[test]
+    fn it_not_queue_twice() {
+        let mut queue = RunQueue::new();
+        queue.push_back(PriorityLevel::new(1), 1, 0);
+        queue.push_back(PriorityLevel::new(2), 1, 0);
+
+        assert_eq!(queue.pop_front(), Some((PriorityLevel::new(1), 1)));
+        assert_eq!(queue.pop_front(), None);
+    }
+
+
+    #[test]
+    fn it_remove() {
+        let mut queue = RunQueue::new();
+        queue.push_back(PriorityLevel::new(1), 1, 0);
+        queue.push_back(PriorityLevel::new(1), 2, 0);
+
+        queue.remove(1);
+
+        assert!(!queue.contains(1));
+        assert_eq!(queue.pop_front(), Some((PriorityLevel::new(1), 2)));
+    }
+
+
+    #[test]
+    fn it_age_waiting_tasks() {
+        let mut queue = RunQueue::new();
+        queue.push_back(PriorityLevel::new(0), 1, 0);
+        queue.push_back(PriorityLevel::new(0), 2, 5);
+        queue.push_back(PriorityLevel::new(1), 3, 0);
+        queue.push_back(PriorityLevel::new(2), 4, 0);
+
+        queue.age(10, 10, PriorityLevel::new(2));
+
+        assert_eq!(queue.pop_front(), Some((PriorityLevel::new(2), 4)));
+        assert_eq!(queue.pop_front(), Some((PriorityLevel::new(2), 3)));
+        assert_eq!(queue.pop_front(), Some((PriorityLevel::new(1), 1)));
+        assert_eq!(queue.pop_front(), Some((PriorityLevel::new(0), 2)));
+    }
+}
@@ -0,0 +1,111 @@
+use alloc::alloc::{alloc_zeroed, dealloc, handle_alloc_error};
+use core::alloc::Layout;
+use core::ops::Range;
+use core::ptr::NonNull;
+
+use crate::paging::PAGE_SIZE_4K;
+
+/// The kernel stack of a task with a guard page below it.
+///
+/// The guard page is made not present in the page table,
+/// so a stack overflow causes a page fault instead of silently corrupting the heap.
+pub struct TaskStack {
+    base: NonNull<u8>,
+    layout: Layout,
+    guarded: bool,
+}
+
+
+impl TaskStack {
+    /// Allocates a zeroed stack of at least `stack_size` bytes, rounded up to pages.
+    pub fn new(stack_size: usize) -> Self {
+        let size = align_up(stack_size) + PAGE_SIZE_4K;
+        let layout = Layout::from_size_align(size, PAGE_SIZE_4K).unwrap();
+        let base = NonNull::new(unsafe { alloc_zeroed(layout) })
+            .unwrap_or_else(|| handle_alloc_error(layout));
+
+        Self {
+            base,
+            layout,
+            guarded: install_guard_page(base.as_ptr() as u64),
+        }
+    }
+
+
+    /// Returns the bytes usable as the stack, excluding the guard page.
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.layout.size() - PAGE_SIZE_4K
+    }
+
+
+    /// Returns the address next to the top of the stack, where it starts to grow down from.
+    #[inline]
+    pub fn end(&self) -> u64 {
+        self.base.as_ptr() as u64 + self.layout.size() as u64
+    }
+
+
+    #[inline]
+    pub fn guard_page(&self) -> Range<u64> {
+        let base = self.base.as_ptr() as u64;
+        base..base + PAGE_SIZE_4K as u64
+    }
+}
+
+
+impl Drop for TaskStack {
+    fn drop(&mut self) {
+        // The allocator writes to the freed memory, so the guard page must be mapped back first.
+        if self.guarded {
+            remove_guard_page(self.guard_page().start);
+        }
+
+        unsafe { dealloc(self.base.as_ptr(), self.layout) };
+    }
+}
+
+
+#[inline]
+fn align_up(size: usize) -> usize {
+    (size + PAGE_SIZE_4K - 1) & !(PAGE_SIZE_4K - 1)
+}
+
+
+#[cfg(not(test))]
+fn install_guard_page(page: u64) -> bool {
+    crate::paging::guard::install_guard_page(page).is_ok()
+}
+
+
+#[cfg(not(test))]
+fn remove_guard_page(page: u64) {
+    crate::paging::guard::remove_guard_page(page).unwrap();
+}
+
+
+/// Page tables can't be modified in tests, so stacks have no guard there.
+#[cfg(test)]
+fn install_guard_page(_page: u64) -> bool {
+    false
+}
+
+
+#[cfg(test)]
+fn remove_guard_page(_page: u64) {}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::paging::PAGE_SIZE_4K;
+    use crate::task::stack::TaskStack;
+
+    #[test]
+    fn it_round_up_to_pages() {
+        let stack = TaskStack::new(5000);
+
+        assert_eq!(stack.size(), 2 * PAGE_SIZE_4K);
+        assert_eq!(stack.guard_page().start % PAGE_SIZE_4K as u64, 0);
+        assert_eq!(stack.guard_page().end + stack.size() as u64, stack.end());
+    }
+}
@@ -1,3 +1,5 @@
+use core::fmt::{Display, Formatter};
+
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd)]
 #[repr(u8)]
 pub enum Status {
@@ -6,6 +8,9 @@ pub enum Status {
     Pending = 1,
 
     Running = 2,
+
+    /// The task has finished and is removed on the next scheduling.
+    Exited = 3,
 }
 
 
@@ -26,6 +31,22 @@ impl Status {
     pub const fn is_sleep(&self) -> bool {
         matches!(self, Status::Sleep)
     }
+
+
+    #[inline(always)]
+    pub const fn is_exited(&self) -> bool {
+        matches!(self, Status::Exited)
+    }
 }
 
 
+impl Display for Status {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.pad(match self {
+            Status::Sleep => "Sleep",
+            Status::Pending => "Pending",
+            Status::Running => "Running",
+            Status::Exited => "Exited",
+        })
+    }
+}
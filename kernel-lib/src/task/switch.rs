@@ -1,6 +1,8 @@
+use crate::segmentation::tss;
 use crate::task::status::Status;
 use crate::task::status::Status::Running;
 use crate::task::Task;
+use crate::timer::uptime_nanos;
 
 pub struct SwitchCommand<'t> {
     running: &'t Task,
@@ -27,6 +29,12 @@ impl<'t> SwitchCommand<'t> {
     }
 
 
+    #[inline(always)]
+    pub fn switch_and_exit(&mut self) {
+        self.switch(Status::Exited);
+    }
+
+
     #[cfg(test)]
     pub(crate) fn running_id(&self) -> u64 {
         self.running.id
@@ -39,21 +47,20 @@ impl<'t> SwitchCommand<'t> {
     }
 
 
-    pub fn switch_if_need(&self, status: Status) {
-        if self.next.priority_level < self.running.priority_level {
-            return;
-        }
-
-        self.switch(status);
-    }
-
-
     fn switch(&self, status: Status) {
+        let now = uptime_nanos();
+        self.running.switch_out(now);
+        self.next.switch_in(now);
+
         self.running
             .store_status(status);
         self.next
             .store_status(Running);
 
+        self.running
+            .store_rsp0(tss::rsp0());
+        tss::set_rsp0(self.next.rsp0());
+
         self.running
             .switch_to(self.next)
     }
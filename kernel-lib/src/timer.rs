@@ -1,10 +1,201 @@
+use core::cell::OnceCell;
+use core::sync::atomic::{AtomicU32, AtomicU8, Ordering};
+use core::time::Duration;
+
+use crate::error::KernelResult;
+use crate::kernel_bail;
+#[cfg(not(test))]
+use crate::timer::apic::local_apic_timer::LocalApicTimer;
+#[cfg(not(test))]
+use crate::timer::apic::ApicTimer;
 use crate::timer::handler::manager::TimeHandleManager;
+use crate::timer::hpet::hpet;
+use crate::timer::pm_timer::PmTimer;
 
 pub mod apic;
 pub mod handler;
+pub mod hpet;
+pub mod instant;
+pub mod pm_timer;
+pub mod rtc;
+
+/// The number of timer interrupts per second.
+pub const TIMER_FREQ: u32 = 100;
+
+/// The length of a timer tick in nanoseconds.
+pub const TICK_NANOS: u64 = 1_000_000_000 / TIMER_FREQ as u64;
 
 pub static TIME_HANDLE_MANAGER: TimeHandleManager = TimeHandleManager::new();
 
+static TICK_SOURCE: AtomicU8 = AtomicU8::new(TickSource::LocalApic as u8);
+
+static PM_TIMER: GlobalPmTimer = GlobalPmTimer(OnceCell::new());
+
+/// The count of the PM timer at the last tick, used with [`TickSource::PmTimer`].
+static LAST_TICK_PM_COUNT: AtomicU32 = AtomicU32::new(0);
+
+
+/// The timer which fires the ticks and measures the time between them.
+#[repr(u8)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum TickSource {
+    /// The HPET fires the ticks, and its main counter gives the uptime.
+    Hpet,
+    /// The local APIC timer fires the ticks and measures the time within a tick.
+    LocalApic,
+    /// The local APIC timer fires the ticks, and the PM timer measures the time within a tick.
+    PmTimer,
+}
+
+
+impl TickSource {
+    const fn from_u8(raw: u8) -> Self {
+        match raw {
+            0 => Self::Hpet,
+            2 => Self::PmTimer,
+            _ => Self::LocalApic,
+        }
+    }
+}
+
+
+struct GlobalPmTimer(OnceCell<PmTimer>);
+
+
+unsafe impl Sync for GlobalPmTimer {}
+
+
+/// Keeps the PM timer found in the FADT, so that it can be selected by [`set_tick_source`].
+pub fn init_pm_timer(pm_timer: PmTimer) {
+    let _ = PM_TIMER.0.set(pm_timer);
+}
+
+
+/// Selects the timer measuring the time within a tick.
+///
+/// The kernel calls this after starting the timer which fires the ticks.
+pub fn set_tick_source(source: TickSource) -> KernelResult {
+    match source {
+        TickSource::Hpet if hpet().is_none() => kernel_bail!("HPET is not initialized"),
+        TickSource::Hpet if hpet().is_some_and(|hpet| !hpet.is_64bit()) => {
+            kernel_bail!("HPET counter is not 64-bit")
+        }
+        TickSource::PmTimer if PM_TIMER.0.get().is_none() => {
+            kernel_bail!("PM timer is not initialized")
+        }
+        _ => {
+            TICK_SOURCE.store(source as u8, Ordering::Relaxed);
+            Ok(())
+        }
+    }
+}
+
+
+#[inline]
+pub fn tick_source() -> TickSource {
+    TickSource::from_u8(TICK_SOURCE.load(Ordering::Relaxed))
+}
+
+
+/// Counts a tick and calls the expired handlers; called from the timer interrupt handler.
+pub fn tick() {
+    if tick_source() == TickSource::PmTimer {
+        if let Some(pm_timer) = PM_TIMER.0.get() {
+            LAST_TICK_PM_COUNT.store(pm_timer.count(), Ordering::Relaxed);
+        }
+    }
+
+    TIME_HANDLE_MANAGER.tick();
+}
+
+
+/// Converts the duration into timer ticks, rounding up.
+pub fn duration_to_ticks(duration: Duration) -> u64 {
+    let nanos = duration
+        .as_nanos()
+        .saturating_mul(TIMER_FREQ as u128);
+
+    u64::try_from((nanos + 999_999_999) / 1_000_000_000).unwrap_or(u64::MAX)
+}
+
+
+#[inline]
+pub fn ticks_to_duration(ticks: u64) -> Duration {
+    Duration::from_millis(ticks.saturating_mul(1000) / TIMER_FREQ as u64)
+}
+
+
+/// Returns the nanoseconds since the timer started.
+///
+/// The time within the current tick is read from the [`TickSource`],
+/// so this is finer than [`TimeHandleManager::ticks`].
+pub fn uptime_nanos() -> u64 {
+    if let (TickSource::Hpet, Some(hpet)) = (tick_source(), hpet()) {
+        return hpet.nanos();
+    }
+
+    TIME_HANDLE_MANAGER
+        .ticks()
+        .saturating_mul(TICK_NANOS)
+        .saturating_add(tick_progress_nanos())
+}
+
+
+#[cfg(not(test))]
+fn tick_progress_nanos() -> u64 {
+    if let (TickSource::PmTimer, Some(pm_timer)) = (tick_source(), PM_TIMER.0.get()) {
+        let last_tick = LAST_TICK_PM_COUNT.load(Ordering::Relaxed);
+        let counts = pm_timer.counts_between(last_tick, pm_timer.count());
+        return PmTimer::counts_to_nanos(counts).min(TICK_NANOS - 1);
+    }
+
+    let timer = LocalApicTimer::new();
+    let initial_count = timer.initial_count() as u64;
+    if initial_count == 0 {
+        return 0;
+    }
+
+    (timer.elapsed() as u64 * TICK_NANOS / initial_count).min(TICK_NANOS - 1)
+}
+
+
+#[cfg(test)]
+fn tick_progress_nanos() -> u64 {
+    0
+}
+
+
+#[cfg(test)]
+mod tests {
+    use core::time::Duration;
+
+    use crate::timer::{duration_to_ticks, ticks_to_duration, TickSource};
+
+    #[test]
+    fn it_duration_to_ticks() {
+        assert_eq!(duration_to_ticks(Duration::ZERO), 0);
+        assert_eq!(duration_to_ticks(Duration::from_millis(1)), 1);
+        assert_eq!(duration_to_ticks(Duration::from_millis(10)), 1);
+        assert_eq!(duration_to_ticks(Duration::from_millis(11)), 2);
+        assert_eq!(duration_to_ticks(Duration::from_secs(2)), 200);
+        assert_eq!(duration_to_ticks(Duration::MAX), u64::MAX);
+    }
+
+
+    #[test]
+    fn it_ticks_to_duration() {
+        assert_eq!(ticks_to_duration(150), Duration::from_millis(1500));
+    }
+
+
+    #[test]
+    fn it_restore_tick_source() {
+        for source in [TickSource::Hpet, TickSource::LocalApic, TickSource::PmTimer] {
+            assert_eq!(TickSource::from_u8(source as u8), source);
+        }
+    }
+}
+
 
 
 
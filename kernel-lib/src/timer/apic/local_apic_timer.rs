@@ -20,6 +20,14 @@ impl LocalApicTimer {
             local_apic_registers,
         }
     }
+
+
+    /// Returns the count the timer reloads at every period.
+    pub fn initial_count(&self) -> u32 {
+        self.local_apic_registers
+            .initial_count()
+            .read_volatile()
+    }
 }
 
 
@@ -1,6 +1,7 @@
 use alloc::boxed::Box;
 use alloc::rc::Rc;
 
+use crate::task;
 use crate::task::message::TaskMessage;
 use crate::task::TASK_MANAGER;
 use crate::timer::TIME_HANDLE_MANAGER;
@@ -86,6 +87,23 @@ impl TimeHandle {
 
         }
     }
+
+
+    /// Sends [`TaskMessage::TimerExpired`] with `timer_id` to the task every `interval`.
+    pub fn start_notify(
+        interval: usize,
+        task_id: u64,
+        timer_id: u64,
+    ) -> Self {
+        let id = TIME_HANDLE_MANAGER.entry(interval, move || {
+            let _ = task::send_message(task_id, TaskMessage::TimerExpired { timer_id });
+        });
+
+        Self {
+            id,
+
+        }
+    }
 }
 
 
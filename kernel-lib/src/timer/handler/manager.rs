@@ -1,12 +1,16 @@
 use alloc::collections::BTreeMap;
+use alloc::rc::Rc;
+use alloc::vec::Vec;
 use core::cell::RefCell;
-use core::sync::atomic::{AtomicUsize, Ordering};
+use core::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
 
+use crate::interrupt::asm::without_interrupt;
 use crate::timer::handler::TimeCallback;
 use crate::timer::handler::timer::HandleTimer;
 
 pub struct TimeHandleManager {
-    handlers: RefCell<BTreeMap<usize, HandleTimer>>,
+    handlers: RefCell<BTreeMap<usize, Rc<HandleTimer>>>,
+    ticks: AtomicU64,
 }
 
 
@@ -17,37 +21,135 @@ impl TimeHandleManager {
     #[inline]
     pub const fn new() -> Self {
         Self {
-            handlers: RefCell::new(BTreeMap::new())
+            handlers: RefCell::new(BTreeMap::new()),
+            ticks: AtomicU64::new(0),
         }
     }
 
 
     #[inline]
     pub fn tick(&self) {
-        self.handlers
+        self.ticks
+            .fetch_add(1, Ordering::Relaxed);
+
+        let expired: Vec<(usize, Rc<HandleTimer>)> = self
+            .handlers
             .borrow()
-            .values()
-            .for_each(|timer| {
-                timer.tick();
-            })
+            .iter()
+            .filter(|(_, timer)| timer.tick())
+            .map(|(id, timer)| (*id, Rc::clone(timer)))
+            .collect();
+
+        // Handlers are called without borrowing, since they may switch tasks
+        // or register other timers.
+        for (id, timer) in expired {
+            if timer.is_one_shot() {
+                self.handlers
+                    .borrow_mut()
+                    .remove(&id);
+            }
+            timer.call();
+        }
+    }
+
+
+    /// Returns the number of ticks since the timer started.
+    #[inline]
+    pub fn ticks(&self) -> u64 {
+        self.ticks
+            .load(Ordering::Relaxed)
     }
 
 
     pub fn entry(&self, interval: usize, handler: impl TimeCallback + 'static) -> usize {
+        self.insert(HandleTimer::new(interval, handler))
+    }
+
+
+    /// Registers a handler called once after `interval` ticks, then removed.
+    pub fn entry_once(&self, interval: usize, handler: impl TimeCallback + 'static) -> usize {
+        self.insert(HandleTimer::one_shot(interval, handler))
+    }
+
+
+    pub fn remove(&self, id: usize) {
+        without_interrupt(|| {
+            self.handlers
+                .borrow_mut()
+                .remove(&id);
+        });
+    }
+
+
+    fn insert(&self, timer: HandleTimer) -> usize {
         static ID: AtomicUsize = AtomicUsize::new(0);
         let id = ID.fetch_add(1, Ordering::Relaxed);
 
-        self.handlers
-            .borrow_mut()
-            .insert(id, HandleTimer::new(interval, handler));
+        // The timer interrupt borrows the handlers, so it must not come in meanwhile.
+        without_interrupt(|| {
+            self.handlers
+                .borrow_mut()
+                .insert(id, Rc::new(timer));
+        });
 
         id
     }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use alloc::rc::Rc;
+    use core::cell::Cell;
+
+    use crate::timer::handler::manager::TimeHandleManager;
+
+    #[test]
+    fn it_call_one_shot_once() {
+        let manager = TimeHandleManager::new();
+        let count = Rc::new(Cell::new(0));
+        let c = Rc::clone(&count);
+        manager.entry_once(1, move || c.set(c.get() + 1));
+
+        for _ in 0..5 {
+            manager.tick();
+        }
+
+        assert_eq!(count.get(), 1);
+        assert!(manager
+            .handlers
+            .borrow()
+            .is_empty());
+    }
 
 
-    pub fn remove(&self, id: usize) {
-        self.handlers
-            .borrow_mut()
-            .remove(&id);
+    #[test]
+    fn it_call_periodic_repeatedly() {
+        let manager = TimeHandleManager::new();
+        let count = Rc::new(Cell::new(0));
+        let c = Rc::clone(&count);
+        manager.entry(1, move || c.set(c.get() + 1));
+
+        for _ in 0..6 {
+            manager.tick();
+        }
+
+        assert_eq!(count.get(), 6);
+    }
+
+
+    #[test]
+    fn it_call_one_shot_after_interval() {
+        let manager = TimeHandleManager::new();
+        let count = Rc::new(Cell::new(0));
+        let c = Rc::clone(&count);
+        manager.entry_once(3, move || c.set(c.get() + 1));
+
+        manager.tick();
+        manager.tick();
+        assert_eq!(count.get(), 0);
+
+        manager.tick();
+        assert_eq!(count.get(), 1);
     }
 }
@@ -8,6 +8,7 @@ pub struct HandleTimer {
     interval: usize,
     tick: AtomicUsize,
     handler: BoxedTimeHandler,
+    one_shot: bool,
 }
 
 
@@ -18,26 +19,53 @@ impl HandleTimer {
             interval,
             tick: AtomicUsize::new(0),
             handler: Box::new(handler),
+            one_shot: false,
         }
     }
 
 
-    pub fn tick(&self) {
-        let next_tick = self
+    /// Creates a timer which calls the handler only once, after `interval` ticks.
+    #[inline(always)]
+    pub fn one_shot(interval: usize, handler: impl TimeCallback + 'static) -> Self {
+        Self {
+            one_shot: true,
+            ..Self::new(interval, handler)
+        }
+    }
+
+
+    #[inline(always)]
+    pub fn is_one_shot(&self) -> bool {
+        self.one_shot
+    }
+
+
+    /// Counts a tick and returns true if the interval has elapsed.
+    ///
+    /// The handler isn't called here; see [`HandleTimer::call`].
+    pub fn tick(&self) -> bool {
+        let elapsed = self
             .tick
-            .fetch_add(1, Relaxed);
+            .fetch_add(1, Relaxed)
+            + 1;
 
-        if self.interval <= next_tick {
+        if self.interval <= elapsed {
             self.reset();
-            self.handler.call();
+            true
+        } else {
+            false
         }
     }
 
 
+    #[inline(always)]
+    pub fn call(&self) {
+        self.handler.call();
+    }
+
+
     #[inline(always)]
     pub fn reset(&self) {
         self.tick.store(0, Relaxed);
     }
 }
-
-
@@ -0,0 +1,347 @@
+use core::cell::OnceCell;
+
+use crate::error::KernelResult;
+use crate::{kernel_bail, kernel_error};
+
+const GENERAL_CAPABILITIES: u64 = 0x000;
+const GENERAL_CONFIG: u64 = 0x010;
+const MAIN_COUNTER: u64 = 0x0F0;
+
+const COMPARATOR_CONFIG: u64 = 0x100;
+const COMPARATOR_VALUE: u64 = 0x108;
+const COMPARATOR_STRIDE: u64 = 0x20;
+
+const ENABLE_CNF: u64 = 1 << 0;
+const LEG_RT_CNF: u64 = 1 << 1;
+const COUNT_SIZE_CAP: u64 = 1 << 13;
+const LEG_RT_CAP: u64 = 1 << 15;
+
+const TN_INT_ENB_CNF: u64 = 1 << 2;
+const TN_TYPE_CNF: u64 = 1 << 3;
+const TN_PER_INT_CAP: u64 = 1 << 4;
+const TN_SIZE_CAP: u64 = 1 << 5;
+const TN_VAL_SET_CNF: u64 = 1 << 6;
+const TN_32MODE_CNF: u64 = 1 << 8;
+const TN_INT_ROUTE_SHIFT: u64 = 9;
+const TN_INT_ROUTE_MASK: u64 = 0x1F << TN_INT_ROUTE_SHIFT;
+
+/// The specification limits the period of the main counter to 100 nanoseconds.
+const MAX_PERIOD_FEMTOS: u64 = 100_000_000;
+
+const FEMTOS_PER_NANO: u128 = 1_000_000;
+
+static HPET: GlobalHpet = GlobalHpet(OnceCell::new());
+
+
+/// Sets up the HPET whose registers are at `base`, found in the ACPI HPET table.
+///
+/// The counter is left halted; call [`HpetTimer::enable`] after setting the comparators.
+pub fn init_hpet(base: u64) -> KernelResult {
+    let hpet = HpetTimer::new(base)?;
+    hpet.disable();
+    hpet.reset_counter();
+    for index in 0..hpet.comparators() {
+        hpet.comparator(index)?.stop();
+    }
+
+    HPET.0
+        .set(hpet)
+        .map_err(|_| kernel_error!("HPET is already initialized"))
+}
+
+
+/// Returns the HPET if [`init_hpet`] succeeded.
+#[inline]
+pub fn hpet() -> Option<&'static HpetTimer> {
+    HPET.0.get()
+}
+
+
+struct GlobalHpet(OnceCell<HpetTimer>);
+
+
+unsafe impl Sync for GlobalHpet {}
+
+
+/// High Precision Event Timer
+///
+/// A monotonic main counter with the comparators firing interrupts at its values.
+#[derive(Debug)]
+pub struct HpetTimer {
+    base: u64,
+    period_femtos: u64,
+    comparators: u8,
+    counter_64bit: bool,
+    legacy_replacement_capable: bool,
+}
+
+
+impl HpetTimer {
+    pub fn new(base: u64) -> KernelResult<Self> {
+        let capabilities = read_register(base, GENERAL_CAPABILITIES);
+        let period_femtos = capabilities >> 32;
+        if period_femtos == 0 || MAX_PERIOD_FEMTOS < period_femtos {
+            return kernel_bail!("Invalid HPET counter period {period_femtos}fs");
+        }
+
+        Ok(Self {
+            base,
+            period_femtos,
+            comparators: ((capabilities >> 8) & 0x1F) as u8 + 1,
+            counter_64bit: capabilities & COUNT_SIZE_CAP != 0,
+            legacy_replacement_capable: capabilities & LEG_RT_CAP != 0,
+        })
+    }
+
+
+    /// Returns the length of a count of the main counter in femtoseconds.
+    #[inline]
+    pub fn period_femtos(&self) -> u64 {
+        self.period_femtos
+    }
+
+
+    /// Returns the counts of the main counter per second.
+    #[inline]
+    pub fn frequency(&self) -> u64 {
+        1_000_000_000_000_000 / self.period_femtos
+    }
+
+
+    #[inline]
+    pub fn comparators(&self) -> u8 {
+        self.comparators
+    }
+
+
+    /// Returns whether the main counter is 64-bit.
+    ///
+    /// A 32-bit counter wraps around in minutes, so it can't measure the uptime.
+    #[inline]
+    pub fn is_64bit(&self) -> bool {
+        self.counter_64bit
+    }
+
+
+    #[inline]
+    pub fn counter(&self) -> u64 {
+        self.read(MAIN_COUNTER)
+    }
+
+
+    /// Returns the nanoseconds since the main counter was reset.
+    #[inline]
+    pub fn nanos(&self) -> u64 {
+        counts_to_nanos(self.counter(), self.period_femtos)
+    }
+
+
+    #[inline]
+    pub fn nanos_to_counts(&self, nanos: u64) -> u64 {
+        nanos_to_counts(nanos, self.period_femtos)
+    }
+
+
+    pub fn enable(&self) {
+        self.write(GENERAL_CONFIG, self.read(GENERAL_CONFIG) | ENABLE_CNF);
+    }
+
+
+    pub fn disable(&self) {
+        self.write(GENERAL_CONFIG, self.read(GENERAL_CONFIG) & !ENABLE_CNF);
+    }
+
+
+    /// Routes the comparators 0 and 1 to IRQ 0 and IRQ 8 in place of the PIT and the RTC.
+    pub fn enable_legacy_replacement(&self) -> KernelResult {
+        if !self.legacy_replacement_capable {
+            return kernel_bail!("HPET doesn't support the legacy replacement route");
+        }
+
+        self.write(GENERAL_CONFIG, self.read(GENERAL_CONFIG) | LEG_RT_CNF);
+        Ok(())
+    }
+
+
+    pub fn comparator(&self, index: u8) -> KernelResult<HpetComparator> {
+        if self.comparators <= index {
+            return kernel_bail!("HPET has no comparator {index}");
+        }
+
+        Ok(HpetComparator {
+            base: self.base,
+            offset: index as u64 * COMPARATOR_STRIDE,
+        })
+    }
+
+
+    /// Must be called while the counter is halted.
+    fn reset_counter(&self) {
+        self.write(MAIN_COUNTER, 0);
+    }
+
+
+    #[inline]
+    fn read(&self, offset: u64) -> u64 {
+        read_register(self.base, offset)
+    }
+
+
+    #[inline]
+    fn write(&self, offset: u64, value: u64) {
+        write_register(self.base, offset, value);
+    }
+}
+
+
+/// A comparator of the HPET, which fires an edge triggered interrupt
+/// when the main counter reaches its value.
+#[derive(Debug)]
+pub struct HpetComparator {
+    base: u64,
+    offset: u64,
+}
+
+
+impl HpetComparator {
+    #[inline]
+    pub fn supports_periodic(&self) -> bool {
+        self.config() & TN_PER_INT_CAP != 0
+    }
+
+
+    /// Returns the bitmap of the I/O APIC inputs the comparator can be routed to.
+    #[inline]
+    pub fn route_capabilities(&self) -> u32 {
+        (self.config() >> 32) as u32
+    }
+
+
+    /// Routes the interrupts to the I/O APIC input `gsi`.
+    ///
+    /// Ignored while the legacy replacement route is enabled for the comparators 0 and 1.
+    pub fn set_route(&self, gsi: u8) -> KernelResult {
+        if gsi >= 32 || self.route_capabilities() & (1 << gsi) == 0 {
+            return kernel_bail!("HPET comparator can't be routed to GSI {gsi}");
+        }
+
+        let config = (self.config() & !TN_INT_ROUTE_MASK) | (gsi as u64) << TN_INT_ROUTE_SHIFT;
+        self.set_config(config);
+        Ok(())
+    }
+
+
+    /// Fires an interrupt once after `counts` of the main counter.
+    pub fn start_one_shot(&self, counts: u64) {
+        let config = self.config() & !(TN_TYPE_CNF | TN_VAL_SET_CNF);
+        self.set_config(self.with_size(config) | TN_INT_ENB_CNF);
+        self.set_value(self.counter().wrapping_add(counts));
+    }
+
+
+    /// Fires an interrupt every `counts` of the main counter.
+    pub fn start_periodic(&self, counts: u64) -> KernelResult {
+        if !self.supports_periodic() {
+            return kernel_bail!("HPET comparator doesn't support the periodic mode");
+        }
+
+        let config = self.with_size(self.config()) | TN_INT_ENB_CNF | TN_TYPE_CNF;
+        // With VAL_SET, the first write sets the comparator value, which the hardware clears.
+        // The second write then goes to the accumulator, which is added on every interrupt.
+        self.set_config(config | TN_VAL_SET_CNF);
+        self.set_value(self.counter().wrapping_add(counts));
+        self.set_value(counts);
+        Ok(())
+    }
+
+
+    pub fn stop(&self) {
+        self.set_config(self.config() & !(TN_INT_ENB_CNF | TN_TYPE_CNF));
+    }
+
+
+    /// Uses the 32-bit mode if the comparator can't hold 64-bit values.
+    fn with_size(&self, config: u64) -> u64 {
+        if config & TN_SIZE_CAP == 0 {
+            config | TN_32MODE_CNF
+        } else {
+            config
+        }
+    }
+
+
+    #[inline]
+    fn counter(&self) -> u64 {
+        read_register(self.base, MAIN_COUNTER)
+    }
+
+
+    #[inline]
+    fn config(&self) -> u64 {
+        read_register(self.base, COMPARATOR_CONFIG + self.offset)
+    }
+
+
+    #[inline]
+    fn set_config(&self, config: u64) {
+        write_register(self.base, COMPARATOR_CONFIG + self.offset, config);
+    }
+
+
+    #[inline]
+    fn set_value(&self, value: u64) {
+        write_register(self.base, COMPARATOR_VALUE + self.offset, value);
+    }
+}
+
+
+#[inline]
+fn counts_to_nanos(counts: u64, period_femtos: u64) -> u64 {
+    let nanos = counts as u128 * period_femtos as u128 / FEMTOS_PER_NANO;
+    u64::try_from(nanos).unwrap_or(u64::MAX)
+}
+
+
+/// Converts the nanoseconds into counts, rounding up so that a timer never fires early.
+#[inline]
+fn nanos_to_counts(nanos: u64, period_femtos: u64) -> u64 {
+    let femtos = nanos as u128 * FEMTOS_PER_NANO;
+    let counts = (femtos + period_femtos as u128 - 1) / period_femtos as u128;
+    u64::try_from(counts).unwrap_or(u64::MAX)
+}
+
+
+#[inline]
+fn read_register(base: u64, offset: u64) -> u64 {
+    unsafe { ((base + offset) as *const u64).read_volatile() }
+}
+
+
+#[inline]
+fn write_register(base: u64, offset: u64, value: u64) {
+    unsafe { ((base + offset) as *mut u64).write_volatile(value) }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::timer::hpet::{counts_to_nanos, nanos_to_counts};
+
+    /// The period of the HPET emulated by QEMU, which runs at 100MHz.
+    const QEMU_PERIOD_FEMTOS: u64 = 10_000_000;
+
+    #[test]
+    fn it_counts_to_nanos() {
+        assert_eq!(counts_to_nanos(0, QEMU_PERIOD_FEMTOS), 0);
+        assert_eq!(counts_to_nanos(100_000_000, QEMU_PERIOD_FEMTOS), 1_000_000_000);
+        assert_eq!(counts_to_nanos(u64::MAX, 69_841_279), u64::MAX);
+    }
+
+
+    #[test]
+    fn it_nanos_to_counts_rounding_up() {
+        assert_eq!(nanos_to_counts(10_000_000, QEMU_PERIOD_FEMTOS), 1_000_000);
+        assert_eq!(nanos_to_counts(1, 69_841_279), 1);
+        assert_eq!(nanos_to_counts(70, 69_841_279), 2);
+    }
+}
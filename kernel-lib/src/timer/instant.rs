@@ -0,0 +1,153 @@
+use core::ops::{Add, AddAssign, Sub, SubAssign};
+use core::sync::atomic::{AtomicU64, Ordering};
+use core::time::Duration;
+
+use crate::timer::uptime_nanos;
+
+/// The latest instant handed out, so that [`Instant::now`] never goes backward
+/// even if the tick source is read just before its counter is reloaded.
+static LATEST_NANOS: AtomicU64 = AtomicU64::new(0);
+
+
+/// A point on the monotonic clock, which starts with the timer.
+///
+/// Only meaningful compared with other instants, like `std::time::Instant`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Instant(u64);
+
+
+impl Instant {
+    pub fn now() -> Self {
+        let nanos = uptime_nanos();
+        let latest = LATEST_NANOS.fetch_max(nanos, Ordering::Relaxed);
+
+        Self(nanos.max(latest))
+    }
+
+
+    #[inline]
+    pub const fn from_nanos(nanos: u64) -> Self {
+        Self(nanos)
+    }
+
+
+    /// Returns the nanoseconds since the timer started.
+    #[inline]
+    pub const fn as_nanos(&self) -> u64 {
+        self.0
+    }
+
+
+    #[inline]
+    pub fn elapsed(&self) -> Duration {
+        Self::now().duration_since(*self)
+    }
+
+
+    /// Returns the duration from `earlier` to this, or zero if `earlier` is later.
+    #[inline]
+    pub fn duration_since(&self, earlier: Instant) -> Duration {
+        self.checked_duration_since(earlier)
+            .unwrap_or_default()
+    }
+
+
+    #[inline]
+    pub fn checked_duration_since(&self, earlier: Instant) -> Option<Duration> {
+        self.0
+            .checked_sub(earlier.0)
+            .map(Duration::from_nanos)
+    }
+
+
+    #[inline]
+    pub fn checked_add(&self, duration: Duration) -> Option<Instant> {
+        let nanos = u64::try_from(duration.as_nanos()).ok()?;
+        self.0.checked_add(nanos).map(Self)
+    }
+
+
+    #[inline]
+    pub fn checked_sub(&self, duration: Duration) -> Option<Instant> {
+        let nanos = u64::try_from(duration.as_nanos()).ok()?;
+        self.0.checked_sub(nanos).map(Self)
+    }
+}
+
+
+impl Add<Duration> for Instant {
+    type Output = Instant;
+
+    fn add(self, rhs: Duration) -> Self::Output {
+        self.checked_add(rhs)
+            .expect("overflow when adding duration to instant")
+    }
+}
+
+
+impl AddAssign<Duration> for Instant {
+    fn add_assign(&mut self, rhs: Duration) {
+        *self = *self + rhs;
+    }
+}
+
+
+impl Sub<Duration> for Instant {
+    type Output = Instant;
+
+    fn sub(self, rhs: Duration) -> Self::Output {
+        self.checked_sub(rhs)
+            .expect("overflow when subtracting duration from instant")
+    }
+}
+
+
+impl SubAssign<Duration> for Instant {
+    fn sub_assign(&mut self, rhs: Duration) {
+        *self = *self - rhs;
+    }
+}
+
+
+impl Sub<Instant> for Instant {
+    type Output = Duration;
+
+    fn sub(self, rhs: Instant) -> Self::Output {
+        self.duration_since(rhs)
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use core::time::Duration;
+
+    use crate::timer::instant::Instant;
+
+    #[test]
+    fn it_measure_duration_between_instants() {
+        let earlier = Instant::from_nanos(1_000);
+        let later = earlier + Duration::from_micros(5);
+
+        assert_eq!(later.as_nanos(), 6_000);
+        assert_eq!(later - earlier, Duration::from_micros(5));
+        assert_eq!(earlier - later, Duration::ZERO);
+        assert_eq!(earlier.checked_duration_since(later), None);
+    }
+
+
+    #[test]
+    fn it_check_overflow() {
+        assert_eq!(Instant::from_nanos(u64::MAX).checked_add(Duration::from_nanos(1)), None);
+        assert_eq!(Instant::from_nanos(0).checked_sub(Duration::from_nanos(1)), None);
+        assert_eq!(Instant::from_nanos(0).checked_add(Duration::MAX), None);
+    }
+
+
+    #[test]
+    fn it_never_go_backward() {
+        let first = Instant::now();
+
+        assert!(first <= Instant::now());
+    }
+}
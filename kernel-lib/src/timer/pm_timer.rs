@@ -0,0 +1,69 @@
+use crate::io::asm::io_in32;
+
+/// The frequency of the ACPI power management timer in Hz.
+pub const PM_TIMER_FREQ: u64 = 3_579_545;
+
+
+/// The ACPI power management timer, a free running 24-bit or 32-bit counter on an I/O port.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct PmTimer {
+    port: u16,
+    is_32bit: bool,
+}
+
+
+impl PmTimer {
+    #[inline]
+    pub const fn new(port: u16, is_32bit: bool) -> Self {
+        Self { port, is_32bit }
+    }
+
+
+    #[inline]
+    pub fn count(&self) -> u32 {
+        io_in32(self.port) & self.mask()
+    }
+
+
+    /// Returns the counts from `start` to `end`, which wrap around at the width of the counter.
+    #[inline]
+    pub const fn counts_between(&self, start: u32, end: u32) -> u32 {
+        end.wrapping_sub(start) & self.mask()
+    }
+
+
+    #[inline]
+    pub const fn counts_to_nanos(counts: u32) -> u64 {
+        counts as u64 * 1_000_000_000 / PM_TIMER_FREQ
+    }
+
+
+    #[inline]
+    const fn mask(&self) -> u32 {
+        if self.is_32bit {
+            u32::MAX
+        } else {
+            0x00FF_FFFF
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use crate::timer::pm_timer::{PmTimer, PM_TIMER_FREQ};
+
+    #[test]
+    fn it_counts_between_wrapping_24bit() {
+        let timer = PmTimer::new(0, false);
+
+        assert_eq!(timer.counts_between(0x10, 0x20), 0x10);
+        assert_eq!(timer.counts_between(0xFF_FFF0, 0x10), 0x20);
+    }
+
+
+    #[test]
+    fn it_counts_to_nanos() {
+        assert_eq!(PmTimer::counts_to_nanos(PM_TIMER_FREQ as u32), 1_000_000_000);
+    }
+}
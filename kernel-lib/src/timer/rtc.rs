@@ -0,0 +1,296 @@
+use core::fmt::{Display, Formatter};
+use core::sync::atomic::{AtomicU64, Ordering};
+
+use crate::interrupt::asm::without_interrupt;
+use crate::io::asm::{io_in8, io_out8};
+use crate::timer::instant::Instant;
+
+const CMOS_ADDRESS: u16 = 0x70;
+const CMOS_DATA: u16 = 0x71;
+
+const RTC_SECOND: u8 = 0x00;
+const RTC_MINUTE: u8 = 0x02;
+const RTC_HOUR: u8 = 0x04;
+const RTC_DAY: u8 = 0x07;
+const RTC_MONTH: u8 = 0x08;
+const RTC_YEAR: u8 = 0x09;
+const RTC_STATUS_A: u8 = 0x0A;
+const RTC_STATUS_B: u8 = 0x0B;
+
+/// Set in the status register A while the RTC updates the time registers.
+const UPDATE_IN_PROGRESS: u8 = 0x80;
+/// Set in the status register B if the hours run from 0 to 23.
+const HOUR_24: u8 = 0x02;
+/// Set in the status register B if the registers are in binary rather than BCD.
+const BINARY_MODE: u8 = 0x04;
+/// Set in the hour register for the afternoon in 12-hour mode.
+const HOUR_PM: u8 = 0x80;
+
+/// The RTC keeps only two digits of the year.
+const CENTURY: u16 = 2000;
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// The date and time read from the RTC at [`init_wall_clock`], in seconds since the Unix epoch.
+static BOOT_UNIX_SECONDS: AtomicU64 = AtomicU64::new(0);
+
+/// The instant [`BOOT_UNIX_SECONDS`] was read, in nanoseconds.
+static BOOT_INSTANT_NANOS: AtomicU64 = AtomicU64::new(0);
+
+
+/// A date and time without the time zone, which is the one the RTC is set to.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd)]
+pub struct DateTime {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+}
+
+
+impl DateTime {
+    pub fn from_unix_seconds(seconds: u64) -> Self {
+        let (year, month, day) = civil_from_days(seconds / SECONDS_PER_DAY);
+        let seconds_of_day = seconds % SECONDS_PER_DAY;
+
+        Self {
+            year,
+            month,
+            day,
+            hour: (seconds_of_day / 3600) as u8,
+            minute: (seconds_of_day / 60 % 60) as u8,
+            second: (seconds_of_day % 60) as u8,
+        }
+    }
+
+
+    /// Returns the seconds since 1970-01-01 00:00:00, or zero for the dates before it.
+    pub fn to_unix_seconds(&self) -> u64 {
+        days_from_civil(self.year, self.month, self.day) * SECONDS_PER_DAY
+            + self.hour as u64 * 3600
+            + self.minute as u64 * 60
+            + self.second as u64
+    }
+}
+
+
+impl Display for DateTime {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+            self.year, self.month, self.day, self.hour, self.minute, self.second
+        )
+    }
+}
+
+
+/// Reads the RTC once, so that [`now`] can follow it with the monotonic clock.
+///
+/// Must be called after the timer starts.
+pub fn init_wall_clock() {
+    let date_time = read_rtc();
+
+    BOOT_INSTANT_NANOS.store(Instant::now().as_nanos(), Ordering::Relaxed);
+    BOOT_UNIX_SECONDS.store(date_time.to_unix_seconds(), Ordering::Relaxed);
+}
+
+
+/// Returns the current date and time, or the ones since the Unix epoch
+/// if [`init_wall_clock`] has not been called.
+pub fn now() -> DateTime {
+    let boot_instant = Instant::from_nanos(BOOT_INSTANT_NANOS.load(Ordering::Relaxed));
+    let seconds = BOOT_UNIX_SECONDS.load(Ordering::Relaxed) + boot_instant.elapsed().as_secs();
+
+    DateTime::from_unix_seconds(seconds)
+}
+
+
+/// Reads the date and time from the CMOS RTC.
+///
+/// The registers are read until two reads match, since they may change in the middle of a read.
+pub fn read_rtc() -> DateTime {
+    without_interrupt(|| {
+        let mut registers = read_registers();
+        loop {
+            let again = read_registers();
+            if again == registers {
+                return registers.decode();
+            }
+            registers = again;
+        }
+    })
+}
+
+
+/// The raw values of the time registers, which may be in BCD and in 12-hour mode.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+struct RtcRegisters {
+    second: u8,
+    minute: u8,
+    hour: u8,
+    day: u8,
+    month: u8,
+    year: u8,
+    status_b: u8,
+}
+
+
+impl RtcRegisters {
+    fn decode(&self) -> DateTime {
+        let decode = |value: u8| {
+            if self.status_b & BINARY_MODE != 0 {
+                value
+            } else {
+                (value >> 4) * 10 + (value & 0x0F)
+            }
+        };
+
+        let is_pm = self.hour & HOUR_PM != 0;
+        let mut hour = decode(self.hour & !HOUR_PM);
+        if self.status_b & HOUR_24 == 0 {
+            // 12 AM is midnight and 12 PM is noon.
+            hour = hour % 12 + if is_pm { 12 } else { 0 };
+        }
+
+        DateTime {
+            year: CENTURY + decode(self.year) as u16,
+            month: decode(self.month),
+            day: decode(self.day),
+            hour,
+            minute: decode(self.minute),
+            second: decode(self.second),
+        }
+    }
+}
+
+
+fn read_registers() -> RtcRegisters {
+    while read_cmos(RTC_STATUS_A) & UPDATE_IN_PROGRESS != 0 {
+        core::hint::spin_loop();
+    }
+
+    RtcRegisters {
+        second: read_cmos(RTC_SECOND),
+        minute: read_cmos(RTC_MINUTE),
+        hour: read_cmos(RTC_HOUR),
+        day: read_cmos(RTC_DAY),
+        month: read_cmos(RTC_MONTH),
+        year: read_cmos(RTC_YEAR),
+        status_b: read_cmos(RTC_STATUS_B),
+    }
+}
+
+
+#[inline]
+fn read_cmos(register: u8) -> u8 {
+    io_out8(CMOS_ADDRESS, register);
+    io_in8(CMOS_DATA)
+}
+
+
+/// Returns the days since 1970-01-01 of the date in the proleptic Gregorian calendar.
+fn days_from_civil(year: u16, month: u8, day: u8) -> u64 {
+    let year = year as i64 - if month <= 2 { 1 } else { 0 };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month_from_march = (month as i64 + 9) % 12;
+    let day_of_year = (153 * month_from_march + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    u64::try_from(era * 146_097 + day_of_era - 719_468).unwrap_or(0)
+}
+
+
+/// The inverse of [`days_from_civil`].
+fn civil_from_days(days: u64) -> (u16, u8, u8) {
+    let days = days + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_from_march = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_from_march + 2) / 5 + 1;
+    let month = if month_from_march < 10 {
+        month_from_march + 3
+    } else {
+        month_from_march - 9
+    };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    (year as u16, month as u8, day as u8)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+
+    use crate::timer::rtc::{DateTime, RtcRegisters, BINARY_MODE, HOUR_24, HOUR_PM};
+
+    const DATE_TIME: DateTime = DateTime {
+        year: 2023,
+        month: 7,
+        day: 16,
+        hour: 21,
+        minute: 5,
+        second: 9,
+    };
+
+    #[test]
+    fn it_decode_bcd_24_hour() {
+        let registers = RtcRegisters {
+            second: 0x09,
+            minute: 0x05,
+            hour: 0x21,
+            day: 0x16,
+            month: 0x07,
+            year: 0x23,
+            status_b: HOUR_24,
+        };
+
+        assert_eq!(registers.decode(), DATE_TIME);
+    }
+
+
+    #[test]
+    fn it_decode_binary_12_hour() {
+        let registers = RtcRegisters {
+            second: 9,
+            minute: 5,
+            hour: HOUR_PM | 9,
+            day: 16,
+            month: 7,
+            year: 23,
+            status_b: BINARY_MODE,
+        };
+        assert_eq!(registers.decode(), DATE_TIME);
+
+        let midnight = RtcRegisters {
+            hour: 12,
+            ..registers
+        };
+        assert_eq!(midnight.decode().hour, 0);
+    }
+
+
+    #[test]
+    fn it_convert_unix_seconds() {
+        assert_eq!(DateTime::from_unix_seconds(0).to_string(), "1970-01-01 00:00:00");
+        assert_eq!(DATE_TIME.to_unix_seconds(), 1_689_541_509);
+        assert_eq!(DateTime::from_unix_seconds(1_689_541_509), DATE_TIME);
+
+        let leap_day = DateTime {
+            year: 2024,
+            month: 2,
+            day: 29,
+            hour: 23,
+            minute: 59,
+            second: 59,
+        };
+        assert_eq!(DateTime::from_unix_seconds(leap_day.to_unix_seconds()), leap_day);
+    }
+}
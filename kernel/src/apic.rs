@@ -1,30 +1,96 @@
-use core::ffi::c_void;
-
 use kernel_lib::acpi;
+use kernel_lib::acpi::fadt::Fadt;
 use kernel_lib::apic::device_config::LocalApicTimerDivide;
+use kernel_lib::apic::io_apic::{init_io_apics, route_irq, IRQ_PIT};
+use kernel_lib::apic::{set_local_apic_base, LocalApicRegisters};
 use kernel_lib::error::KernelResult;
+use kernel_lib::interrupt::interrupt_vector::InterruptVector;
 use kernel_lib::timer::apic::local_apic_timer::LocalApicTimer;
 use kernel_lib::timer::apic::ApicTimer;
+use kernel_lib::timer::hpet::{hpet, init_hpet};
+use kernel_lib::timer::{TickSource, TICK_NANOS, TIMER_FREQ};
+use kernel_lib::volatile_bits::VolatileBitsReadable;
+use kernel_lib::{kernel_bail, kernel_error, serial_println, timer};
+
+/// The time the local APIC timer runs against the PM timer to measure its frequency.
+const CALIBRATION_MILLIS: u32 = 100;
+
+
+/// Reads the interrupt controllers from the MADT and masks all the legacy IRQs.
+///
+/// Must be called before the local APIC is used.
+pub fn init_apic() -> KernelResult {
+    let madt = acpi::find_madt()?;
+    set_local_apic_base(madt.local_apic_address());
+
+    init_io_apics(&madt)
+}
+
+
+/// Starts firing [`TIMER_FREQ`] ticks per second with `source`,
+/// falling back to the local APIC timer if the HPET can't be used.
+///
+/// Returns the source actually selected.
+pub fn start_timer(source: TickSource) -> KernelResult<TickSource> {
+    let fadt = acpi::find_fadt()?;
+    timer::init_pm_timer(fadt.pm_timer());
 
-pub const TIMER_FREQ: u32 = 100;
+    if source == TickSource::Hpet {
+        match start_hpet_timer() {
+            Ok(()) => {
+                timer::set_tick_source(TickSource::Hpet)?;
+                return Ok(TickSource::Hpet);
+            }
+            Err(e) => serial_println!("Falling back to the local APIC timer: {:?}", e),
+        }
+    }
 
-pub const TIMER_200_MILLI_INTERVAL: usize = 2;
+    start_local_apic_timer(&fadt);
+
+    let source = if source == TickSource::PmTimer {
+        TickSource::PmTimer
+    } else {
+        TickSource::LocalApic
+    };
+    timer::set_tick_source(source)?;
+    Ok(source)
+}
 
 
-pub fn start_timer(rsdp: Option<*const c_void>, timer_freq_milli: u32) -> KernelResult<()> {
-    let fadt = acpi::init_acpi_timer(rsdp)?;
+/// Fires the ticks with the comparator 0 of the HPET, delivered as IRQ 0 to this CPU.
+fn start_hpet_timer() -> KernelResult {
+    init_hpet(acpi::find_hpet()?.base_address())?;
+    let hpet = hpet().ok_or(kernel_error!("HPET is not initialized"))?;
+    if !hpet.is_64bit() {
+        return kernel_bail!("HPET counter is not 64-bit");
+    }
+
+    hpet.enable_legacy_replacement()?;
+    hpet.comparator(0)?
+        .start_periodic(hpet.nanos_to_counts(TICK_NANOS))?;
+
+    let bsp_local_apic_id: u8 = LocalApicRegisters::default()
+        .local_apic_id()
+        .read_volatile();
+    route_irq(IRQ_PIT, InterruptVector::HpetTimer.cast(), bsp_local_apic_id)?;
+
+    hpet.enable();
+    Ok(())
+}
+
+
+/// Measures the frequency of the local APIC timer with the PM timer, then starts it.
+fn start_local_apic_timer(fadt: &Fadt) {
     let mut apic_timer = LocalApicTimer::new();
 
     apic_timer.start(u32::MAX, LocalApicTimerDivide::By1);
-    fadt.wait_milli_for(timer_freq_milli);
+    fadt.wait_milli_for(CALIBRATION_MILLIS);
     let elapsed = apic_timer.elapsed();
     apic_timer.stop();
 
-    let local_apic_timer_freq = elapsed * 10;
+    let local_apic_timer_freq = elapsed * (1000 / CALIBRATION_MILLIS);
 
     let initial_count = local_apic_timer_freq / TIMER_FREQ;
 
     apic_timer.start(initial_count, LocalApicTimerDivide::By1);
-
-    Ok(())
 }
@@ -29,13 +29,17 @@ macro_rules! kernel_entry_point {
             frame_buffer_config: &common_lib::frame_buffer::FrameBufferConfig,
             memory_map: &uefi::table::boot::MemoryMapIter<'static>,
             rsdp: &Option<*const core::ffi::c_void>,
-            fat_volume: *mut u8
+            fat_volume: *mut u8,
+            kernel_elf: *const u8,
+            kernel_elf_len: usize
         ){
             let kernel_stack_end_addr = KERNEL_STACK.end_addr();
 
             unsafe {
                 core::arch::asm!(
                     "mov rsp, {0}",
+                    // Terminates the chain of frame pointers for backtraces.
+                    "xor rbp, rbp",
                     "call kernel_main",
 
                     in(reg) kernel_stack_end_addr,
@@ -43,6 +47,8 @@ macro_rules! kernel_entry_point {
                     in("esi") memory_map,
                     in("edx") rsdp,
                     in("rcx") fat_volume,
+                    in("r8") kernel_elf,
+                    in("r9") kernel_elf_len,
                     clobber_abi("sysv64")
                 )
             }
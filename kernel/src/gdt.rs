@@ -2,11 +2,16 @@ use core::arch::asm;
 
 use spin::Mutex;
 use x86_64::instructions::segmentation::{Segment, CS};
+use x86_64::instructions::tables::load_tss;
 use x86_64::registers::segmentation::SS;
 use x86_64::structures::gdt::{Descriptor, GlobalDescriptorTable};
 
 use kernel_lib::interrupt::asm::cli;
 use kernel_lib::segmentation::asm::{read_code_segment, read_stack_segment};
+use kernel_lib::segmentation::tss::{init_interrupt_stack_table, tss};
+use kernel_lib::segmentation::{
+    KERNEL_CODE_SEGMENT, KERNEL_DATA_SEGMENT, TSS_SEGMENT, USER_CODE_SEGMENT, USER_DATA_SEGMENT,
+};
 
 static mut GDT: Mutex<GlobalDescriptorTable> = Mutex::new(GlobalDescriptorTable::new());
 
@@ -23,6 +28,15 @@ unsafe fn init_gdt_unsafe() {
     let stack_segment = GDT
         .lock()
         .add_entry(Descriptor::kernel_data_segment());
+    let user_data_segment = GDT
+        .lock()
+        .add_entry(Descriptor::user_data_segment());
+    let user_code_segment = GDT
+        .lock()
+        .add_entry(Descriptor::user_code_segment());
+    let tss_segment = GDT
+        .lock()
+        .add_entry(Descriptor::tss_segment(tss()));
 
     GDT.get_mut().load();
     CS::set_reg(code_segment);
@@ -36,6 +50,14 @@ unsafe fn init_gdt_unsafe() {
         in(reg) x,
         options(nostack, preserves_flags)
     );
+    init_interrupt_stack_table();
+    load_tss(tss_segment);
+
     assert_eq!(read_code_segment(), code_segment.0);
     assert_eq!(read_stack_segment(), stack_segment.0);
+    assert_eq!(code_segment.0, KERNEL_CODE_SEGMENT);
+    assert_eq!(stack_segment.0, KERNEL_DATA_SEGMENT);
+    assert_eq!(user_data_segment.0, USER_DATA_SEGMENT);
+    assert_eq!(user_code_segment.0, USER_CODE_SEGMENT);
+    assert_eq!(tss_segment.0, TSS_SEGMENT);
 }
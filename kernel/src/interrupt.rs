@@ -1,15 +1,19 @@
 use kernel_lib::error::KernelResult;
+use kernel_lib::interrupt::exception::{init_exception_handlers, set_exception_hook};
 use kernel_lib::interrupt::gate_type::GateType;
 use kernel_lib::interrupt::interrupt_descriptor_attribute::InterruptDescriptorAttribute;
 use kernel_lib::interrupt::interrupt_vector::InterruptVector;
 use kernel_lib::interrupt::IDT;
+use kernel_lib::segmentation::tss::DOUBLE_FAULT_IST_INDEX;
 
+use crate::interrupt::double_fault::double_fault_hook;
 use crate::interrupt::overflow::interrupt_overflow;
-use crate::interrupt::page_fault::page_fault_handler;
+use crate::interrupt::page_fault::page_fault_hook;
 use crate::interrupt::timer::interrupt_timer_handler;
 
 use self::xhci::interrupt_xhci_handler;
 
+mod double_fault;
 mod overflow;
 mod page_fault;
 pub mod timer;
@@ -22,11 +26,16 @@ pub fn init_idt() -> KernelResult {
             .with_gate_type(GateType::InterruptGate)
             .with_present(true);
 
+        init_exception_handlers(type_attribute)?;
         IDT[InterruptVector::Overflow].set_handler(interrupt_overflow, type_attribute)?;
-        IDT[InterruptVector::PageFault]
-            .set_page_fault_handler(page_fault_handler, type_attribute)?;
+        // Page faults and double faults go through the entry points saving all the registers,
+        // so that the fatal ones are dumped like the other exceptions.
+        IDT[InterruptVector::DoubleFault].set_interrupt_stack_table_offset(DOUBLE_FAULT_IST_INDEX);
+        set_exception_hook(InterruptVector::DoubleFault, double_fault_hook);
+        set_exception_hook(InterruptVector::PageFault, page_fault_hook);
         IDT[InterruptVector::Xhci].set_handler(interrupt_xhci_handler, type_attribute)?;
         IDT[InterruptVector::ApicTimer].set_handler(interrupt_timer_handler, type_attribute)?;
+        IDT[InterruptVector::HpetTimer].set_handler(interrupt_timer_handler, type_attribute)?;
         IDT.load();
     }
 
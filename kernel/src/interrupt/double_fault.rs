@@ -0,0 +1,16 @@
+use x86_64::registers::control::Cr2;
+
+use kernel_lib::interrupt::exception::ExceptionFrame;
+
+use crate::interrupt::page_fault::kill_on_stack_overflow;
+
+/// Kills the task whose stack overflowed, and leaves any other double fault
+/// to the register dump of the default handling.
+///
+/// Runs on its own stack, since a stack overflow can't push the page fault frame
+/// onto the overflowing stack and ends up here.
+pub(crate) fn double_fault_hook(frame: &ExceptionFrame) -> bool {
+    kill_on_stack_overflow(Cr2::read().as_u64(), &frame.stack_frame);
+
+    false
+}
@@ -1,19 +1,95 @@
-use x86_64::structures::idt::{InterruptStackFrame, PageFaultErrorCode};
+use x86_64::registers::control::Cr2;
+use x86_64::structures::idt::{InterruptStackFrameValue, PageFaultErrorCode};
 
-use kernel_lib::interrupt::asm::cli;
+use kernel_lib::interrupt::exception::ExceptionFrame;
+use kernel_lib::paging::address_space::USER_SPACE_START;
 use kernel_lib::serial_println;
+use kernel_lib::task;
+use kernel_lib::task::app::{exit_app, EXIT_CODE_SEGMENTATION_FAULT};
+use kernel_lib::task::TASK_MANAGER;
 
-pub extern "x86-interrupt" fn page_fault_handler(
-    stack_frame: InterruptStackFrame,
-    error_code: PageFaultErrorCode,
-) {
-    use x86_64::registers::control::Cr2;
-    cli();
-    serial_println!("EXCEPTION: PAGE FAULT");
-    serial_println!("Accessed Address: {:?}", Cr2::read());
-    serial_println!("Error Code: {:?}", error_code);
+use crate::syscall::release_app_resources;
+
+/// Maps the page on demand or kills the faulting application.
+///
+/// Any other fault is left to the register dump of the default handling.
+pub(crate) fn page_fault_hook(frame: &ExceptionFrame) -> bool {
+    let addr = Cr2::read().as_u64();
+    kill_on_stack_overflow(addr, &frame.stack_frame);
+
+    let error_code = PageFaultErrorCode::from_bits_truncate(frame.error_code);
+    let write = error_code.contains(PageFaultErrorCode::CAUSED_BY_WRITE);
+
+    if !error_code.contains(PageFaultErrorCode::PROTECTION_VIOLATION)
+        && unsafe { TASK_MANAGER.map_on_demand(addr, write) }.is_ok()
+    {
+        return true;
+    }
+
+    // Either the application itself or a system call on behalf of it accessed invalid memory.
+    // Faults on any other task are bugs in the kernel.
+    if error_code.contains(PageFaultErrorCode::USER_MODE) || USER_SPACE_START <= addr {
+        if let Some(task_id) = running_app_id() {
+            kill_app(task_id, addr, error_code, &frame.stack_frame);
+        }
+    }
 
+    false
+}
+
+
+/// Kills the running task if `addr` is in the guard page of its stack.
+///
+/// The main task can't be killed, so the kernel halts instead,
+/// as it does if killing the task fails.
+pub(crate) fn kill_on_stack_overflow(addr: u64, stack_frame: &InterruptStackFrameValue) {
+    let Some(task_id) = (unsafe { TASK_MANAGER.stack_guard_owner(addr) }) else {
+        return;
+    };
+    if task::current_id().ok() != Some(task_id) {
+        return;
+    }
+
+    serial_println!("Stack overflow in task {}: address=0x{:X}", task_id, addr);
     serial_println!("{:?}", stack_frame);
 
+    if task_id == 0 {
+        common_lib::assembly::hlt_forever();
+    }
+
+    // Guard pages only exist on kernel task stacks, so no application resources are held.
+    // This may run on the stack of the double fault handler,
+    // so the task must exit without allocating or waiting on preemptive locks.
+    let result = unsafe { TASK_MANAGER.abort_running(EXIT_CODE_SEGMENTATION_FAULT) };
+
+    // Aborting the running task switches to another task and never returns on success.
+    serial_println!("Failed to kill task {}: {:?}", task_id, result);
     common_lib::assembly::hlt_forever();
 }
+
+
+/// Returns the id of the running task if it runs an application in its own address space.
+fn running_app_id() -> Option<u64> {
+    task::current_id()
+        .ok()
+        .filter(|task_id| unsafe { TASK_MANAGER.is_app(*task_id) })
+}
+
+
+fn kill_app(
+    task_id: u64,
+    addr: u64,
+    error_code: PageFaultErrorCode,
+    stack_frame: &InterruptStackFrameValue,
+) -> ! {
+    serial_println!(
+        "Segmentation fault in task {}: address=0x{:X} error={:?}",
+        task_id,
+        addr,
+        error_code
+    );
+    serial_println!("{:?}", stack_frame);
+
+    release_app_resources(task_id);
+    unsafe { exit_app(EXIT_CODE_SEGMENTATION_FAULT) }
+}
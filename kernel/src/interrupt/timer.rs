@@ -1,12 +1,21 @@
 use x86_64::structures::idt::InterruptStackFrame;
 
 use kernel_lib::apic::LocalApicRegisters;
-use kernel_lib::timer::TIME_HANDLE_MANAGER;
+use kernel_lib::task::app::exit_app_if_kill_requested;
+use kernel_lib::task::TASK_MANAGER;
+use kernel_lib::timer;
 
-pub extern "x86-interrupt" fn interrupt_timer_handler(_stack_frame: InterruptStackFrame) {
+pub extern "x86-interrupt" fn interrupt_timer_handler(stack_frame: InterruptStackFrame) {
     LocalApicRegisters::default()
         .end_of_interrupt()
         .notify();
 
-    TIME_HANDLE_MANAGER.tick();
+    timer::tick();
+    // May switch to another task, so the timers are handled first.
+    unsafe { TASK_MANAGER.tick() };
+
+    // An application preempted in ring 3 holds no kernel locks, so it can exit here if killed.
+    if stack_frame.code_segment & 3 == 3 {
+        unsafe { exit_app_if_kill_requested() };
+    }
 }
@@ -6,17 +6,21 @@ use kernel_lib::error::KernelResult;
 use kernel_lib::gop;
 use kernel_lib::layers::LAYERS;
 
+use crate::layers::clock::clock_window;
 use crate::layers::console::console;
 use crate::layers::desktop::desktop;
 use crate::layers::mouse::mouse;
 use crate::layers::time_count::time_count_window;
+use crate::layers::top::top_window;
 use crate::layers::window_keyboard::window_keyboard;
 
+pub(crate) mod clock;
 mod console;
 mod desktop;
 mod mouse;
 mod terminal;
 mod time_count;
+pub(crate) mod top;
 mod window_keyboard;
 
 
@@ -28,6 +32,10 @@ pub const KEYBOARD_TEXT: &str = "WINDOW TEXT";
 pub const MOUSE_LAYER_KEY: &str = "MOUSE_CURSOR";
 pub const CONSOLE_LAYER_KEY: &str = "CONSOLE";
 pub const TERMINAL_LAYER_KEY: &str = "Terminal";
+pub const TOP_WINDOW_LAYER_KEY: &str = "Top Window";
+pub const TOP_TEXT_LAYER_KEY: &str = "TOP";
+pub const CLOCK_WINDOW_LAYER_KEY: &str = "Clock Window";
+pub const CLOCK_TEXT_LAYER_KEY: &str = "CLOCK";
 
 
 pub fn init_layers(config: FrameBufferConfig) -> KernelResult {
@@ -51,7 +59,9 @@ pub fn init_layers(config: FrameBufferConfig) -> KernelResult {
         COUNT_TEXT_LAYER2_KEY,
         "Count Window 2",
     )?);
+    layers.new_layer(clock_window()?);
     layers.new_layer(window_keyboard()?);
+    layers.new_layer(top_window()?);
     layers.new_layer(terminal::terminal());
     layers.new_layer(mouse(config));
 
@@ -74,6 +84,21 @@ pub fn _print(args: core::fmt::Arguments) {
 }
 
 
+/// Prints the message to the terminal above the line being edited.
+pub(crate) fn print_terminal(message: &str) {
+    LAYERS
+        .lock()
+        .update_layer(TERMINAL_LAYER_KEY, |layer| {
+            layer
+                .require_terminal()
+                .unwrap()
+                .print(message)
+                .unwrap();
+        })
+        .unwrap();
+}
+
+
 #[macro_export]
 macro_rules! print {
     ($($args:tt)*) => ($crate::layers::_print(format_args!($($args)*)));
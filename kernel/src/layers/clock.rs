@@ -0,0 +1,60 @@
+use alloc::string::ToString;
+
+use common_lib::math::size::Size;
+use common_lib::math::vector::Vector2D;
+use common_lib::transform::transform2d::Transform2D;
+use kernel_lib::error::KernelResult;
+use kernel_lib::gop;
+use kernel_lib::gop::pixel::pixel_color::PixelColor;
+use kernel_lib::layers::layer_key::LayerKey;
+use kernel_lib::layers::text::{config, TextLayer};
+use kernel_lib::layers::window::WindowLayer;
+use kernel_lib::layers::LAYERS;
+use kernel_lib::timer::rtc;
+
+use crate::layers::{CLOCK_TEXT_LAYER_KEY, CLOCK_WINDOW_LAYER_KEY};
+
+/// The length of `YYYY-MM-DD hh:mm:ss`.
+const COLUMNS: usize = 19;
+
+
+pub(crate) fn clock_window() -> KernelResult<LayerKey> {
+    let pos = Vector2D::new(300, 100);
+    let size = Size::new(COLUMNS * 8 + 6, 16 + 24 + 10);
+    let transform = Transform2D::new(pos, size);
+
+    Ok(WindowLayer::new_default_color("Clock", transform)
+        .then_add(|_| clock_text())?
+        .into_enum()
+        .into_layer_key(CLOCK_WINDOW_LAYER_KEY))
+}
+
+
+/// Redraws the window with the current date and time.
+pub(crate) fn update_clock() {
+    let text = rtc::now().to_string();
+
+    LAYERS
+        .lock()
+        .update_layer(CLOCK_TEXT_LAYER_KEY, |layer| {
+            layer
+                .require_text()
+                .unwrap()
+                .replace(&text)
+                .unwrap();
+        })
+        .unwrap();
+}
+
+
+fn clock_text() -> LayerKey {
+    let config = config::Builder::new()
+        .foreground(PixelColor::black())
+        .background(PixelColor::white())
+        .build();
+
+    TextLayer::new(gop::config(), Vector2D::zeros(), Size::new(COLUMNS, 1), config)
+        .unwrap()
+        .into_enum()
+        .into_layer_key(CLOCK_TEXT_LAYER_KEY)
+}
@@ -1,30 +1,57 @@
 use alloc::format;
+use alloc::rc::Rc;
 use alloc::string::{String, ToString};
+use core::cell::RefCell;
 
 use common_lib::math::size::Size;
 use common_lib::math::vector::Vector2D;
 use common_lib::transform::transform2d::Transform2D;
+use kernel_lib::fs::path::Path;
 use kernel_lib::layers::layer_key::LayerKey;
 use kernel_lib::layers::terminal::TerminalLayer;
 use kernel_lib::layers::text::command::{Command, CommandAction, CommandArgs, CommandResult};
 use kernel_lib::layers::text::config;
-use kernel_lib::simple_fat::dir::entry::short::ShortDirEntryReadable;
-use kernel_lib::{fs, task};
+use kernel_lib::task;
+use kernel_lib::timer::rtc;
 use pci::pci_device_searcher::PciDeviceSearcher;
 
+use crate::allocate::memory_stats;
+use crate::layers::terminal::acpi::{acpitables, reboot, shutdown};
+use crate::layers::terminal::app::{run, run_if_exists};
+use crate::layers::terminal::file::{cat, cd, hexdump, ls, pwd, stat};
 use crate::layers::TERMINAL_LAYER_KEY;
 
+mod acpi;
+mod app;
+mod file;
+
 pub(crate) fn terminal() -> LayerKey {
     let pos = Vector2D::new(100, 200);
     let size = Size::new(500, 16 * 20 + 10 + 17);
     let transform = Transform2D::new(pos, size);
+    let cwd = Rc::new(RefCell::new(Path::root()));
     let config = config::Builder::terminal()
         .add_command(Command::new("echo", echo))
         .add_command(Command::new("clear", clear))
         .add_command(Command::new("lspci", lspci))
         .add_command(Command::new("sleep", sleep))
         .add_command(Command::new("wakeup", wakeup))
-        .add_command(Command::new("ls", ls))
+        .add_command(Command::new("kill", kill))
+        .add_command(Command::new("ps", ps))
+        .add_command(Command::new("free", meminfo))
+        .add_command(Command::new("meminfo", meminfo))
+        .add_command(Command::new("acpitables", acpitables))
+        .add_command(Command::new("date", date))
+        .add_command(Command::new("shutdown", shutdown))
+        .add_command(Command::new("reboot", reboot))
+        .add_command(Command::new("ls", with_cwd(&cwd, ls)))
+        .add_command(Command::new("cd", with_cwd(&cwd, cd)))
+        .add_command(Command::new("pwd", with_cwd(&cwd, pwd)))
+        .add_command(Command::new("cat", with_cwd(&cwd, cat)))
+        .add_command(Command::new("hexdump", with_cwd(&cwd, hexdump)))
+        .add_command(Command::new("stat", with_cwd(&cwd, stat)))
+        .add_command(Command::new("run", with_cwd(&cwd, run)))
+        .fallback(Command::new("run", with_cwd(&cwd, run_if_exists)))
         .build();
 
     TerminalLayer::new(transform, config)
@@ -84,6 +111,51 @@ fn wakeup(args: CommandArgs) -> CommandResult {
 }
 
 
+fn ps(_args: CommandArgs) -> CommandResult {
+    let mut output = format!(
+        "{:>4} {:>3} {:<8} {:>8} {:>4} NAME",
+        "ID", "PRI", "STATUS", "TICKS", "MSGS"
+    );
+    for task in task::tasks() {
+        output.push_str(&format!(
+            "\n{:>4} {:>3} {:<8} {:>8} {:>4} {}",
+            task.id,
+            task.priority_level.value(),
+            task.status,
+            task.cpu_ticks,
+            task.pending_messages,
+            task.name
+        ));
+    }
+
+    Ok(CommandAction::output(output))
+}
+
+
+fn kill(args: CommandArgs) -> CommandResult {
+    let task_id = parse_task_id(args)?;
+    // Kernel tasks may hold locks or devices that killing them would never release.
+    if !task::is_app(task_id) {
+        return Err(format!("Task {task_id} is not an application"));
+    }
+
+    // The application releases its resources when it exits at its next safe point.
+    task::kill(task_id).map_err(|e| format!("{e:?}"))?;
+
+    Ok(CommandAction::output(format!("killing {task_id}")))
+}
+
+
+fn meminfo(_args: CommandArgs) -> CommandResult {
+    Ok(CommandAction::output(memory_stats().to_string()))
+}
+
+
+fn date(_args: CommandArgs) -> CommandResult {
+    Ok(CommandAction::output(rtc::now().to_string()))
+}
+
+
 fn parse_task_id(args: CommandArgs) -> Result<u64, String> {
     args.first()
         .and_then(|task_id| task_id.parse::<u64>().ok())
@@ -91,17 +163,11 @@ fn parse_task_id(args: CommandArgs) -> Result<u64, String> {
 }
 
 
-fn ls(_args: CommandArgs) -> CommandResult {
-    let dir = fs::root_dir().map_err(|e| e.to_string())?;
-    let output = dir
-        .map(|data| data.name())
-        .filter_map(|name| name.ok())
-        .filter_map(|name| {
-            name.to_str()
-                .map(|name| format!("{}\n", name))
-                .ok()
-        })
-        .collect::<String>();
-
-    Ok(CommandAction::Output(output))
+/// Binds the current directory of the terminal to the command.
+fn with_cwd(
+    cwd: &Rc<RefCell<Path>>,
+    f: fn(&RefCell<Path>, CommandArgs) -> CommandResult,
+) -> impl Fn(CommandArgs) -> CommandResult {
+    let cwd = Rc::clone(cwd);
+    move |args| f(&cwd, args)
 }
@@ -0,0 +1,152 @@
+use alloc::format;
+use alloc::string::{String, ToString};
+use core::fmt::Write;
+
+use kernel_lib::acpi;
+use kernel_lib::acpi::madt::MadtEntry;
+use kernel_lib::acpi::power;
+use kernel_lib::layers::text::command::{CommandAction, CommandArgs, CommandResult};
+
+/// Lists the ACPI tables, or shows the fields of the table given by its signature.
+pub(crate) fn acpitables(args: CommandArgs) -> CommandResult {
+    let output = match args.first() {
+        None => list_tables(),
+        Some(&"APIC" | &"MADT") => madt(),
+        Some(&"HPET") => hpet(),
+        Some(&"MCFG") => mcfg(),
+        Some(&"FACS") => facs(),
+        Some(signature) => Err(format!("Unsupported table {signature}")),
+    }?;
+
+    Ok(CommandAction::output(output))
+}
+
+
+pub(crate) fn shutdown(_args: CommandArgs) -> CommandResult {
+    power::shutdown().map_err(|e| format!("{e:?}"))?;
+    Ok(CommandAction::output(""))
+}
+
+
+pub(crate) fn reboot(_args: CommandArgs) -> CommandResult {
+    power::reboot()
+}
+
+
+fn list_tables() -> Result<String, String> {
+    let mut output = format!(
+        "{:<4} {:>6} {:>3} {:<6} {:<8} {:>8} SUM",
+        "SIG", "LENGTH", "REV", "OEMID", "TABLEID", "OEMREV"
+    );
+    for table in acpi::tables().map_err(|e| format!("{e:?}"))? {
+        let _ = write!(
+            output,
+            "\n{:<4} {:>6} {:>3} {:<6} {:<8} {:>8} {}",
+            table.signature(),
+            table.length(),
+            table.revision(),
+            table.oem_id(),
+            table.oem_table_id(),
+            table.oem_revision(),
+            if table.valid_checksum() { "ok" } else { "NG" }
+        );
+    }
+
+    if let Ok(facs) = acpi::find_facs() {
+        let _ = write!(output, "\n{:<4} {:>6} {:>3}", "FACS", facs.length(), facs.version());
+    }
+
+    Ok(output)
+}
+
+
+fn madt() -> Result<String, String> {
+    let madt = acpi::find_madt().map_err(|e| format!("{e:?}"))?;
+
+    let mut output = format!("Local APIC Address: 0x{:X}", madt.local_apic_address());
+    for entry in madt.entries() {
+        let _ = match entry {
+            MadtEntry::LocalApic {
+                processor_id,
+                apic_id,
+                flags,
+            } => write!(
+                output,
+                "\nLocal APIC: processor={processor_id} id={apic_id} flags=0x{flags:X}"
+            ),
+            MadtEntry::IoApic(io_apic) => write!(
+                output,
+                "\nI/O APIC: id={} address=0x{:X} gsi_base={}",
+                io_apic.id, io_apic.address, io_apic.global_system_interrupt_base
+            ),
+            MadtEntry::InterruptSourceOverride(source_override) => write!(
+                output,
+                "\nOverride: irq={} gsi={} flags=0x{:X}",
+                source_override.source,
+                source_override.global_system_interrupt,
+                source_override.flags
+            ),
+            MadtEntry::LocalApicAddressOverride { address } => {
+                write!(output, "\nLocal APIC Address Override: 0x{address:X}")
+            }
+            MadtEntry::Unknown(entry_type) => write!(output, "\nType {entry_type}"),
+        };
+    }
+
+    Ok(output)
+}
+
+
+fn hpet() -> Result<String, String> {
+    let hpet = acpi::find_hpet().map_err(|e| format!("{e:?}"))?;
+    let id = hpet.event_timer_block_id();
+
+    Ok(format!(
+        "Base Address: 0x{:X}\nNumber: {}\nMinimum Tick: {}\nComparators: {}\n64-bit Counter: {}\n\
+         Legacy Replacement: {}\nVendor: 0x{:04X}",
+        hpet.base_address(),
+        hpet.hpet_number(),
+        hpet.minimum_tick(),
+        id.comparators(),
+        id.is_64bit_counter(),
+        id.is_legacy_replacement_capable(),
+        id.pci_vendor_id()
+    ))
+}
+
+
+fn mcfg() -> Result<String, String> {
+    let mcfg = acpi::find_mcfg().map_err(|e| format!("{e:?}"))?;
+
+    let mut output = String::new();
+    for entry in mcfg.entries() {
+        let _ = writeln!(
+            output,
+            "Base Address: 0x{:X} segment={} bus={}-{}",
+            entry.base_address, entry.segment_group, entry.start_bus, entry.end_bus
+        );
+    }
+    output.pop();
+
+    if output.is_empty() {
+        return Ok("No Entries".to_string());
+    }
+    Ok(output)
+}
+
+
+fn facs() -> Result<String, String> {
+    let facs = acpi::find_facs().map_err(|e| format!("{e:?}"))?;
+
+    Ok(format!(
+        "Address: 0x{:X}\nLength: {}\nVersion: {}\nHardware Signature: 0x{:X}\n\
+         Waking Vector: 0x{:X}\nGlobal Lock: 0x{:X}\nFlags: 0x{:X}",
+        facs.addr(),
+        facs.length(),
+        facs.version(),
+        facs.hardware_signature(),
+        facs.firmware_waking_vector(),
+        facs.global_lock(),
+        facs.flags()
+    ))
+}
@@ -0,0 +1,87 @@
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::cell::RefCell;
+
+use kernel_lib::fs;
+use kernel_lib::fs::path::Path;
+use kernel_lib::layers::text::command::{CommandAction, CommandArgs, CommandResult};
+use kernel_lib::task::dispatch;
+
+use crate::layers::print_terminal;
+use crate::syscall::release_app_resources;
+
+/// Usage: `run <file> [args...]`
+pub(crate) fn run(cwd: &RefCell<Path>, args: CommandArgs) -> CommandResult {
+    let name = args
+        .first()
+        .ok_or("Must be specify file path".to_string())?;
+    let path = cwd.borrow().join(name);
+
+    spawn(&path, args)
+}
+
+
+/// Runs the application named as the command.
+///
+/// The name is looked up in the current directory and then in the root directory,
+/// with and without the `.elf` extension.
+pub(crate) fn run_if_exists(cwd: &RefCell<Path>, args: CommandArgs) -> CommandResult {
+    let name = args[0];
+    let path = candidates(&cwd.borrow(), name)
+        .into_iter()
+        .find(|path| {
+            fs::find(path.as_str())
+                .map(|entry| !entry.is_dir())
+                .unwrap_or(false)
+        })
+        .ok_or(format!("No such command `{name}`"))?;
+
+    spawn(&path, args)
+}
+
+
+fn spawn(path: &Path, args: CommandArgs) -> CommandResult {
+    let mut argv: Vec<&str> = Vec::with_capacity(args.len());
+    argv.push(path.as_str());
+    argv.extend_from_slice(&args[1..]);
+
+    let name = path.to_string();
+    let task_id = fs::spawn_elf(path.as_str(), &argv, move |task_id, exit_code| {
+        // Applications terminated by exceptions exit without a system call releasing them.
+        release_app_resources(task_id);
+
+        let message = format!("[{task_id}] {name} exited with code {exit_code}");
+        dispatch(move || print_terminal(&message));
+    })
+    .map_err(|e| e.to_string())?;
+
+    Ok(CommandAction::output(format!("[{task_id}] {path}")))
+}
+
+
+fn candidates(cwd: &Path, name: &str) -> Vec<Path> {
+    let mut names: Vec<String> = Vec::with_capacity(2);
+    names.push(name.to_string());
+    if !name
+        .to_ascii_lowercase()
+        .ends_with(".elf")
+    {
+        names.push(format!("{name}.elf"));
+    }
+
+    let mut paths: Vec<Path> = names
+        .iter()
+        .map(|name| cwd.join(name))
+        .collect();
+
+    if !name.contains('/') {
+        paths.extend(
+            names
+                .iter()
+                .map(|name| Path::new(name)),
+        );
+    }
+
+    paths
+}
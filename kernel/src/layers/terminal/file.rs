@@ -0,0 +1,240 @@
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::cell::RefCell;
+use core::fmt::Write;
+
+use kernel_lib::fs;
+use kernel_lib::fs::path::Path;
+use kernel_lib::fs::volume::dir_entry::{
+    DirEntry, ATTR_ARCHIVE, ATTR_DIRECTORY, ATTR_HIDDEN, ATTR_READ_ONLY, ATTR_SYSTEM,
+};
+use kernel_lib::layers::text::command::{CommandAction, CommandArgs, CommandResult};
+
+const HEXDUMP_BYTES_PER_LINE: usize = 8;
+
+
+pub(crate) fn ls(cwd: &RefCell<Path>, args: CommandArgs) -> CommandResult {
+    let path = cwd
+        .borrow()
+        .join(args.first().unwrap_or(&"."));
+
+    if !path.is_root() {
+        let entry = fs::find(path.as_str()).map_err(|e| e.to_string())?;
+        if !entry.is_dir() {
+            return Ok(CommandAction::output(entry.name()));
+        }
+    }
+
+    let mut output = fs::read_dir(path.as_str())
+        .map_err(|e| e.to_string())?
+        .iter()
+        .filter(|entry| entry.name() != "." && entry.name() != "..")
+        .map(|entry| {
+            if entry.is_dir() {
+                format!("{}/\n", entry.name())
+            } else {
+                format!("{}\n", entry.name())
+            }
+        })
+        .collect::<String>();
+
+    output.pop();
+
+    Ok(CommandAction::Output(output))
+}
+
+
+pub(crate) fn cd(cwd: &RefCell<Path>, args: CommandArgs) -> CommandResult {
+    let path = cwd
+        .borrow()
+        .join(args.first().unwrap_or(&"/"));
+
+    if !path.is_root() {
+        let entry = fs::find(path.as_str()).map_err(|e| e.to_string())?;
+        if !entry.is_dir() {
+            return Err(format!("Not a directory: {path}"));
+        }
+    }
+
+    cwd.replace(path);
+    Ok(CommandAction::output(""))
+}
+
+
+pub(crate) fn pwd(cwd: &RefCell<Path>, _args: CommandArgs) -> CommandResult {
+    Ok(CommandAction::output(cwd.borrow().to_string()))
+}
+
+
+pub(crate) fn cat(cwd: &RefCell<Path>, args: CommandArgs) -> CommandResult {
+    let buff = read_file(cwd, args)?;
+
+    let mut text = String::with_capacity(buff.len());
+    for b in buff.iter().filter(|b| **b != b'\r') {
+        match *b {
+            b'\t' => text.push_str("    "),
+            b'\n' => text.push('\n'),
+            b if b.is_ascii_graphic() || b == b' ' => text.push(b as char),
+            _ => text.push('.'),
+        }
+    }
+
+    Ok(CommandAction::paginate(text))
+}
+
+
+/// Usage: `hexdump <file> [offset] [len]`
+///
+/// The offset and length accept both decimal and `0x` prefixed hexadecimal.
+pub(crate) fn hexdump(cwd: &RefCell<Path>, args: CommandArgs) -> CommandResult {
+    let buff = read_file(cwd, args)?;
+
+    let offset = args
+        .get(1)
+        .map(|offset| parse_number(offset))
+        .transpose()?
+        .unwrap_or(0)
+        .min(buff.len());
+    let len = args
+        .get(2)
+        .map(|len| parse_number(len))
+        .transpose()?
+        .unwrap_or(buff.len())
+        .min(buff.len() - offset);
+
+    let mut output = String::new();
+    for (i, line) in buff[offset..offset + len]
+        .chunks(HEXDUMP_BYTES_PER_LINE)
+        .enumerate()
+    {
+        write!(output, "{:08X} ", offset + i * HEXDUMP_BYTES_PER_LINE).unwrap();
+
+        for b in line {
+            write!(output, " {b:02X}").unwrap();
+        }
+        for _ in line.len()..HEXDUMP_BYTES_PER_LINE {
+            output.push_str("   ");
+        }
+
+        output.push_str("  ");
+        output.extend(line.iter().map(|b| {
+            if b.is_ascii_graphic() || *b == b' ' {
+                *b as char
+            } else {
+                '.'
+            }
+        }));
+        output.push('\n');
+    }
+
+    Ok(CommandAction::paginate(output))
+}
+
+
+pub(crate) fn stat(cwd: &RefCell<Path>, args: CommandArgs) -> CommandResult {
+    let path = resolve_path(cwd, args)?;
+    let entry = fs::find(path.as_str()).map_err(|e| e.to_string())?;
+    let clusters = if entry.first_cluster() == 0 {
+        Vec::new()
+    } else {
+        fs::cluster_chain(entry.first_cluster()).map_err(|e| e.to_string())?
+    };
+
+    let accessed = entry.accessed();
+    let output = format!(
+        "File: {path}\nShort name: {}\nSize: {} bytes\nAttributes: {}\nClusters: {}\nCreated: {}\nModified: {}\nAccessed: {:04}-{:02}-{:02}",
+        entry.short_name(),
+        entry.file_size(),
+        attributes(&entry),
+        cluster_ranges(&clusters),
+        entry.created(),
+        entry.modified(),
+        accessed.year(),
+        accessed.month(),
+        accessed.day()
+    );
+
+    Ok(CommandAction::paginate(output))
+}
+
+
+fn read_file(cwd: &RefCell<Path>, args: CommandArgs) -> Result<Vec<u8>, String> {
+    let path = resolve_path(cwd, args)?;
+
+    fs::read_file(path.as_str()).map_err(|e| e.to_string())
+}
+
+
+fn resolve_path(cwd: &RefCell<Path>, args: CommandArgs) -> Result<Path, String> {
+    args.first()
+        .map(|path| cwd.borrow().join(path))
+        .ok_or("Must be specify file path".to_string())
+}
+
+
+fn parse_number(number: &str) -> Result<usize, String> {
+    let parsed = if let Some(hex) = number
+        .strip_prefix("0x")
+        .or_else(|| number.strip_prefix("0X"))
+    {
+        usize::from_str_radix(hex, 16)
+    } else {
+        number.parse::<usize>()
+    };
+
+    parsed.map_err(|_| format!("Invalid number `{number}`"))
+}
+
+
+fn attributes(entry: &DirEntry) -> String {
+    let attr = entry.attr();
+    let names = [
+        (ATTR_READ_ONLY, "READ_ONLY"),
+        (ATTR_HIDDEN, "HIDDEN"),
+        (ATTR_SYSTEM, "SYSTEM"),
+        (ATTR_DIRECTORY, "DIRECTORY"),
+        (ATTR_ARCHIVE, "ARCHIVE"),
+    ]
+    .iter()
+    .filter(|(bit, _)| attr & bit != 0)
+    .map(|(_, name)| *name)
+    .collect::<Vec<&str>>()
+    .join(" ");
+
+    format!("0x{attr:02X} {names}")
+}
+
+
+/// Formats the chain as ranges of contiguous clusters such as `3-7 12 15-16`.
+fn cluster_ranges(clusters: &[u32]) -> String {
+    if clusters.is_empty() {
+        return "none".to_string();
+    }
+
+    let mut ranges = Vec::new();
+    let mut start = clusters[0];
+    let mut end = clusters[0];
+    for cluster in clusters[1..].iter() {
+        if *cluster == end + 1 {
+            end = *cluster;
+        } else {
+            ranges.push((start, end));
+            start = *cluster;
+            end = *cluster;
+        }
+    }
+    ranges.push((start, end));
+
+    ranges
+        .iter()
+        .map(|(start, end)| {
+            if start == end {
+                format!("{start}")
+            } else {
+                format!("{start}-{end}")
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(" ")
+}
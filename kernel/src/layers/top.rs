@@ -0,0 +1,88 @@
+use alloc::format;
+use alloc::string::String;
+use core::time::Duration;
+
+use common_lib::math::size::Size;
+use common_lib::math::vector::Vector2D;
+use common_lib::transform::transform2d::Transform2D;
+use kernel_lib::error::KernelResult;
+use kernel_lib::gop;
+use kernel_lib::gop::pixel::pixel_color::PixelColor;
+use kernel_lib::layers::layer_key::LayerKey;
+use kernel_lib::layers::text::{config, TextLayer};
+use kernel_lib::layers::window::WindowLayer;
+use kernel_lib::layers::LAYERS;
+use kernel_lib::task;
+use kernel_lib::task::info::TaskInfo;
+use kernel_lib::timer::uptime_nanos;
+
+use crate::layers::{TOP_TEXT_LAYER_KEY, TOP_WINDOW_LAYER_KEY};
+
+const COLUMNS: usize = 36;
+const ROWS: usize = 10;
+
+
+pub(crate) fn top_window() -> KernelResult<LayerKey> {
+    let pos = Vector2D::new(500, 280);
+    let size = Size::new(COLUMNS * 8 + 6, ROWS * 16 + 24 + 10);
+    let transform = Transform2D::new(pos, size);
+
+    Ok(WindowLayer::new_dark_color("Top", transform)
+        .then_add(|_| top_text())?
+        .into_enum()
+        .into_layer_key(TOP_WINDOW_LAYER_KEY))
+}
+
+
+/// Redraws the window with the current snapshots of the tasks.
+pub(crate) fn update_top() {
+    let text = top_text_of(&task::tasks(), Duration::from_nanos(uptime_nanos()));
+
+    LAYERS
+        .lock()
+        .update_layer(TOP_TEXT_LAYER_KEY, |layer| {
+            layer
+                .require_text()
+                .unwrap()
+                .replace(&text)
+                .unwrap();
+        })
+        .unwrap();
+}
+
+
+fn top_text() -> LayerKey {
+    let config = config::Builder::new()
+        .foreground(PixelColor::white())
+        .background(PixelColor::black())
+        .build();
+
+    TextLayer::new(gop::config(), Vector2D::zeros(), Size::new(COLUMNS, ROWS), config)
+        .unwrap()
+        .into_enum()
+        .into_layer_key(TOP_TEXT_LAYER_KEY)
+}
+
+
+fn top_text_of(tasks: &[TaskInfo], uptime: Duration) -> String {
+    let mut text = format!("up {}s  tasks {}\n", uptime.as_secs(), tasks.len());
+    text.push_str(&format!(
+        "{:>3} {:<8} {:>7} {:>5}{:>5}{:>5}",
+        "ID", "NAME", "TIME", "1s%", "5s%", "15s%"
+    ));
+
+    for task in tasks {
+        let [one, five, fifteen] = task.loads;
+        text.push_str(&format!(
+            "\n{:>3} {:<8.8} {:>7} {:>5}{:>5}{:>5}",
+            task.id,
+            task.name,
+            format!("{}.{:02}", task.cpu_time.as_secs(), task.cpu_time.subsec_millis() / 10),
+            one / 10,
+            five / 10,
+            fifteen / 10
+        ));
+    }
+
+    text
+}
@@ -19,13 +19,15 @@ use uefi::table::boot::MemoryMapIter;
 
 use allocate::init_alloc;
 use common_lib::frame_buffer::FrameBufferConfig;
-use kernel_lib::{fs, serial_println};
+use kernel_lib::timer::rtc;
+use kernel_lib::timer::TickSource;
+use kernel_lib::{acpi, backtrace, fs, serial_println};
 
-use crate::apic::TIMER_FREQ;
 use crate::gdt::init_gdt;
 use crate::interrupt::init_idt;
 use crate::layers::init_layers;
 use crate::paging::init_paging_table;
+use crate::syscall::init_syscall;
 use crate::usb::mouse::MouseSubscriber;
 use crate::usb::xhci::start_xhci_host_controller;
 use crate::usb::{enable_msi, serial_bus_usb_devices};
@@ -38,6 +40,7 @@ mod interrupt;
 mod layers;
 mod paging;
 mod qemu;
+mod syscall;
 mod task;
 #[cfg(test)]
 mod test_runner;
@@ -53,19 +56,36 @@ pub extern "sysv64" fn kernel_main(
     memory_map: &MemoryMapIter<'static>,
     rsdp: &Option<*const c_void>,
     fat_volume: *mut u8,
+    kernel_elf: *const u8,
+    kernel_elf_len: usize,
 ) {
+    let kernel_elf = unsafe { core::slice::from_raw_parts(kernel_elf, kernel_elf_len) };
+    if let Err(e) = backtrace::init(kernel_elf) {
+        serial_println!("Backtraces will not be symbolized: {:?}", e);
+    }
+
     init_gdt();
 
     init_idt().unwrap();
 
+    init_syscall();
+
     init_paging_table();
 
     init_alloc(memory_map.clone()).unwrap();
     init_layers(*frame_buffer_config).unwrap();
 
-    apic::start_timer(*rsdp, TIMER_FREQ).unwrap();
+    acpi::init(*rsdp).unwrap();
+    if let Err(e) = apic::init_apic() {
+        // Without the I/O APIC, the ticks can only come from the local APIC timer.
+        serial_println!("Continuing without the I/O APIC: {:?}", e);
+    }
+    let tick_source = apic::start_timer(TickSource::Hpet).unwrap();
+    serial_println!("Tick Source: {:?}", tick_source);
+    rtc::init_wall_clock();
+    serial_println!("Date: {}", rtc::now());
 
-    fs::init(fat_volume);
+    fs::init(fat_volume).unwrap();
 
     #[cfg(test)]
     test_main();
@@ -89,6 +109,7 @@ pub extern "sysv64" fn kernel_main(
 #[cfg(not(test))]
 fn panic(info: &PanicInfo) -> ! {
     serial_println!("{}", info);
+    backtrace::print_backtrace();
 
     common_lib::assembly::hlt_forever();
 }
@@ -107,5 +128,6 @@ fn panic(info: &PanicInfo) -> ! {
 fn on_oom(layout: core::alloc::Layout) -> ! {
     println!("Failed Heap Allocate! {:?}", layout);
     serial_println!("Failed Heap Allocate! {:?}", layout);
+    serial_println!("{}", allocate::memory_stats());
     common_lib::assembly::hlt_forever();
 }
@@ -0,0 +1,345 @@
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::mem::size_of;
+use core::sync::atomic::{AtomicU64, Ordering};
+use core::time::Duration;
+
+use common_lib::event::AppEvent;
+use common_lib::math::size::Size;
+use common_lib::math::vector::Vector2D;
+use common_lib::syscall::{
+    Syscall, SyscallError, SyscallResult, OPEN_CREATE, OPEN_READ, OPEN_TRUNCATE, OPEN_WRITE,
+    STDERR, STDIN, STDOUT,
+};
+use common_lib::transform::transform2d::Transform2D;
+use kernel_lib::error::{FsReason, KernelError};
+use kernel_lib::fs;
+use kernel_lib::fs::path::Path;
+use kernel_lib::layers::window::WindowLayer;
+use kernel_lib::layers::LAYERS;
+use kernel_lib::paging::address_space::USER_SPACE_START;
+use kernel_lib::sync::preemptive_mutex::PreemptiveMutex;
+use kernel_lib::syscall::SYSCALL_TABLE;
+use kernel_lib::task;
+use kernel_lib::task::app::exit_app;
+use kernel_lib::timer::{ticks_to_duration, TIME_HANDLE_MANAGER};
+
+use crate::layers::{print_terminal, MOUSE_LAYER_KEY};
+
+/// Buffers and strings passed from applications are limited to this size.
+const MAX_USER_BUFF_LEN: u64 = 1 << 20;
+
+/// File descriptors below this are the standard streams.
+const FIRST_FILE_DESCRIPTOR: u64 = 3;
+
+
+/// Files opened by each task, keyed by the task id.
+static FILES: PreemptiveMutex<BTreeMap<u64, BTreeMap<u64, OpenFile>>> =
+    PreemptiveMutex::new(BTreeMap::new());
+
+
+/// The task owning each application window, keyed by the layer key.
+static WINDOW_OWNERS: PreemptiveMutex<BTreeMap<String, u64>> =
+    PreemptiveMutex::new(BTreeMap::new());
+
+
+struct OpenFile {
+    path: String,
+    offset: usize,
+    flags: u64,
+}
+
+
+pub(crate) fn init_syscall() {
+    unsafe {
+        SYSCALL_TABLE.register(Syscall::Write, write);
+        SYSCALL_TABLE.register(Syscall::Open, open);
+        SYSCALL_TABLE.register(Syscall::Read, read);
+        SYSCALL_TABLE.register(Syscall::Close, close);
+        SYSCALL_TABLE.register(Syscall::Exit, exit);
+        SYSCALL_TABLE.register(Syscall::GetTime, get_time);
+        SYSCALL_TABLE.register(Syscall::Sleep, sleep);
+        SYSCALL_TABLE.register(Syscall::OpenWindow, open_window);
+        SYSCALL_TABLE.register(Syscall::ReadEvent, read_event);
+
+        kernel_lib::syscall::init();
+    }
+}
+
+
+fn write(args: &[u64; 6]) -> SyscallResult {
+    let result = user_buff(args[1], args[2]).and_then(|buff| match args[0] {
+        STDOUT | STDERR => {
+            let message = String::from_utf8_lossy(buff).to_string();
+            task::dispatch(move || print_terminal(&message));
+            Ok(buff.len() as u64)
+        }
+        fd => {
+            // Copied before locking so that a fault on the buffer doesn't leave the lock held.
+            let buff = buff.to_vec();
+            with_file(fd, |file| {
+                if file.flags & OPEN_WRITE == 0 {
+                    return Err(SyscallError::BadDescriptor);
+                }
+
+                fs::write_at(&file.path, file.offset, &buff).map_err(syscall_error)?;
+
+                file.offset += buff.len();
+                Ok(buff.len() as u64)
+            })
+        }
+    });
+
+    result.into()
+}
+
+
+fn open(args: &[u64; 6]) -> SyscallResult {
+    let result = user_str(args[0], args[1]).and_then(|path| {
+        let flags = args[2];
+        let writable = flags & OPEN_WRITE != 0;
+        if flags & (OPEN_READ | OPEN_WRITE) == 0
+            || (!writable && flags & (OPEN_CREATE | OPEN_TRUNCATE) != 0)
+        {
+            return Err(SyscallError::InvalidArgument);
+        }
+
+        let path = Path::new(path);
+        match fs::find(path.as_str()) {
+            Ok(entry) if entry.is_dir() => return Err(SyscallError::InvalidArgument),
+            Ok(_) if flags & OPEN_TRUNCATE != 0 => {
+                fs::truncate(path.as_str(), 0).map_err(syscall_error)?;
+            }
+            Ok(_) => {}
+            Err(_) if flags & OPEN_CREATE != 0 => {
+                fs::create_file(path.as_str()).map_err(syscall_error)?;
+            }
+            Err(e) => return Err(syscall_error(e)),
+        }
+
+        let task_id = current_task_id()?;
+        let mut files = FILES.lock();
+        let table = files.entry(task_id).or_default();
+        let fd = table
+            .keys()
+            .last()
+            .map(|fd| fd + 1)
+            .unwrap_or(FIRST_FILE_DESCRIPTOR);
+        table.insert(
+            fd,
+            OpenFile {
+                path: path.to_string(),
+                offset: 0,
+                flags,
+            },
+        );
+
+        Ok(fd)
+    });
+
+    result.into()
+}
+
+
+fn read(args: &[u64; 6]) -> SyscallResult {
+    let result = user_buff_mut(args[1], args[2]).and_then(|buff| match args[0] {
+        STDIN => Err(SyscallError::NotSupported),
+        fd => {
+            let mut content = Vec::new();
+            with_file(fd, |file| {
+                if file.flags & OPEN_READ == 0 {
+                    return Err(SyscallError::BadDescriptor);
+                }
+
+                content = fs::read_file(&file.path).map_err(syscall_error)?;
+                let start = file.offset.min(content.len());
+                let len = buff.len().min(content.len() - start);
+                content = content.split_off(start);
+                content.truncate(len);
+
+                file.offset += len;
+                Ok(len as u64)
+            })?;
+
+            // Copied after unlocking so that a fault on the buffer doesn't leave the lock held.
+            buff[..content.len()].copy_from_slice(&content);
+            Ok(content.len() as u64)
+        }
+    });
+
+    result.into()
+}
+
+
+fn close(args: &[u64; 6]) -> SyscallResult {
+    let result = current_task_id().and_then(|task_id| {
+        FILES
+            .lock()
+            .get_mut(&task_id)
+            .and_then(|table| table.remove(&args[0]))
+            .map(|_| 0)
+            .ok_or(SyscallError::BadDescriptor)
+    });
+
+    result.into()
+}
+
+
+/// Closes all the files the task opened and detaches its windows.
+pub(crate) fn release_app_resources(task_id: u64) {
+    FILES.lock().remove(&task_id);
+    WINDOW_OWNERS
+        .lock()
+        .retain(|_, owner| *owner != task_id);
+}
+
+
+/// Returns the id of the task which opened the window.
+pub(crate) fn window_owner(layer_key: &str) -> Option<u64> {
+    WINDOW_OWNERS
+        .lock()
+        .get(layer_key)
+        .copied()
+}
+
+
+fn exit(args: &[u64; 6]) -> SyscallResult {
+    if let Ok(task_id) = current_task_id() {
+        release_app_resources(task_id);
+    }
+
+    unsafe { exit_app(args[0] as i32) }
+}
+
+
+fn get_time(_args: &[u64; 6]) -> SyscallResult {
+    SyscallResult::ok(ticks_to_duration(TIME_HANDLE_MANAGER.ticks()).as_millis() as u64)
+}
+
+
+fn sleep(args: &[u64; 6]) -> SyscallResult {
+    task::sleep_for(Duration::from_millis(args[0]))
+        .map(|_| 0)
+        .map_err(|_| SyscallError::Failed)
+        .into()
+}
+
+
+fn open_window(args: &[u64; 6]) -> SyscallResult {
+    static WINDOW_ID: AtomicU64 = AtomicU64::new(0);
+
+    let result = user_str(args[4], args[5]).and_then(|title| {
+        let pos = Vector2D::new(args[0] as usize, args[1] as usize);
+        let size = Size::new(args[2] as usize, args[3] as usize);
+        let task_id = current_task_id()?;
+        let id = WINDOW_ID.fetch_add(1, Ordering::Relaxed);
+        let key = format!("App Window {id}");
+        WINDOW_OWNERS
+            .lock()
+            .insert(key.clone(), task_id);
+
+        let window = WindowLayer::new_default_color(title, Transform2D::new(pos, size))
+            .into_enum()
+            .into_layer_key(&key);
+
+        let mut layers = LAYERS.lock();
+        layers.new_layer(window);
+        layers
+            .bring_to_front(MOUSE_LAYER_KEY)
+            .and_then(|_| layers.update_layer(&key, |_| {}))
+            .map_err(syscall_error)?;
+
+        Ok(id)
+    });
+
+    result.into()
+}
+
+
+fn read_event(args: &[u64; 6]) -> SyscallResult {
+    let (ptr, len) = (args[0], args[1] as usize);
+    let bytes = args[1].saturating_mul(size_of::<AppEvent>() as u64);
+    let result = check_user_buff(ptr, bytes).and_then(|_| {
+        if len == 0 {
+            return Ok(0);
+        }
+
+        let mut events = Vec::with_capacity(len);
+        while events.is_empty() {
+            let message = task::receive_message().map_err(|_| SyscallError::Failed)?;
+            events.extend(message.into_app_event());
+        }
+        while events.len() < len {
+            match task::try_receive_message() {
+                Ok(Some(message)) => events.extend(message.into_app_event()),
+                _ => break,
+            }
+        }
+
+        let user_events = ptr as *mut AppEvent;
+        for (i, event) in events.iter().enumerate() {
+            unsafe { user_events.add(i).write_unaligned(*event) };
+        }
+        Ok(events.len() as u64)
+    });
+
+    result.into()
+}
+
+
+fn with_file(
+    fd: u64,
+    f: impl FnOnce(&mut OpenFile) -> Result<u64, SyscallError>,
+) -> Result<u64, SyscallError> {
+    let task_id = current_task_id()?;
+    let mut files = FILES.lock();
+    let file = files
+        .get_mut(&task_id)
+        .and_then(|table| table.get_mut(&fd))
+        .ok_or(SyscallError::BadDescriptor)?;
+
+    f(file)
+}
+
+
+fn current_task_id() -> Result<u64, SyscallError> {
+    task::current_id().map_err(|_| SyscallError::Failed)
+}
+
+
+fn user_buff<'a>(ptr: u64, len: u64) -> Result<&'a [u8], SyscallError> {
+    check_user_buff(ptr, len)?;
+
+    Ok(unsafe { core::slice::from_raw_parts(ptr as *const u8, len as usize) })
+}
+
+
+fn user_buff_mut<'a>(ptr: u64, len: u64) -> Result<&'a mut [u8], SyscallError> {
+    check_user_buff(ptr, len)?;
+
+    Ok(unsafe { core::slice::from_raw_parts_mut(ptr as *mut u8, len as usize) })
+}
+
+
+fn user_str<'a>(ptr: u64, len: u64) -> Result<&'a str, SyscallError> {
+    core::str::from_utf8(user_buff(ptr, len)?).map_err(|_| SyscallError::InvalidArgument)
+}
+
+
+fn check_user_buff(ptr: u64, len: u64) -> Result<(), SyscallError> {
+    if ptr < USER_SPACE_START || MAX_USER_BUFF_LEN < len || ptr.checked_add(len).is_none() {
+        Err(SyscallError::InvalidArgument)
+    } else {
+        Ok(())
+    }
+}
+
+
+fn syscall_error(error: KernelError) -> SyscallError {
+    match error {
+        KernelError::FailedOperateFs(FsReason::NotFound(_)) => SyscallError::NotFound,
+        _ => SyscallError::Failed,
+    }
+}
@@ -1,52 +1,51 @@
-use kernel_lib::interrupt::asm::sti_and_hlt;
+use core::time::Duration;
+
 use kernel_lib::layers::LAYERS;
 use kernel_lib::task::priority_level::PriorityLevel;
-use kernel_lib::task::{dispatch, TASK_MANAGER};
+use kernel_lib::task::{dispatch, sleep_for, spawn, TASK_MANAGER};
+use kernel_lib::timer::{TIMER_FREQ, TIME_HANDLE_MANAGER};
 
+use crate::layers::clock::update_clock;
+use crate::layers::top::update_top;
 use crate::layers::{COUNT_TEXT_LAYER2_KEY, COUNT_TEXT_LAYER_KEY};
 use crate::task::idle::idle;
 
 mod idle;
 pub mod task_message_iter;
 
+const COUNT_INTERVAL: Duration = Duration::from_millis(50);
 
-unsafe fn addr(f: extern "sysv64" fn(u64, u64)) -> u64 {
-    f as *const () as u64
-}
+/// The load averages are sampled, and the top and clock windows are redrawn every second.
+const LOAD_SAMPLE_INTERVAL: usize = TIMER_FREQ as usize;
 
 
 pub unsafe fn init() {
     TASK_MANAGER.init();
 
-    TASK_MANAGER.new_task(PriorityLevel::new(1), addr(window_count_task1), 0x30);
-    TASK_MANAGER.new_task(PriorityLevel::new(1), addr(window_count_task2), 0x30);
+    spawn("count1", PriorityLevel::new(1), || window_count(COUNT_TEXT_LAYER_KEY)).unwrap();
+    spawn("count2", PriorityLevel::new(1), || window_count(COUNT_TEXT_LAYER2_KEY)).unwrap();
+    spawn("idle", PriorityLevel::new(0), idle).unwrap();
 
-    TASK_MANAGER.new_task(PriorityLevel::new(0), addr(idle), 0x00);
+    TIME_HANDLE_MANAGER.entry(LOAD_SAMPLE_INTERVAL, || {
+        TASK_MANAGER.update_loads();
+        dispatch(update_top);
+        dispatch(update_clock);
+    });
 }
 
 
-macro_rules! count_task {
-    ($num: literal, $key: expr) => {
-        paste::paste! {
-            extern "sysv64" fn [< window_count_task $num >](_id: u64, _data: u64) {
-                let mut count: usize = 0;
-                loop {
-                    count += 1;
-                    dispatch(move ||{
-                        update_count(count, $key);
-                    });
-                    sti_and_hlt();
-                }
-            }
-        }
-    };
+fn window_count(key: &'static str) {
+    let mut count: usize = 0;
+    loop {
+        count += 1;
+        dispatch(move || {
+            update_count(count, key);
+        });
+        sleep_for(COUNT_INTERVAL).unwrap();
+    }
 }
 
 
-count_task!(1, COUNT_TEXT_LAYER_KEY);
-count_task!(2, COUNT_TEXT_LAYER2_KEY);
-
-
 #[inline(always)]
 fn update_count(count: usize, key: &str) {
     LAYERS
@@ -1,17 +1,14 @@
-use kernel_lib::interrupt::asm::{cli, sti};
+use kernel_lib::task;
 use kernel_lib::task::message::TaskMessage;
 
-use crate::task::TASK_MANAGER;
-
-pub struct TaskMessageIter {
-    task_id: u64,
-}
+/// Yields the messages sent to the calling task, sleeping while there are none.
+pub struct TaskMessageIter;
 
 
 impl TaskMessageIter {
     #[inline(always)]
-    pub const fn new(task_id: u64) -> Self {
-        Self { task_id }
+    pub const fn new() -> Self {
+        Self
     }
 }
 
@@ -20,23 +17,6 @@ impl Iterator for TaskMessageIter {
     type Item = TaskMessage;
 
     fn next(&mut self) -> Option<Self::Item> {
-        cli();
-
-        let mut value = unsafe { TASK_MANAGER.receive_message_at(self.task_id) };
-
-        while value.is_none() {
-            unsafe {
-                TASK_MANAGER
-                    .sleep_at(self.task_id)
-                    .unwrap()
-            };
-
-            cli();
-
-            value = unsafe { TASK_MANAGER.receive_message_at(self.task_id) };
-        }
-
-        sti();
-        value
+        task::receive_message().ok()
     }
 }
@@ -1,36 +1,27 @@
 use kernel_lib::fs;
-use kernel_lib::simple_fat::dir::entry::short::ShortDirEntryReadable;
 
 #[test_case]
 fn it_read_root_dir() {
-    let mut dir = fs::root_dir().unwrap();
-
-    dir.next();
-
-    let hello_txt_file = dir
-        .next()
-        .unwrap()
-        .into_regular_file()
-        .unwrap()
-        .name()
-        .unwrap();
-
-    assert_eq!(
-        hello_txt_file
-            .to_str()
-            .unwrap(),
-        "HELLO.TXT"
-    );
+    let root_dir = fs::read_dir("/").unwrap();
+
+    assert_eq!(root_dir[0].name(), "HELLO.TXT");
 }
 
 
 #[test_case]
 fn it_exists_hlt_elf() {
-    let hlt = fs::open_file("HLT.ELF")
-        .unwrap()
-        .name()
-        .unwrap();
+    let hlt = fs::find("/HLT.ELF").unwrap();
+
+    assert_eq!(hlt.name(), "HLT.ELF");
+}
+
+
+#[test_case]
+fn it_write_and_read_file() {
+    fs::write_file("/test_write.txt", b"hello world").unwrap();
+
+    assert_eq!(fs::read_file("/test_write.txt").unwrap(), b"hello world");
 
-    let name = hlt.to_str().unwrap();
-    assert_eq!(name, "HLT.ELF");
+    fs::remove("/test_write.txt").unwrap();
+    assert!(fs::find("/test_write.txt").is_err());
 }
@@ -0,0 +1,43 @@
+use kernel_lib::paging::address_space::{AddressSpace, USER_SPACE_START};
+
+#[test_case]
+fn it_write_in_activated_address_space() {
+    let mut address_space = AddressSpace::new().unwrap();
+    let frame = address_space
+        .map_zeroed(USER_SPACE_START, true)
+        .unwrap();
+
+    address_space.with_activated(|_| unsafe {
+        (USER_SPACE_START as *mut u64).write_volatile(0xDEAD_BEEF);
+    });
+
+    assert_eq!(unsafe { (frame as *const u64).read_volatile() }, 0xDEAD_BEEF);
+}
+
+
+#[test_case]
+fn it_private_user_half() {
+    let mut first = AddressSpace::new().unwrap();
+    let mut second = AddressSpace::new().unwrap();
+    first
+        .map_zeroed(USER_SPACE_START, true)
+        .unwrap();
+    second
+        .map_zeroed(USER_SPACE_START, true)
+        .unwrap();
+
+    first.with_activated(|_| unsafe {
+        (USER_SPACE_START as *mut u64).write_volatile(1);
+    });
+    second.with_activated(|_| unsafe {
+        (USER_SPACE_START as *mut u64).write_volatile(2);
+    });
+
+    let read = |address_space: &mut AddressSpace| {
+        address_space.with_activated(|_| unsafe {
+            (USER_SPACE_START as *const u64).read_volatile()
+        })
+    };
+    assert_eq!(read(&mut first), 1);
+    assert_eq!(read(&mut second), 2);
+}
@@ -1,15 +1,19 @@
 use alloc::string::ToString;
 use core::fmt::Write;
 
+use common_lib::event::{Key, KeyEvent};
 use kernel_lib::layers::multiple_layer::LayerFindable;
 use kernel_lib::layers::terminal::TerminalLayer;
 use kernel_lib::layers::window::WindowLayer;
 use kernel_lib::layers::LAYERS;
+use kernel_lib::task;
+use kernel_lib::task::message::TaskMessage;
 use pci::class_driver::keyboard;
 use pci::class_driver::keyboard::driver::KeyboardDriver;
 use pci::class_driver::keyboard::Keycode;
 
 use crate::layers::KEYBOARD_TEXT;
+use crate::syscall::window_owner;
 
 pub fn build_keyboard_driver() -> KeyboardDriver {
     keyboard::builder::Builder::new()
@@ -18,7 +22,11 @@ pub fn build_keyboard_driver() -> KeyboardDriver {
 }
 
 
-fn keyboard_subscribe(_modifier_bits: u8, keycode: Keycode) {
+fn keyboard_subscribe(modifier_bits: u8, keycode: Keycode) {
+    if send_to_app_window(modifier_bits, keycode) {
+        return;
+    }
+
     LAYERS
         .lock()
         .update_active_layer(|layer| {
@@ -34,6 +42,25 @@ fn keyboard_subscribe(_modifier_bits: u8, keycode: Keycode) {
 }
 
 
+/// Sends the key to the task if the active window belongs to an application.
+fn send_to_app_window(modifier_bits: u8, keycode: Keycode) -> bool {
+    let owner = LAYERS
+        .lock()
+        .find_active_window_layer_ref()
+        .and_then(|layer| window_owner(layer.key()));
+
+    let key = match keycode {
+        Keycode::ArrowUp => Key::ArrowUp,
+        Keycode::ArrowDown => Key::ArrowDown,
+        Keycode::Ascii(c) => Key::Ascii(c),
+    };
+
+    owner.is_some_and(|task_id| {
+        task::send_message(task_id, TaskMessage::Key(KeyEvent::new(modifier_bits, key))).is_ok()
+    })
+}
+
+
 fn keyboard_text_box(keycode: Keycode, window: &mut WindowLayer) {
     if let Keycode::Ascii(c) = keycode {
         if let Some(text_box) = window.find_by_key_mut(KEYBOARD_TEXT) {
@@ -1,14 +1,18 @@
 use alloc::string::String;
 
+use common_lib::event::{MouseEvent, MOUSE_BUTTON_LEFT, MOUSE_BUTTON_MIDDLE, MOUSE_BUTTON_RIGHT};
 use common_lib::math::vector::Vector2D;
 use common_lib::transform::transform2d::Transformable2D;
 use kernel_lib::gop::pixel::pixel_color::PixelColor;
 use kernel_lib::layers::cursor::colors::CursorColors;
 use kernel_lib::layers::LAYERS;
+use kernel_lib::task;
+use kernel_lib::task::message::TaskMessage;
 use pci::class_driver::mouse::subscribable::MouseSubscribable;
 use pci::class_driver::mouse::MouseButton;
 
 use crate::layers::MOUSE_LAYER_KEY;
+use crate::syscall::window_owner;
 
 #[derive(Debug, Clone)]
 pub struct MouseSubscriber;
@@ -32,6 +36,7 @@ impl MouseSubscribable for MouseSubscriber {
     ) -> anyhow::Result<()> {
         update_cursor_layer(current_cursor, button)?;
         update_window_layer(prev_cursor, current_cursor, prev_button, button)?;
+        notify_app_window(current_cursor, prev_button, button);
 
         Ok(())
     }
@@ -85,6 +90,37 @@ fn update_window_layer(
 }
 
 
+/// Sends the button change to the task owning the window under the cursor.
+fn notify_app_window(
+    current_cursor: Vector2D<usize>,
+    prev_button: Option<MouseButton>,
+    button: Option<MouseButton>,
+) {
+    let buttons = button_bits(button);
+    if buttons == button_bits(prev_button) {
+        return;
+    }
+
+    if let Some(task_id) =
+        find_window_layer_key(&current_cursor).and_then(|key| window_owner(&key))
+    {
+        let event = MouseEvent::new(current_cursor.x(), current_cursor.y(), buttons);
+        let _ = task::send_message(task_id, TaskMessage::Mouse(event));
+    }
+}
+
+
+#[inline]
+fn button_bits(button: Option<MouseButton>) -> u8 {
+    match button {
+        Some(MouseButton::Button1) => MOUSE_BUTTON_LEFT,
+        Some(MouseButton::Button2) => MOUSE_BUTTON_RIGHT,
+        Some(MouseButton::Button3) => MOUSE_BUTTON_MIDDLE,
+        _ => 0,
+    }
+}
+
+
 #[inline(always)]
 fn find_window_layer_key(current_cursor: &Vector2D<usize>) -> Option<String> {
     LAYERS
@@ -1,6 +1,4 @@
 use kernel_lib::task::message::TaskMessage;
-use kernel_lib::task::TASK_MANAGER;
-use kernel_lib::timer::TIME_HANDLE_MANAGER;
 use pci::class_driver::mouse::driver::MouseDriver;
 use pci::class_driver::mouse::subscribable::MouseSubscribable;
 use pci::xhc::allocator::mikanos_pci_memory_allocator::MikanOSPciMemoryAllocator;
@@ -8,7 +6,6 @@ use pci::xhc::registers::external::{External, IdentityMapper};
 use pci::xhc::registers::memory_mapped_addr::MemoryMappedAddr;
 use pci::xhc::XhcController;
 
-use crate::apic::TIMER_200_MILLI_INTERVAL;
 use crate::task::task_message_iter::TaskMessageIter;
 use crate::usb::keyboard::build_keyboard_driver;
 
@@ -18,14 +15,11 @@ pub fn start_xhci_host_controller(
 ) -> anyhow::Result<()> {
     unsafe {
         crate::task::init();
-        TIME_HANDLE_MANAGER.entry(TIMER_200_MILLI_INTERVAL, || {
-            TASK_MANAGER.switch().unwrap();
-        });
     }
 
     let mut xhc_controller = start_xhc_controller(mmio_base_addr, mouse_subscriber)?;
 
-    let messages = TaskMessageIter::new(0);
+    let messages = TaskMessageIter::new();
     messages.for_each(|message| match message {
         TaskMessage::Xhci => {
             xhc_controller.process_all_events();
@@ -34,6 +28,8 @@ pub fn start_xhci_host_controller(
         TaskMessage::Dispatch(handler) => {
             handler();
         }
+
+        _ => {}
     });
 
     Ok(())
@@ -33,6 +33,12 @@ sudo mkdir -p mnt
 sudo mount -o loop ${img} mnt
 sudo mkdir -p mnt/EFI/BOOT
 sudo unzip scripts/fat_disk.zip -d scripts
+# アプリケーションをFATボリュームに追加します。
+sudo mkdir -p fat_mnt
+sudo mount -o loop scripts/fat_disk fat_mnt
+sudo cp target/app/debug/hlt.elf ./fat_mnt/HLT.ELF
+sudo umount fat_mnt
+sudo rm -r -f fat_mnt
 sudo cp scripts/fat_disk ./mnt/fat_disk
 sudo cp "$HOME"/workspace/mikanos-rs/target/x86_64-unknown-uefi/debug/bootloader.efi ./mnt/EFI/BOOT/BOOTX64.EFI
 sudo cp "$kernel" ./mnt/kernel.elf